@@ -3,6 +3,7 @@
 #include <stdint.h>
 #include <stdio.h>
 #include <stdlib.h>
+#include <string.h>
 
 #ifdef SOLANA
 #include "../../../solana/sdk/bpf/c/inc/solana_sdk.h"
@@ -10,84 +11,62 @@
 
 typedef unsigned char i8;
 
-void inplace_reverse(i8* str, uint16_t len);
-i8* pad_int(i8* out, int x);
+/* pointer and length of a dynamic ABI value, see solenoid-rt/src/abi.rs */
+typedef struct {
+    const i8* ptr;
+    uint64_t len;
+} abi_slice;
+
+/* scratch memory the arrays of decoded structs are allocated from */
+typedef struct {
+    i8* ptr;
+    uint64_t len;
+} abi_arena;
+
+/* implemented by solenoid-rt */
 
-int cmp(i8* a, i8* b);
-void cpy(i8* a, i8* b);
+i8* pad_int(i8* out, int x);
 void prt(i8* a);
 void swap_endianness(i8* i);
+void swap_words(i8* p, uint64_t len);
 
-void memcpy(void *dst, const void *src, int len);
-void *memset(void *b, int c, size_t len);
-
-static int occupancy = 1;
-i8 storage[1024*64];
+extern i8 storage[];
+extern int reverted;
 
 void revert();
 void sload(i8* st, i8* key);
 void sstore(i8* st, i8* key, i8* val);
+int storage_bind(i8* st, uint64_t len, uint64_t limit, uint8_t shards);
+int storage_add_shard(i8* st, uint64_t len, uint64_t limit);
 void dump_storage();
-void dump_stack(i8* label, int sp, int pc, i8* stack, i8* mem);
+void dump_stack(i8* label, long sp, long pc, i8* stack, i8* mem);
 
 void udiv256(i8*, i8*, i8*);
 void sdiv256(i8*, i8*, i8*);
 void neg(i8*);
 void powmod(i8*, i8*, i8*);
 
-
-/* sha3 - an implementation of Secure Hash Algorithm 3 (Keccak).
- * based on the
- * The Keccak SHA-3 submission. Submission to NIST (Round 3), 2011
- * by Guido Bertoni, Joan Daemen, Michaël Peeters and Gilles Van Assche
- *
- * Copyright: 2013 Aleksey Kravchenko <rhash.admin@gmail.com>
- *
- * Permission is hereby granted,  free of charge,  to any person  obtaining a
- * copy of this software and associated documentation files (the "Software"),
- * to deal in the Software without restriction,  including without limitation
- * the rights to  use, copy, modify,  merge, publish, distribute, sublicense,
- * and/or sell copies  of  the Software,  and to permit  persons  to whom the
- * Software is furnished to do so.
- *
- * This program  is  distributed  in  the  hope  that it will be useful,  but
- * WITHOUT ANY WARRANTY; without even the implied warranty of MERCHANTABILITY
- * or FITNESS FOR A PARTICULAR PURPOSE.  Use this program  at  your own risk!
- */
-
-#include <stdint.h>
-
-#define sha3_max_permutation_size 25
-#define sha3_max_rate_in_qwords 24
-
-typedef struct SHA3_CTX {
-    /* 1600 bits algorithm hashing state */
-    uint64_t hash[sha3_max_permutation_size];
-    /* 1536-bit buffer for leftovers */
-    uint64_t message[sha3_max_rate_in_qwords];
-    /* count of bytes in the message[] buffer */
-    uint16_t rest;
-    /* size of a message block processed at once */
-    //unsigned block_size;
-} SHA3_CTX;
-
-
-#ifdef __cplusplus
-extern "C" {
-#endif  /* __cplusplus */
-
-
-void keccak_init(SHA3_CTX *ctx);
-void keccak_update(SHA3_CTX *ctx, const unsigned char *msg, uint16_t size);
-void keccak_final(SHA3_CTX *ctx, unsigned char* result);
-
-void keccak256(const unsigned char *msg, uint16_t size, unsigned char* result);
-
-
-
-#ifdef __cplusplus
-}
-#endif  /* __cplusplus */
-
-
-#endif /* RT_H */
\ No newline at end of file
+void keccak256(i8* msg, uint64_t size, i8* result);
+
+void emit_log(i8* data, uint64_t len, const i8* topics, uint64_t n);
+uint64_t log_count();
+int log_get(uint64_t i, const i8** topics, uint64_t* n, const i8** data, uint64_t* len);
+void log_clear();
+
+uint64_t abi_encode_tail(i8* out, const char* ty, const i8* data, uint64_t len);
+int abi_decode_tail(const i8* base, uint64_t size, const i8* head, const char* ty, const i8** ptr, uint64_t* len);
+void abi_put_uint(i8* out, uint64_t at, uint64_t x);
+void abi_put_int(i8* out, uint64_t at, int64_t x);
+void abi_put_word(i8* out, uint64_t at, const i8* data, uint64_t n, int right);
+uint64_t abi_put_bytes(i8* out, uint64_t at, const i8* data, uint64_t len);
+int abi_get_uint(const i8* data, uint64_t size, uint64_t at, uint32_t bits, uint64_t* x);
+int abi_get_int(const i8* data, uint64_t size, uint64_t at, uint32_t bits, int64_t* x);
+int abi_get_word(const i8* data, uint64_t size, uint64_t at, i8* out, uint64_t n, int right);
+int abi_get_bytes(const i8* data, uint64_t size, uint64_t at, abi_slice* out);
+void* abi_alloc(abi_arena* arena, uint64_t size);
+
+void pubkey_to_address(const i8* pubkey, i8* address);
+int address_to_pubkey(const i8* registry, const i8* address, i8* pubkey);
+int register_address(i8* registry, uint64_t registry_len, const i8* pubkey, i8* address);
+
+#endif /* RT_H */
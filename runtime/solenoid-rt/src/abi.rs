@@ -0,0 +1,584 @@
+//! ABI encoding and decoding of dynamic types for the generated
+//! `abi_<Contract>_<fn>` and `abi_<Contract>_<fn>_decode` helpers.
+//!
+//! Static parameters are encoded inline by the compiler. Dynamic ones are
+//! described by their canonical type name, e.g. `uint256[]` or
+//! `(address,string)`, and a pointer/length pair:
+//!
+//! - `bytes`, `string`: the bytes and their length.
+//! - `T[]`, `T[k]`: the elements and their count. Static elements are given
+//!   already encoded, 32 bytes per word. Dynamic elements are given as an
+//!   array of `AbiSlice`, one per element.
+//! - tuples: an array of `AbiSlice`, one per component. Static components
+//!   point to their encoded words.
+//!
+//! The C structs generated for tuple parameters are encoded and decoded by
+//! code in `contracts.h`, which writes and reads single values with the
+//! `abi_put_*` and `abi_get_*` functions below. Positions are byte offsets
+//! into the buffer, a null output buffer only measures the encoding.
+
+/// Pointer/length pair describing one dynamic value.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct AbiSlice {
+    pub ptr: *const u8,
+    pub len: u64,
+}
+
+/// Scratch memory the arrays of decoded structs are allocated from.
+#[repr(C)]
+pub struct AbiArena {
+    pub ptr: *mut u8,
+    pub len: u64,
+}
+
+/// Canonical type name, parsed on demand.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub(crate) enum Kind<'a> {
+    /// `bytes` and `string`
+    Bytes,
+    /// Any other static type of the given number of words
+    Words(usize),
+    Array(&'a [u8]),
+    FixedArray(&'a [u8], usize),
+    Tuple(&'a [u8]),
+}
+
+fn parse_usize(s: &[u8]) -> usize {
+    s.iter().fold(0, |acc, &d| acc * 10 + (d - b'0') as usize)
+}
+
+pub(crate) fn kind(ty: &[u8]) -> Kind<'_> {
+    if ty.last() == Some(&b']') {
+        let open = ty.iter().rposition(|&c| c == b'[').unwrap_or(0);
+        let elem = &ty[..open];
+        let dim = &ty[open + 1..ty.len() - 1];
+        if dim.is_empty() {
+            return Kind::Array(elem);
+        }
+        let n = parse_usize(dim);
+        return if is_dynamic(elem) { Kind::FixedArray(elem, n) } else { Kind::Words(n * words(elem)) };
+    }
+    if ty.first() == Some(&b'(') {
+        let inner = &ty[1..ty.len() - 1];
+        return if components(inner).any(is_dynamic) {
+            Kind::Tuple(inner)
+        } else {
+            Kind::Words(components(inner).map(words).sum())
+        };
+    }
+    match ty {
+        b"bytes" | b"string" => Kind::Bytes,
+        _ => Kind::Words(1),
+    }
+}
+
+/// Top level comma separated components of a tuple body.
+#[derive(Clone)]
+pub(crate) struct Components<'a> {
+    rest: &'a [u8],
+}
+
+impl<'a> Iterator for Components<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<&'a [u8]> {
+        if self.rest.is_empty() {
+            return None;
+        }
+        let mut depth = 0;
+        let end = self.rest.iter().position(|&c| {
+            match c {
+                b'(' => depth += 1,
+                b')' => depth -= 1,
+                _ => {}
+            }
+            c == b',' && depth == 0
+        });
+        let (item, rest) = match end {
+            Some(end) => (&self.rest[..end], &self.rest[end + 1..]),
+            None => (self.rest, &self.rest[self.rest.len()..]),
+        };
+        self.rest = rest;
+        Some(item)
+    }
+}
+
+pub(crate) fn components(inner: &[u8]) -> Components<'_> {
+    Components { rest: inner }
+}
+
+pub(crate) fn is_dynamic(ty: &[u8]) -> bool {
+    !matches!(kind(ty), Kind::Words(_))
+}
+
+/// Number of words a static type takes.
+fn words(ty: &[u8]) -> usize {
+    match kind(ty) {
+        Kind::Words(n) => n,
+        _ => 1,
+    }
+}
+
+/// Size of the head of `ty`: its encoding when static, an offset otherwise.
+pub(crate) fn head_size(ty: &[u8]) -> usize {
+    32 * words(ty)
+}
+
+pub(crate) unsafe fn write_word(out: *mut u8, x: u64) {
+    core::ptr::write_bytes(out, 0, 24);
+    core::ptr::copy_nonoverlapping(x.to_be_bytes().as_ptr(), out.add(24), 8);
+}
+
+/// Encode `n` values of the given types, heads first and then tails.
+unsafe fn encode_sequence<'a>(
+    out: *mut u8,
+    types: impl Iterator<Item = &'a [u8]> + Clone,
+    value: impl Fn(usize) -> AbiSlice,
+) -> usize {
+    let heads: usize = types.clone().map(head_size).sum();
+    let mut head = 0;
+    let mut tail = heads;
+    for (i, ty) in types.enumerate() {
+        let item = value(i);
+        if is_dynamic(ty) {
+            write_word(out.add(head), tail as u64);
+            tail += encode(out.add(tail), ty, item.ptr, item.len);
+        } else {
+            core::ptr::copy_nonoverlapping(item.ptr, out.add(head), head_size(ty));
+        }
+        head += head_size(ty);
+    }
+    tail
+}
+
+unsafe fn encode_elements(out: *mut u8, elem: &[u8], data: *const u8, n: usize) -> usize {
+    let slices = data as *const AbiSlice;
+    let size = head_size(elem);
+    let dynamic = is_dynamic(elem);
+    // not `repeat_n`, which older BPF toolchains lack
+    #[allow(clippy::manual_repeat_n)]
+    let elems = core::iter::repeat(elem).take(n);
+    encode_sequence(out, elems, |i| {
+        if dynamic {
+            core::ptr::read_unaligned(slices.add(i))
+        } else {
+            AbiSlice { ptr: data.add(i * size), len: 1 }
+        }
+    })
+}
+
+/// Encode the value `data`/`len` of type `ty` at `out`, returning its size.
+pub(crate) unsafe fn encode(out: *mut u8, ty: &[u8], data: *const u8, len: u64) -> usize {
+    match kind(ty) {
+        Kind::Bytes => {
+            let padded = (len as usize + 31) / 32 * 32;
+            write_word(out, len);
+            core::ptr::write_bytes(out.add(32), 0, padded);
+            core::ptr::copy_nonoverlapping(data, out.add(32), len as usize);
+            32 + padded
+        }
+        Kind::Words(n) => {
+            core::ptr::copy_nonoverlapping(data, out, 32 * n);
+            32 * n
+        }
+        Kind::Array(elem) => {
+            write_word(out, len);
+            32 + encode_elements(out.add(32), elem, data, len as usize)
+        }
+        Kind::FixedArray(elem, n) => encode_elements(out, elem, data, n),
+        Kind::Tuple(inner) => {
+            let slices = data as *const AbiSlice;
+            encode_sequence(out, components(inner), |i| core::ptr::read_unaligned(slices.add(i)))
+        }
+    }
+}
+
+/// Append the tail of the dynamic value `data`/`len` of type `ty` to `out`
+/// and return its size. `ty` is a NUL terminated canonical type name.
+#[no_mangle]
+pub unsafe extern "C" fn abi_encode_tail(out: *mut u8, ty: *const u8, data: *const u8, len: u64) -> u64 {
+    let ty = core::slice::from_raw_parts(ty, strlen(ty));
+    encode(out, ty, data, len) as u64
+}
+
+unsafe fn read_word(word: *const u8) -> Option<u64> {
+    let word = core::slice::from_raw_parts(word, 32);
+    if word[..24].iter().any(|&b| b != 0) {
+        return None;
+    }
+    let mut x = [0u8; 8];
+    x.copy_from_slice(&word[24..]);
+    Some(u64::from_be_bytes(x))
+}
+
+unsafe fn decode(base: *const u8, size: u64, head: *const u8, ty: &[u8]) -> Option<(*const u8, u64)> {
+    let offset = read_word(head)?;
+    let body = offset.checked_add(32).filter(|&end| end <= size);
+    let (end, ptr, len) = match kind(ty) {
+        Kind::Bytes => {
+            let body = body?;
+            let len = read_word(base.add(offset as usize))?;
+            (body.checked_add(len)?, body, len)
+        }
+        Kind::Array(elem) => {
+            let body = body?;
+            let n = read_word(base.add(offset as usize))?;
+            (n.checked_mul(head_size(elem) as u64)?.checked_add(body)?, body, n)
+        }
+        Kind::FixedArray(elem, n) => (offset.checked_add((n * head_size(elem)) as u64)?, offset, n as u64),
+        Kind::Tuple(inner) => {
+            let heads = components(inner).map(head_size).sum::<usize>() as u64;
+            (offset.checked_add(heads)?, offset, components(inner).count() as u64)
+        }
+        Kind::Words(_) => return None,
+    };
+    if end > size {
+        return None;
+    }
+    Some((base.add(ptr as usize), len))
+}
+
+/// Decode the dynamic value of type `ty` whose offset is the word at `head`,
+/// within the `size` bytes at `base`. Offsets are relative to `base`.
+///
+/// `bytes` and `string` give their data and length, arrays their element
+/// heads and count, dynamic tuples and fixed arrays their heads and number of
+/// components. Dynamic elements are decoded by calling this again with the
+/// element heads as `base`. Returns 0 if the data is malformed.
+#[no_mangle]
+pub unsafe extern "C" fn abi_decode_tail(
+    base: *const u8,
+    size: u64,
+    head: *const u8,
+    ty: *const u8,
+    ptr: *mut *const u8,
+    len: *mut u64,
+) -> i32 {
+    let ty = core::slice::from_raw_parts(ty, strlen(ty));
+    if head < base || head.add(32) > base.add(size as usize) {
+        return 0;
+    }
+    match decode(base, size, head, ty) {
+        Some((p, l)) => {
+            *ptr = p;
+            *len = l;
+            1
+        }
+        None => 0,
+    }
+}
+
+/// Write `x` as a word at `out + at`.
+#[no_mangle]
+pub unsafe extern "C" fn abi_put_uint(out: *mut u8, at: u64, x: u64) {
+    if !out.is_null() {
+        write_word(out.add(at as usize), x);
+    }
+}
+
+/// Write `x` as a sign extended word at `out + at`.
+#[no_mangle]
+pub unsafe extern "C" fn abi_put_int(out: *mut u8, at: u64, x: i64) {
+    if !out.is_null() {
+        write_word(out.add(at as usize), x as u64);
+        if x < 0 {
+            core::ptr::write_bytes(out.add(at as usize), 0xff, 24);
+        }
+    }
+}
+
+/// Write the `n` bytes at `data` as a zero padded word at `out + at`, right
+/// aligned if `right` is set, as addresses are, left aligned otherwise.
+#[no_mangle]
+pub unsafe extern "C" fn abi_put_word(out: *mut u8, at: u64, data: *const u8, n: u64, right: i32) {
+    if !out.is_null() {
+        let out = out.add(at as usize);
+        let pad = if right != 0 { 32 - n as usize } else { 0 };
+        core::ptr::write_bytes(out, 0, 32);
+        core::ptr::copy_nonoverlapping(data, out.add(pad), n as usize);
+    }
+}
+
+/// Write `bytes` or `string` of `len` bytes at `out + at`, returning its size.
+#[no_mangle]
+pub unsafe extern "C" fn abi_put_bytes(out: *mut u8, at: u64, data: *const u8, len: u64) -> u64 {
+    if !out.is_null() {
+        encode(out.add(at as usize), b"bytes", data, len);
+    }
+    32 + (len + 31) / 32 * 32
+}
+
+/// Word at `data + at`, if it lies within the `size` bytes at `data`.
+unsafe fn word_at<'a>(data: *const u8, size: u64, at: u64) -> Option<&'a [u8]> {
+    match at.checked_add(32) {
+        Some(end) if end <= size => Some(core::slice::from_raw_parts(data.add(at as usize), 32)),
+        _ => None,
+    }
+}
+
+/// Read the word at `data + at` as an unsigned integer of `bits` bits, at
+/// most 64. Returns 0 if it is out of bounds or out of range.
+#[no_mangle]
+pub unsafe extern "C" fn abi_get_uint(data: *const u8, size: u64, at: u64, bits: u32, x: *mut u64) -> i32 {
+    let word = match word_at(data, size, at) {
+        Some(word) => word,
+        None => return 0,
+    };
+    match read_word(word.as_ptr()) {
+        Some(value) if bits >= 64 || value >> bits == 0 => {
+            *x = value;
+            1
+        }
+        _ => 0,
+    }
+}
+
+/// Read the word at `data + at` as a signed integer of `bits` bits, at most
+/// 64. Returns 0 if it is out of bounds or out of range.
+#[no_mangle]
+pub unsafe extern "C" fn abi_get_int(data: *const u8, size: u64, at: u64, bits: u32, x: *mut i64) -> i32 {
+    let word = match word_at(data, size, at) {
+        Some(word) => word,
+        None => return 0,
+    };
+    let mut low = [0u8; 8];
+    low.copy_from_slice(&word[24..]);
+    let value = i64::from_be_bytes(low);
+    let sign = if value < 0 { 0xff } else { 0 };
+    let bits = bits.clamp(1, 64);
+    if word[..24].iter().any(|&b| b != sign) || (value >> (bits - 1)) != (value >> 63) {
+        return 0;
+    }
+    *x = value;
+    1
+}
+
+/// Read `n` bytes of the word at `data + at` into `out`, right aligned if
+/// `right` is set. Returns 0 if it is out of bounds or its padding is not zero.
+#[no_mangle]
+pub unsafe extern "C" fn abi_get_word(data: *const u8, size: u64, at: u64, out: *mut u8, n: u64, right: i32) -> i32 {
+    let word = match word_at(data, size, at) {
+        Some(word) => word,
+        None => return 0,
+    };
+    let n = n as usize;
+    let (value, padding) = if right != 0 { (&word[32 - n..], &word[..32 - n]) } else { (&word[..n], &word[n..]) };
+    if padding.iter().any(|&b| b != 0) {
+        return 0;
+    }
+    core::ptr::copy_nonoverlapping(value.as_ptr(), out, n);
+    1
+}
+
+/// Point `out` to the `bytes` or `string` at `data + at`. Returns 0 if it is
+/// out of bounds.
+#[no_mangle]
+pub unsafe extern "C" fn abi_get_bytes(data: *const u8, size: u64, at: u64, out: *mut AbiSlice) -> i32 {
+    let len = match word_at(data, size, at).and_then(|word| read_word(word.as_ptr())) {
+        Some(len) => len,
+        None => return 0,
+    };
+    match len.checked_add(at + 32) {
+        Some(end) if end <= size => {
+            *out = AbiSlice { ptr: data.add(at as usize + 32), len };
+            1
+        }
+        _ => 0,
+    }
+}
+
+/// Take `size` bytes, 8 byte aligned, from `arena`. Returns null once it is
+/// exhausted.
+#[no_mangle]
+pub unsafe extern "C" fn abi_alloc(arena: *mut AbiArena, size: u64) -> *mut u8 {
+    let arena = &mut *arena;
+    let skip = arena.ptr.align_offset(8) as u64;
+    match size.checked_add(skip) {
+        Some(total) if total <= arena.len => {
+            let ptr = arena.ptr.add(skip as usize);
+            arena.ptr = ptr.add(size as usize);
+            arena.len -= total;
+            ptr
+        }
+        _ => core::ptr::null_mut(),
+    }
+}
+
+pub(crate) unsafe fn strlen(s: *const u8) -> usize {
+    let mut n = 0;
+    while *s.add(n) != 0 {
+        n += 1;
+    }
+    n
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    fn word(x: u64) -> [u8; 32] {
+        let mut w = [0u8; 32];
+        unsafe { write_word(w.as_mut_ptr(), x) };
+        w
+    }
+
+    fn slice(data: &[u8], len: usize) -> AbiSlice {
+        AbiSlice { ptr: data.as_ptr(), len: len as u64 }
+    }
+
+    #[test]
+    fn test_kind() {
+        assert_eq!(kind(b"uint256"), Kind::Words(1));
+        assert_eq!(kind(b"bytes"), Kind::Bytes);
+        assert_eq!(kind(b"uint256[2][]"), Kind::Array(b"uint256[2]"));
+        assert_eq!(kind(b"uint256[2][3]"), Kind::Words(6));
+        assert_eq!(kind(b"string[2]"), Kind::FixedArray(b"string", 2));
+        assert_eq!(kind(b"(uint256,(bool,address))"), Kind::Words(3));
+        assert_eq!(kind(b"(uint256,(bool,string))"), Kind::Tuple(b"uint256,(bool,string)"));
+    }
+
+    #[test]
+    fn test_encode_spec_f() {
+        // f(uint256,uint32[],bytes10,bytes) from the Solidity ABI specification
+        let a = word(0x123);
+        let b = [word(0x456), word(0x789)].concat();
+        let mut c = [0u8; 32];
+        c[..10].copy_from_slice(b"1234567890");
+        let d = b"Hello, world!";
+        let args = [slice(&a, 1), slice(&b, 2), slice(&c, 1), slice(d, d.len())];
+
+        let mut out = [0u8; 32 * 9];
+        let size = unsafe { encode(out.as_mut_ptr(), b"(uint256,uint32[],bytes10,bytes)", args.as_ptr() as *const u8, 4) };
+        assert_eq!(size, out.len());
+        assert_eq!(
+            hex(&out),
+            concat!(
+                "0000000000000000000000000000000000000000000000000000000000000123",
+                "0000000000000000000000000000000000000000000000000000000000000080",
+                "3132333435363738393000000000000000000000000000000000000000000000",
+                "00000000000000000000000000000000000000000000000000000000000000e0",
+                "0000000000000000000000000000000000000000000000000000000000000002",
+                "0000000000000000000000000000000000000000000000000000000000000456",
+                "0000000000000000000000000000000000000000000000000000000000000789",
+                "000000000000000000000000000000000000000000000000000000000000000d",
+                "48656c6c6f2c20776f726c642100000000000000000000000000000000000000",
+            )
+        );
+    }
+
+    #[test]
+    fn test_encode_spec_g() {
+        // g(uint256[][],string[]) with ([[1, 2], [3]], ["one", "two", "three"])
+        let x0 = [word(1), word(2)].concat();
+        let x1 = word(3);
+        let x = [slice(&x0, 2), slice(&x1, 1)];
+        let y = [slice(b"one", 3), slice(b"two", 3), slice(b"three", 5)];
+        let args = [slice(unsafe { as_bytes(&x) }, 2), slice(unsafe { as_bytes(&y) }, 3)];
+
+        let mut out = [0u8; 32 * 20];
+        let size = unsafe { encode(out.as_mut_ptr(), b"(uint256[][],string[])", args.as_ptr() as *const u8, 2) };
+        assert_eq!(size, out.len());
+        let expected = [
+            0x40, 0x140, 2, 0x40, 0xa0, 2, 1, 2, 1, 3, 3, 0x60, 0xa0, 0xe0, 3, 0, 3, 0, 5, 0,
+        ];
+        for (i, (chunk, &w)) in out.chunks(32).zip(expected.iter()).enumerate() {
+            match i {
+                15 => assert_eq!(&chunk[..3], b"one"),
+                17 => assert_eq!(&chunk[..3], b"two"),
+                19 => assert_eq!(&chunk[..5], b"three"),
+                _ => assert_eq!(chunk, &word(w), "word {}", i),
+            }
+        }
+    }
+
+    #[test]
+    fn test_decode_spec_g() {
+        let x0 = [word(1), word(2)].concat();
+        let x1 = word(3);
+        let x = [slice(&x0, 2), slice(&x1, 1)];
+        let y = [slice(b"one", 3), slice(b"two", 3), slice(b"three", 5)];
+        let args = [slice(unsafe { as_bytes(&x) }, 2), slice(unsafe { as_bytes(&y) }, 3)];
+        let mut out = [0u8; 32 * 20];
+        unsafe { encode(out.as_mut_ptr(), b"(uint256[][],string[])", args.as_ptr() as *const u8, 2) };
+
+        let base = out.as_ptr();
+        let size = out.len() as u64;
+        let (mut ptr, mut len) = (core::ptr::null(), 0);
+        unsafe {
+            assert_eq!(abi_decode_tail(base, size, base.add(32), b"string[]\0".as_ptr(), &mut ptr, &mut len), 1);
+            assert_eq!(len, 3);
+            let strings = ptr;
+            assert_eq!(abi_decode_tail(strings, size - 11 * 32, strings.add(64), b"string\0".as_ptr(), &mut ptr, &mut len), 1);
+            assert_eq!(core::slice::from_raw_parts(ptr, len as usize), b"three");
+
+            assert_eq!(abi_decode_tail(base, size, base, b"uint256[][]\0".as_ptr(), &mut ptr, &mut len), 1);
+            assert_eq!(len, 2);
+            let arrays = ptr;
+            assert_eq!(abi_decode_tail(arrays, size - 3 * 32, arrays, b"uint256[]\0".as_ptr(), &mut ptr, &mut len), 1);
+            assert_eq!(core::slice::from_raw_parts(ptr, 64), &x0[..]);
+
+            // truncated return data
+            assert_eq!(abi_decode_tail(base, 18 * 32, base.add(32), b"string[]\0".as_ptr(), &mut ptr, &mut len), 1);
+            assert_eq!(abi_decode_tail(base, 12 * 32, base.add(32), b"string[]\0".as_ptr(), &mut ptr, &mut len), 0);
+        }
+    }
+
+    #[test]
+    fn test_put_get() {
+        let mut out = [0u8; 32 * 5];
+        let p = out.as_mut_ptr();
+        let address = [0x11u8; 20];
+        unsafe {
+            abi_put_uint(p, 0, 0x1234);
+            abi_put_int(p, 32, -2);
+            abi_put_word(p, 64, address.as_ptr(), 20, 1);
+            assert_eq!(abi_put_bytes(p, 96, b"abc".as_ptr(), 3), 64);
+            assert_eq!(abi_put_bytes(core::ptr::null_mut(), 0, b"abc".as_ptr(), 33), 96);
+        }
+        assert_eq!(&out[..32], &word(0x1234));
+        assert_eq!(hex(&out[32..64]), format!("{}fe", "ff".repeat(31)));
+        assert_eq!(&out[64..76], &[0u8; 12]);
+        assert_eq!(&out[76..96], &address);
+
+        let (data, size) = (out.as_ptr(), out.len() as u64);
+        let (mut x, mut y, mut a) = (0u64, 0i64, [0u8; 20]);
+        let mut bytes = AbiSlice { ptr: core::ptr::null(), len: 0 };
+        unsafe {
+            assert_eq!(abi_get_uint(data, size, 0, 16, &mut x), 1);
+            assert_eq!(x, 0x1234);
+            assert_eq!(abi_get_uint(data, size, 0, 8, &mut x), 0);
+            assert_eq!(abi_get_int(data, size, 32, 8, &mut y), 1);
+            assert_eq!(y, -2);
+            assert_eq!(abi_get_int(data, size, 0, 8, &mut y), 0);
+            assert_eq!(abi_get_word(data, size, 64, a.as_mut_ptr(), 20, 1), 1);
+            assert_eq!(a, address);
+            assert_eq!(abi_get_word(data, size, 64, a.as_mut_ptr(), 20, 0), 0);
+            assert_eq!(abi_get_bytes(data, size, 96, &mut bytes), 1);
+            assert_eq!(core::slice::from_raw_parts(bytes.ptr, bytes.len as usize), b"abc");
+            assert_eq!(abi_get_bytes(data, 130, 96, &mut bytes), 0);
+            assert_eq!(abi_get_uint(data, size, size - 16, 64, &mut x), 0);
+        }
+    }
+
+    #[test]
+    fn test_alloc() {
+        let mut scratch = [0u64; 4];
+        let mut arena = AbiArena { ptr: scratch.as_mut_ptr() as *mut u8, len: 32 };
+        unsafe {
+            let a = abi_alloc(&mut arena, 3);
+            let b = abi_alloc(&mut arena, 16);
+            assert_eq!(b as usize - a as usize, 8);
+            assert!(abi_alloc(&mut arena, 16).is_null());
+            assert!(!abi_alloc(&mut arena, 8).is_null());
+        }
+    }
+
+    unsafe fn as_bytes(slices: &[AbiSlice]) -> &[u8] {
+        core::slice::from_raw_parts(slices.as_ptr() as *const u8, core::mem::size_of_val(slices))
+    }
+}
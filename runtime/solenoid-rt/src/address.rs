@@ -0,0 +1,95 @@
+//! Mapping between 20 byte EVM addresses and 32 byte Solana public keys.
+//!
+//! Mirrors `libsolenoid::address`: an address is the last 20 bytes of the
+//! keccak256 hash of a pubkey, and the reverse lookup goes through a registry
+//! laid out in account data as a `u32` entry count followed by
+//! `(address, pubkey)` pairs.
+
+use crate::keccak::hash;
+
+const HEADER_SIZE: usize = 4;
+const ENTRY_SIZE: usize = 20 + 32;
+
+unsafe fn count(registry: *const u8) -> usize {
+    u32::from_le_bytes(core::ptr::read_unaligned(registry as *const [u8; 4])) as usize
+}
+
+unsafe fn find(registry: *const u8, address: &[u8]) -> Option<*const u8> {
+    (0..count(registry))
+        .map(|i| registry.add(HEADER_SIZE + i * ENTRY_SIZE))
+        .find(|&entry| core::slice::from_raw_parts(entry, 20) == address)
+}
+
+/// Write the address of the 32 byte pubkey at `pubkey` to `address`.
+#[no_mangle]
+pub unsafe extern "C" fn pubkey_to_address(pubkey: *const u8, address: *mut u8) {
+    let digest = hash(pubkey, 32);
+    core::ptr::copy_nonoverlapping(digest.as_ptr().add(12), address, 20);
+}
+
+/// Copy the pubkey registered under `address` to `pubkey`.
+/// Returns 0 if the address is not registered. Compiled contracts do not
+/// call it: CALL, which would resolve its target here, is not compiled.
+#[no_mangle]
+pub unsafe extern "C" fn address_to_pubkey(registry: *const u8, address: *const u8, pubkey: *mut u8) -> i32 {
+    match find(registry, core::slice::from_raw_parts(address, 20)) {
+        Some(entry) => {
+            core::ptr::copy_nonoverlapping(entry.add(20), pubkey, 32);
+            1
+        }
+        None => 0,
+    }
+}
+
+/// Add `pubkey` to a registry of `registry_len` bytes and write its address
+/// to `address`. Returns 0 if the registry is full.
+#[no_mangle]
+pub unsafe extern "C" fn register_address(registry: *mut u8, registry_len: u64, pubkey: *const u8, address: *mut u8) -> i32 {
+    pubkey_to_address(pubkey, address);
+    let address = core::slice::from_raw_parts(address, 20);
+    if find(registry, address).is_some() {
+        return 1;
+    }
+
+    let n = count(registry);
+    let entry = HEADER_SIZE + n * ENTRY_SIZE;
+    if entry + ENTRY_SIZE > registry_len as usize {
+        return 0;
+    }
+    core::ptr::copy_nonoverlapping(address.as_ptr(), registry.add(entry), 20);
+    core::ptr::copy_nonoverlapping(pubkey, registry.add(entry + 20), 32);
+    core::ptr::write_unaligned(registry as *mut [u8; 4], (n as u32 + 1).to_le_bytes());
+    1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_register_address() {
+        let mut registry = [0u8; HEADER_SIZE + ENTRY_SIZE];
+        let len = registry.len() as u64;
+        let alice = [1u8; 32];
+        let bob = [2u8; 32];
+        let mut address = [0u8; 20];
+        let mut pubkey = [0u8; 32];
+        unsafe {
+            assert_eq!(register_address(registry.as_mut_ptr(), len, alice.as_ptr(), address.as_mut_ptr()), 1);
+            assert_eq!(register_address(registry.as_mut_ptr(), len, alice.as_ptr(), address.as_mut_ptr()), 1);
+            assert_eq!(address_to_pubkey(registry.as_ptr(), address.as_ptr(), pubkey.as_mut_ptr()), 1);
+            assert_eq!(pubkey, alice);
+
+            assert_eq!(register_address(registry.as_mut_ptr(), len, bob.as_ptr(), address.as_mut_ptr()), 0);
+            assert_eq!(address_to_pubkey(registry.as_ptr(), address.as_ptr(), pubkey.as_mut_ptr()), 0);
+        }
+    }
+
+    #[test]
+    fn test_pubkey_to_address() {
+        let mut address = [0u8; 20];
+        unsafe { pubkey_to_address([0u8; 32].as_ptr(), address.as_mut_ptr()) };
+        assert_eq!(address[..4], [0x88, 0x38, 0x6f, 0xc8]);
+        assert_eq!(address[16..], [0x0e, 0xf3, 0xe5, 0x63]);
+    }
+}
@@ -0,0 +1,194 @@
+//! 256-bit arithmetic that LLVM cannot lower on BPF.
+//!
+//! Operands are `i256` values as the compiled module stores them: 32 bytes,
+//! least significant byte first.
+
+/// 256-bit word as four little-endian limbs.
+type Word = [u64; 4];
+
+const ZERO: Word = [0; 4];
+
+unsafe fn load(ptr: *const u8) -> Word {
+    let bytes = core::ptr::read_unaligned(ptr as *const [u8; 32]);
+    let mut word = ZERO;
+    for (limb, chunk) in word.iter_mut().zip(bytes.chunks(8)) {
+        let mut buf = [0u8; 8];
+        buf.copy_from_slice(chunk);
+        *limb = u64::from_le_bytes(buf);
+    }
+    word
+}
+
+unsafe fn store(ptr: *mut u8, word: &Word) {
+    let mut bytes = [0u8; 32];
+    for (limb, chunk) in word.iter().zip(bytes.chunks_mut(8)) {
+        chunk.copy_from_slice(&limb.to_le_bytes());
+    }
+    core::ptr::write_unaligned(ptr as *mut [u8; 32], bytes);
+}
+
+fn is_zero(a: &Word) -> bool {
+    a.iter().all(|&limb| limb == 0)
+}
+
+fn is_negative(a: &Word) -> bool {
+    a[3] >> 63 == 1
+}
+
+fn ge(a: &Word, b: &Word) -> bool {
+    for i in (0..4).rev() {
+        if a[i] != b[i] {
+            return a[i] > b[i];
+        }
+    }
+    true
+}
+
+fn sub(a: &Word, b: &Word) -> Word {
+    let mut ret = ZERO;
+    let mut borrow = false;
+    for i in 0..4 {
+        let (x, b1) = a[i].overflowing_sub(b[i]);
+        let (x, b2) = x.overflowing_sub(borrow as u64);
+        ret[i] = x;
+        borrow = b1 || b2;
+    }
+    ret
+}
+
+fn negate(a: &Word) -> Word {
+    sub(&ZERO, a)
+}
+
+fn shl1(a: &Word) -> Word {
+    let mut ret = ZERO;
+    for i in (0..4).rev() {
+        ret[i] = a[i] << 1;
+        if i > 0 {
+            ret[i] |= a[i - 1] >> 63;
+        }
+    }
+    ret
+}
+
+/// Wrapping multiplication modulo 2^256.
+fn mul(a: &Word, b: &Word) -> Word {
+    let mut ret = ZERO;
+    for i in 0..4 {
+        let mut carry = 0u128;
+        for j in 0..(4 - i) {
+            let cur = ret[i + j] as u128 + (a[i] as u128) * (b[j] as u128) + carry;
+            ret[i + j] = cur as u64;
+            carry = cur >> 64;
+        }
+    }
+    ret
+}
+
+/// Unsigned long division, returns (quotient, remainder).
+/// Division by zero yields zero for both, as the EVM specifies.
+fn divmod(n: &Word, d: &Word) -> (Word, Word) {
+    if is_zero(d) {
+        return (ZERO, ZERO);
+    }
+    let mut q = ZERO;
+    let mut r = ZERO;
+    for bit in (0..256).rev() {
+        r = shl1(&r);
+        r[0] |= (n[bit / 64] >> (bit % 64)) & 1;
+        if ge(&r, d) {
+            r = sub(&r, d);
+            q[bit / 64] |= 1 << (bit % 64);
+        }
+    }
+    (q, r)
+}
+
+/// `*q = *n / *d`, leaving the remainder in `*n`.
+#[no_mangle]
+pub unsafe extern "C" fn udiv256(n: *mut u8, d: *mut u8, q: *mut u8) {
+    let (quot, rem) = divmod(&load(n), &load(d));
+    store(q, &quot);
+    store(n, &rem);
+}
+
+/// Signed variant of `udiv256`. The remainder takes the sign of the dividend.
+#[no_mangle]
+pub unsafe extern "C" fn sdiv256(n: *mut u8, d: *mut u8, q: *mut u8) {
+    let (n_val, d_val) = (load(n), load(d));
+    let (n_neg, d_neg) = (is_negative(&n_val), is_negative(&d_val));
+    let n_abs = if n_neg { negate(&n_val) } else { n_val };
+    let d_abs = if d_neg { negate(&d_val) } else { d_val };
+
+    let (quot, rem) = divmod(&n_abs, &d_abs);
+    let quot = if n_neg != d_neg { negate(&quot) } else { quot };
+    let rem = if n_neg { negate(&rem) } else { rem };
+    store(q, &quot);
+    store(n, &rem);
+}
+
+/// `*n = -*n`
+#[no_mangle]
+pub unsafe extern "C" fn neg(n: *mut u8) {
+    store(n, &negate(&load(n)));
+}
+
+/// `*ret = *b ** *e`, wrapping at 2^256.
+#[no_mangle]
+pub unsafe extern "C" fn powmod(b: *mut u8, e: *mut u8, ret: *mut u8) {
+    let mut base = load(b);
+    let exp = load(e);
+    let mut acc = [1, 0, 0, 0];
+    for bit in 0..256 {
+        if (exp[bit / 64] >> (bit % 64)) & 1 == 1 {
+            acc = mul(&acc, &base);
+        }
+        base = mul(&base, &base);
+    }
+    store(ret, &acc);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn word(x: u64) -> [u8; 32] {
+        let mut w = [0u8; 32];
+        w[..8].copy_from_slice(&x.to_le_bytes());
+        w
+    }
+
+    #[test]
+    fn test_udiv256() {
+        let (mut n, mut d, mut q) = (word(30), word(10), [0u8; 32]);
+        unsafe { udiv256(n.as_mut_ptr(), d.as_mut_ptr(), q.as_mut_ptr()) };
+        assert_eq!(q, word(3));
+        assert_eq!(n, word(0));
+
+        let (mut n, mut d, mut q) = (word(0xAA00), word(0), [0xFFu8; 32]);
+        unsafe { udiv256(n.as_mut_ptr(), d.as_mut_ptr(), q.as_mut_ptr()) };
+        assert_eq!(q, word(0));
+        assert_eq!(n, word(0));
+    }
+
+    #[test]
+    fn test_sdiv256() {
+        let mut n = [0u8; 32];
+        unsafe { store(n.as_mut_ptr(), &negate(&load(word(7).as_ptr()))) };
+        let (mut d, mut q) = (word(2), [0u8; 32]);
+        unsafe { sdiv256(n.as_mut_ptr(), d.as_mut_ptr(), q.as_mut_ptr()) };
+        assert_eq!(unsafe { negate(&load(q.as_ptr())) }, [3, 0, 0, 0]);
+        assert_eq!(unsafe { negate(&load(n.as_ptr())) }, [1, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_powmod() {
+        let (mut b, mut e, mut r) = (word(2), word(255), [0u8; 32]);
+        unsafe { powmod(b.as_mut_ptr(), e.as_mut_ptr(), r.as_mut_ptr()) };
+        assert_eq!(unsafe { load(r.as_ptr()) }, [0, 0, 0, 1 << 63]);
+
+        let (mut b, mut e, mut r) = (word(2), word(256), [0u8; 32]);
+        unsafe { powmod(b.as_mut_ptr(), e.as_mut_ptr(), r.as_mut_ptr()) };
+        assert_eq!(r, word(0));
+    }
+}
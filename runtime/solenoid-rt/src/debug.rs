@@ -0,0 +1,71 @@
+//! Debug printing helpers. These are no-ops on Solana.
+
+#[cfg(not(feature = "solana"))]
+use crate::printf;
+
+/// Print the 32 byte word at `a`, most significant byte first.
+#[no_mangle]
+pub unsafe extern "C" fn prt(a: *const u8) {
+    #[cfg(not(feature = "solana"))]
+    for i in (0..32).rev() {
+        printf(b"%02X\0".as_ptr(), *a.add(i) as u32);
+    }
+    #[cfg(feature = "solana")]
+    let _ = a;
+}
+
+/// Write `x` into the low bytes of the big endian word at `out`.
+#[no_mangle]
+pub unsafe extern "C" fn pad_int(out: *mut u8, x: i32) -> *mut u8 {
+    let bytes = x.to_be_bytes();
+    core::ptr::copy_nonoverlapping(bytes.as_ptr(), out.add(28), 4);
+    out
+}
+
+/// Print every occupied storage slot as `key : value`.
+#[no_mangle]
+pub unsafe extern "C" fn dump_storage() {
+    #[cfg(not(feature = "solana"))]
+    {
+        use crate::storage::{header, storage, SLOT_SIZE};
+        let st = core::ptr::addr_of!(storage) as *const u8;
+        let zero = [0u8; 32];
+        for i in 0..header(st).count as usize {
+            // slot 0 holds the header in place of the zero key
+            prt(if i == 0 { zero.as_ptr() } else { st.add(i * SLOT_SIZE) });
+            printf(b" : \0".as_ptr());
+            prt(st.add(i * SLOT_SIZE + 32));
+            printf(b"\n\0".as_ptr());
+        }
+        printf(b"\n\0".as_ptr());
+    }
+}
+
+/// Print the top of the stack and the start of memory, called after every
+/// instruction when compiling with `--debug`.
+#[no_mangle]
+pub unsafe extern "C" fn dump_stack(label: *const u8, sp: i64, pc: i64, stack: *const u8, mem: *const u8) {
+    #[cfg(not(feature = "solana"))]
+    {
+        const TOP: i64 = 20;
+        printf(b"----%s----\nstack:(%ld)@%ld\n\0".as_ptr(), label, sp, pc);
+        for i in 0..TOP {
+            if i == sp {
+                printf(b" ->@%04x \n\0".as_ptr(), (i * 32) as u32);
+                break;
+            }
+            printf(b"   @%04x \0".as_ptr(), (i * 32) as u32);
+            prt(stack.add(i as usize * 32));
+            printf(b"\n\0".as_ptr());
+        }
+        printf(b"\n mem:\n\0".as_ptr());
+        for i in 0..TOP {
+            printf(b" %04x \0".as_ptr(), (i * 32) as u32);
+            prt(mem.add(i as usize * 32));
+            printf(b"\n\0".as_ptr());
+        }
+        printf(b"\n\0".as_ptr());
+    }
+    #[cfg(feature = "solana")]
+    let _ = (label, sp, pc, stack, mem);
+}
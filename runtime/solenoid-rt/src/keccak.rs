@@ -0,0 +1,246 @@
+//! Keccak-256 as used by the SHA3 opcode.
+
+const RATE: usize = 136;
+
+const ROUND_CONSTANTS: [u64; 24] = [
+    0x0000000000000001, 0x0000000000008082, 0x800000000000808a, 0x8000000080008000,
+    0x000000000000808b, 0x0000000080000001, 0x8000000080008081, 0x8000000000008009,
+    0x000000000000008a, 0x0000000000000088, 0x0000000080008009, 0x000000008000000a,
+    0x000000008000808b, 0x800000000000008b, 0x8000000000008089, 0x8000000000008003,
+    0x8000000000008002, 0x8000000000000080, 0x000000000000800a, 0x800000008000000a,
+    0x8000000080008081, 0x8000000000008080, 0x0000000080000001, 0x8000000080008008,
+];
+
+const RHO: [u32; 24] = [
+    1, 3, 6, 10, 15, 21, 28, 36, 45, 55, 2, 14, 27, 41, 56, 8, 25, 43, 62, 18, 39, 61, 20, 44,
+];
+
+const PI: [usize; 24] = [
+    10, 7, 11, 17, 18, 3, 5, 16, 8, 21, 24, 4, 15, 23, 19, 13, 12, 2, 20, 14, 22, 9, 6, 1,
+];
+
+fn keccak_f(a: &mut [u64; 25]) {
+    for rc in ROUND_CONSTANTS.iter() {
+        // theta
+        let mut c = [0u64; 5];
+        for x in 0..5 {
+            c[x] = a[x] ^ a[x + 5] ^ a[x + 10] ^ a[x + 15] ^ a[x + 20];
+        }
+        for x in 0..5 {
+            let d = c[(x + 4) % 5] ^ c[(x + 1) % 5].rotate_left(1);
+            for y in (0..25).step_by(5) {
+                a[y + x] ^= d;
+            }
+        }
+
+        // rho and pi
+        let mut last = a[1];
+        for i in 0..24 {
+            let tmp = a[PI[i]];
+            a[PI[i]] = last.rotate_left(RHO[i]);
+            last = tmp;
+        }
+
+        // chi
+        for y in (0..25).step_by(5) {
+            let row = [a[y], a[y + 1], a[y + 2], a[y + 3], a[y + 4]];
+            for x in 0..5 {
+                a[y + x] = row[x] ^ (!row[(x + 1) % 5] & row[(x + 2) % 5]);
+            }
+        }
+
+        // iota
+        a[0] ^= rc;
+    }
+}
+
+/// Incremental Keccak-256 hasher.
+pub struct Keccak256 {
+    state: [u64; 25],
+    offset: usize,
+}
+
+impl Keccak256 {
+    pub fn new() -> Self {
+        Self { state: [0; 25], offset: 0 }
+    }
+
+    fn absorb_byte(&mut self, byte: u8) {
+        self.state[self.offset / 8] ^= (byte as u64) << (8 * (self.offset % 8));
+        self.offset += 1;
+        if self.offset == RATE {
+            keccak_f(&mut self.state);
+            self.offset = 0;
+        }
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        for &byte in data {
+            self.absorb_byte(byte);
+        }
+    }
+
+    pub fn finalize(mut self) -> [u8; 32] {
+        self.state[self.offset / 8] ^= 0x01 << (8 * (self.offset % 8));
+        self.state[(RATE - 1) / 8] ^= 0x80 << (8 * ((RATE - 1) % 8));
+        keccak_f(&mut self.state);
+
+        let mut out = [0u8; 32];
+        for (chunk, lane) in out.chunks_mut(8).zip(self.state.iter()) {
+            chunk.copy_from_slice(&lane.to_le_bytes());
+        }
+        out
+    }
+}
+
+impl Default for Keccak256 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "solana")]
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub(crate) struct SolBytes {
+    pub(crate) addr: *const u8,
+    pub(crate) len: u64,
+}
+
+#[cfg(feature = "solana")]
+extern "C" {
+    fn sol_keccak256(bytes: *const SolBytes, bytes_len: u64, result: *mut u8) -> u64;
+}
+
+/// Reverse every 32 byte word of EVM memory in place, the last one cut short
+/// at `size`.
+#[cfg(any(test, feature = "solana"))]
+unsafe fn reverse_words(msg: *mut u8, size: usize) {
+    for word in core::slice::from_raw_parts_mut(msg, size).chunks_mut(32) {
+        word.reverse();
+    }
+}
+
+/// Hash EVM memory with the `sol_keccak256` syscall, which is far cheaper in
+/// compute units than hashing in the program.
+#[cfg(feature = "solana")]
+unsafe fn digest(msg: *mut u8, size: usize) -> [u8; 32] {
+    let mut out = [0u8; 32];
+    reverse_words(msg, size);
+    let bytes = SolBytes { addr: msg, len: size as u64 };
+    sol_keccak256(&bytes, 1, out.as_mut_ptr());
+    reverse_words(msg, size);
+    out
+}
+
+/// Hash plain bytes, with no word reordering.
+#[cfg(feature = "solana")]
+pub(crate) unsafe fn hash(data: *const u8, size: usize) -> [u8; 32] {
+    let mut out = [0u8; 32];
+    let bytes = SolBytes { addr: data, len: size as u64 };
+    sol_keccak256(&bytes, 1, out.as_mut_ptr());
+    out
+}
+
+#[cfg(not(feature = "solana"))]
+pub(crate) unsafe fn hash(data: *const u8, size: usize) -> [u8; 32] {
+    let mut hasher = Keccak256::new();
+    hasher.update(core::slice::from_raw_parts(data, size));
+    hasher.finalize()
+}
+
+#[cfg(not(feature = "solana"))]
+unsafe fn digest(msg: *mut u8, size: usize) -> [u8; 32] {
+    let mut hasher = Keccak256::new();
+    for word in core::slice::from_raw_parts(msg, size).chunks(32) {
+        for &byte in word.iter().rev() {
+            hasher.absorb_byte(byte);
+        }
+    }
+    hasher.finalize()
+}
+
+/// Hash `size` bytes of EVM memory at `msg` into the word at `result`.
+///
+/// Memory holds each 32 byte word least significant byte first, so words are
+/// read back to front, a trailing partial word only up to `size`. The digest
+/// is stored the same way.
+#[no_mangle]
+pub unsafe extern "C" fn keccak256(msg: *mut u8, size: u64, result: *mut u8) {
+    let mut digest = digest(msg, size as usize);
+    digest.reverse();
+    core::ptr::copy_nonoverlapping(digest.as_ptr(), result, 32);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    #[test]
+    fn test_keccak256_vectors() {
+        let hash = |data: &[u8]| {
+            let mut hasher = Keccak256::new();
+            hasher.update(data);
+            hex(&hasher.finalize())
+        };
+        assert_eq!(hash(b""), "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470");
+        assert_eq!(hash(b"abc"), "4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45");
+        assert_eq!(
+            hash(b"The quick brown fox jumps over the lazy dog"),
+            "4d741b6f1eb29cb2a9b9911c82f56fa8d73b04959d3d9d222895df6c0b28aa15"
+        );
+    }
+
+    #[test]
+    fn test_keccak256_evm_memory() {
+        // mstore(0, 0xAA..AA) mstore(0x20, 0xBB..BB) sha3(0, 64)
+        let mut mem = [0xAAu8; 64];
+        mem[32..].copy_from_slice(&[0xBB; 32]);
+        let mut out = [0u8; 32];
+        unsafe { keccak256(mem.as_mut_ptr(), 64, out.as_mut_ptr()) };
+        out.reverse();
+        assert_eq!(hex(&out), "9f89faaf1495298300ca41edde79c5cc9cb9bf17e1c9ef97acfdc53194f901e1");
+    }
+
+    #[test]
+    fn test_keccak256_long_input() {
+        // lengths past u16::MAX used to be truncated
+        let mut mem: Vec<u8> = (0..70_016u32).map(|i| i as u8).collect();
+        let mut out = [0u8; 32];
+        unsafe { keccak256(mem.as_mut_ptr(), mem.len() as u64, out.as_mut_ptr()) };
+        out.reverse();
+
+        let mut expected = [0u8; 32];
+        let words: Vec<u8> = mem.chunks(32).flat_map(|w| w.iter().rev().cloned()).collect();
+        let mut sponge = tiny_keccak::Keccak::new_keccak256();
+        sponge.update(&words);
+        sponge.finalize(&mut expected);
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn test_keccak256_unaligned() {
+        // the Solana path reverses the words in place and hashes them as is
+        let mut mem: Vec<u8> = (0..64u8).collect();
+        unsafe { reverse_words(mem.as_mut_ptr(), 40) };
+        let mut words: Vec<u8> = (0..32u8).rev().collect();
+        words.extend((32..40u8).rev());
+        words.extend(40..64u8);
+        assert_eq!(mem, words);
+
+        let mut expected = [0u8; 32];
+        let mut sponge = tiny_keccak::Keccak::new_keccak256();
+        sponge.update(&words[..40]);
+        sponge.finalize(&mut expected);
+
+        let mut mem: Vec<u8> = (0..64u8).collect();
+        let mut out = [0u8; 32];
+        unsafe { keccak256(mem.as_mut_ptr(), 40, out.as_mut_ptr()) };
+        out.reverse();
+        assert_eq!(out, expected);
+    }
+}
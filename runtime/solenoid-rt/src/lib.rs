@@ -0,0 +1,116 @@
+//! Runtime support library for contracts compiled by solenoid.
+//!
+//! The compiled module calls into this library for everything that is not
+//! lowered inline: storage access, hashing, address mapping, ABI encoding,
+//! event logs, 256-bit division and exponentiation. Every exported symbol keeps the C calling
+//! convention the compiler declares in `Compiler::{sload, sstore, sha3, ..}`.
+
+#![cfg_attr(not(test), no_std)]
+#![allow(clippy::missing_safety_doc)]
+
+pub mod abi;
+pub mod address;
+pub mod arith;
+pub mod debug;
+pub mod keccak;
+pub mod log;
+pub mod storage;
+
+pub use abi::{
+    abi_alloc, abi_decode_tail, abi_encode_tail, abi_get_bytes, abi_get_int, abi_get_uint, abi_get_word, abi_put_bytes,
+    abi_put_int, abi_put_uint, abi_put_word, AbiArena, AbiSlice,
+};
+pub use address::{address_to_pubkey, pubkey_to_address, register_address};
+pub use arith::{neg, powmod, sdiv256, udiv256};
+pub use debug::{dump_stack, dump_storage, pad_int, prt};
+pub use keccak::keccak256;
+pub use log::emit_log;
+#[cfg(not(feature = "solana"))]
+pub use log::{log_clear, log_count, log_get};
+pub use storage::{sload, sstore, storage_add_shard, storage_bind};
+
+#[cfg(not(feature = "solana"))]
+extern "C" {
+    pub(crate) fn printf(fmt: *const u8, ...) -> i32;
+}
+
+#[cfg(feature = "solana")]
+extern "C" {
+    fn sol_panic_(file: *const u8, len: u64, line: u64, column: u64) -> !;
+}
+
+/// Set by `revert` on host builds so the caller can tell a reverted call from
+/// a successful one. Cleared by the caller before every call.
+#[cfg(not(feature = "solana"))]
+#[no_mangle]
+#[allow(non_upper_case_globals)]
+pub static mut reverted: i32 = 0;
+
+/// Called by the error block of every compiled function. On Solana this aborts
+/// the program so the transaction fails and account data is rolled back.
+#[no_mangle]
+pub unsafe extern "C" fn revert() {
+    #[cfg(not(feature = "solana"))]
+    {
+        reverted = 1;
+    }
+    #[cfg(feature = "solana")]
+    {
+        let msg = b"revert";
+        sol_panic_(msg.as_ptr(), msg.len() as u64, 0, 0);
+    }
+}
+
+/// Reverse the byte order of the 32 byte word at `i` in place.
+#[no_mangle]
+pub unsafe extern "C" fn swap_endianness(i: *mut u8) {
+    core::slice::from_raw_parts_mut(i, 32).reverse();
+}
+
+/// Reverse the byte order of every 32 byte word in the `len` bytes at `p`,
+/// converting between contract memory and ABI encoded data.
+#[no_mangle]
+pub unsafe extern "C" fn swap_words(p: *mut u8, len: u64) {
+    for word in core::slice::from_raw_parts_mut(p, len as usize).chunks_exact_mut(32) {
+        word.reverse();
+    }
+}
+
+#[cfg(all(not(test), not(feature = "solana")))]
+#[panic_handler]
+fn panic(_info: &core::panic::PanicInfo) -> ! {
+    loop {}
+}
+
+#[cfg(all(not(test), feature = "solana"))]
+#[panic_handler]
+fn panic(_info: &core::panic::PanicInfo) -> ! {
+    let file = b"solenoid-rt";
+    unsafe { sol_panic_(file.as_ptr(), file.len() as u64, 0, 0) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_swap_endianness() {
+        let mut word = [0u8; 32];
+        word[0] = 0xAA;
+        word[1] = 0xBB;
+        unsafe { swap_endianness(word.as_mut_ptr()) };
+        assert_eq!(word[31], 0xAA);
+        assert_eq!(word[30], 0xBB);
+        assert_eq!(word[0], 0);
+    }
+
+    #[test]
+    fn test_swap_words() {
+        let mut words = [0u8; 70];
+        words[0] = 0xAA;
+        words[32] = 0xBB;
+        words[64] = 0xCC;
+        unsafe { swap_words(words.as_mut_ptr(), words.len() as u64) };
+        assert_eq!((words[31], words[63], words[64]), (0xAA, 0xBB, 0xCC));
+    }
+}
@@ -0,0 +1,139 @@
+//! Event logs emitted by the LOG0..LOG4 opcodes.
+//!
+//! The compiler hands over the topics as big endian words and the data as it
+//! sits in contract memory, so every full word of data is reversed into ABI
+//! order before it is kept. On Solana logs go to `sol_log_data`, topics first
+//! and data last. Host builds keep them in a buffer that is read back with
+//! `log_count` and `log_get` and emptied with `log_clear`, each entry laid out
+//! as
+//!
+//! ```text
+//! topic count: u64, topics: [[u8; 32]; count], data length: u64, data
+//! ```
+
+#[cfg(not(feature = "solana"))]
+const LOG_SIZE: usize = 16 * 1024;
+
+#[cfg(not(feature = "solana"))]
+static mut LOG: [u8; LOG_SIZE] = [0; LOG_SIZE];
+#[cfg(not(feature = "solana"))]
+static mut LOG_LEN: usize = 0;
+#[cfg(not(feature = "solana"))]
+static mut LOG_COUNT: u64 = 0;
+
+#[cfg(feature = "solana")]
+extern "C" {
+    fn sol_log_data(data: *const crate::keccak::SolBytes, data_len: u64);
+}
+
+/// Emit a log of `n` topics at `topics` and `len` bytes of memory at `data`.
+#[cfg(feature = "solana")]
+#[no_mangle]
+pub unsafe extern "C" fn emit_log(data: *mut u8, len: u64, topics: *const u8, n: u64) {
+    use crate::keccak::SolBytes;
+    let n = n.min(4) as usize;
+    let mut fields = [SolBytes { addr: data, len }; 5];
+    for (i, field) in fields.iter_mut().take(n).enumerate() {
+        *field = SolBytes { addr: topics.add(i * 32), len: 32 };
+    }
+    crate::swap_words(data, len);
+    sol_log_data(fields.as_ptr(), n as u64 + 1);
+    crate::swap_words(data, len);
+}
+
+/// Emit a log of `n` topics at `topics` and `len` bytes of memory at `data`.
+/// Logs that do not fit in the buffer are dropped.
+#[cfg(not(feature = "solana"))]
+#[no_mangle]
+pub unsafe extern "C" fn emit_log(data: *mut u8, len: u64, topics: *const u8, n: u64) {
+    let size = 16 + 32 * n as usize + len as usize;
+    if LOG_LEN + size > LOG_SIZE {
+        return;
+    }
+    let log = &mut *core::ptr::addr_of_mut!(LOG);
+    let entry = &mut log[LOG_LEN..LOG_LEN + size];
+    let (count, rest) = entry.split_at_mut(8);
+    count.copy_from_slice(&n.to_le_bytes());
+    let (t, rest) = rest.split_at_mut(32 * n as usize);
+    t.copy_from_slice(core::slice::from_raw_parts(topics, t.len()));
+    let (data_len, d) = rest.split_at_mut(8);
+    data_len.copy_from_slice(&len.to_le_bytes());
+    d.copy_from_slice(core::slice::from_raw_parts(data, d.len()));
+    crate::swap_words(d.as_mut_ptr(), len);
+    LOG_LEN += size;
+    LOG_COUNT += 1;
+}
+
+#[cfg(not(feature = "solana"))]
+unsafe fn read_u64(at: usize) -> u64 {
+    u64::from_le(core::ptr::read_unaligned((core::ptr::addr_of!(LOG) as *const u8).add(at) as *const u64))
+}
+
+/// Number of logs emitted since the last `log_clear`.
+#[cfg(not(feature = "solana"))]
+#[no_mangle]
+pub unsafe extern "C" fn log_count() -> u64 {
+    LOG_COUNT
+}
+
+/// Point `topics` and `data` at the `i`th log. Returns 0 if there is no such log.
+#[cfg(not(feature = "solana"))]
+#[no_mangle]
+pub unsafe extern "C" fn log_get(
+    i: u64,
+    topics: *mut *const u8,
+    n: *mut u64,
+    data: *mut *const u8,
+    len: *mut u64,
+) -> i32 {
+    if i >= LOG_COUNT {
+        return 0;
+    }
+    let base = core::ptr::addr_of!(LOG) as *const u8;
+    let mut at = 0;
+    for _ in 0..i {
+        at += 8 + 32 * read_u64(at) as usize;
+        at += 8 + read_u64(at) as usize;
+    }
+    *n = read_u64(at);
+    *topics = base.add(at + 8);
+    at += 8 + 32 * *n as usize;
+    *len = read_u64(at);
+    *data = base.add(at + 8);
+    1
+}
+
+/// Drop all buffered logs.
+#[cfg(not(feature = "solana"))]
+#[no_mangle]
+pub unsafe extern "C" fn log_clear() {
+    LOG_LEN = 0;
+    LOG_COUNT = 0;
+}
+
+#[cfg(all(test, not(feature = "solana")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_emit_log() {
+        let mut data = [0u8; 32];
+        data[0] = 7;
+        let topics = [[1u8; 32], [2u8; 32]];
+        let (mut t, mut n, mut d, mut len) = (core::ptr::null(), 0, core::ptr::null(), 0);
+        unsafe {
+            log_clear();
+            emit_log(data.as_mut_ptr(), 0, topics[0].as_ptr(), 1);
+            emit_log(data.as_mut_ptr(), 32, topics.as_ptr() as *const u8, 2);
+            assert_eq!(log_count(), 2);
+            assert_eq!(log_get(2, &mut t, &mut n, &mut d, &mut len), 0);
+            assert_eq!(log_get(1, &mut t, &mut n, &mut d, &mut len), 1);
+            assert_eq!(n, 2);
+            assert_eq!(core::slice::from_raw_parts(t, 64)[32..], [2u8; 32]);
+            // data is handed back in ABI order
+            assert_eq!(core::slice::from_raw_parts(d, len as usize)[31], 7);
+            log_clear();
+            assert_eq!(log_count(), 0);
+        }
+    }
+}
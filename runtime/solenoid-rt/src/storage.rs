@@ -0,0 +1,307 @@
+//! Contract storage.
+//!
+//! Storage is a table of 64 byte slots, each a 32 byte key followed by its
+//! 32 byte value. Keys are compared as raw bytes. The table is laid out to
+//! live directly in Solana account data: slot 0 belongs to the zero key, and
+//! since that key needs no storing, its key field holds the header instead.
+//!
+//! ```text
+//! 0..4    magic "SOLN"
+//! 4..6    version
+//! 6..8    flags
+//! 8..12   slots in use, including slot 0
+//! 12..16  slots that fit in the account
+//! 16..24  size in bytes the account may grow to in this transaction
+//! 24      number of shards
+//! ```
+//!
+//! A zeroed table is initialized on first use. On Solana a full table grows
+//! by reallocating its account, `GROW_SLOTS` at a time, up to the limit
+//! passed to `storage_bind`. Contracts with large mappings can spread their
+//! keys over several accounts: every key is routed by its low byte to one of
+//! the shards, shard 0 being the table handed to the contract and the others
+//! registered by the entrypoint with `storage_add_shard`.
+
+use crate::revert;
+
+pub const SLOTS: usize = 1024;
+pub const SLOT_SIZE: usize = 64;
+
+pub const MAGIC: [u8; 4] = *b"SOLN";
+pub const VERSION: u16 = 1;
+pub const FLAG_SHARDED: u16 = 1;
+pub const MAX_SHARDS: usize = 16;
+
+/// Slots added every time the account is reallocated.
+#[cfg(feature = "solana")]
+const GROW_SLOTS: u64 = 16;
+
+/// Storage table used by host builds. On Solana the table lives in account data.
+#[cfg(not(feature = "solana"))]
+#[no_mangle]
+#[allow(non_upper_case_globals)]
+pub static mut storage: [u8; SLOTS * SLOT_SIZE] = [0; SLOTS * SLOT_SIZE];
+
+/// Shards registered in this transaction, other than shard 0.
+static mut SHARDS: [*mut u8; MAX_SHARDS] = [core::ptr::null_mut(); MAX_SHARDS];
+static mut SHARD_COUNT: usize = 0;
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+pub struct Header {
+    pub magic: [u8; 4],
+    pub version: u16,
+    pub flags: u16,
+    pub count: u32,
+    pub capacity: u32,
+    pub limit: u64,
+    pub shards: u8,
+    reserved: [u8; 7],
+}
+
+pub unsafe fn header(st: *const u8) -> Header {
+    core::ptr::read_unaligned(st as *const Header)
+}
+
+unsafe fn set_header(st: *mut u8, header: &Header) {
+    core::ptr::write_unaligned(st as *mut Header, *header);
+}
+
+/// Size of the account data at `st`. Solana serializes it right before the data.
+#[cfg(feature = "solana")]
+unsafe fn data_len(st: *const u8) -> u64 {
+    core::ptr::read_unaligned(st.sub(8) as *const u64)
+}
+
+#[cfg(not(feature = "solana"))]
+unsafe fn data_len(_st: *const u8) -> u64 {
+    (SLOTS * SLOT_SIZE) as u64
+}
+
+/// Initialize the table at `st` if it is zeroed, otherwise check its header,
+/// and record how far it may grow. Returns 0 if `st` is not a storage table.
+#[no_mangle]
+pub unsafe extern "C" fn storage_bind(st: *mut u8, len: u64, limit: u64, shards: u8) -> i32 {
+    let mut h = header(st);
+    if h.magic == [0; 4] {
+        if len < SLOT_SIZE as u64 || shards as usize > MAX_SHARDS {
+            return 0;
+        }
+        h = Header {
+            magic: MAGIC,
+            version: VERSION,
+            flags: if shards > 1 { FLAG_SHARDED } else { 0 },
+            count: 1,
+            capacity: (len / SLOT_SIZE as u64) as u32,
+            limit,
+            shards: shards.max(1),
+            reserved: [0; 7],
+        };
+    } else if h.magic != MAGIC || h.version != VERSION {
+        return 0;
+    }
+    h.limit = limit;
+    set_header(st, &h);
+    1
+}
+
+/// Bind the table at `st` as the next shard. Returns 0 if there are too many
+/// shards or `st` is not a storage table.
+#[no_mangle]
+pub unsafe extern "C" fn storage_add_shard(st: *mut u8, len: u64, limit: u64) -> i32 {
+    if SHARD_COUNT + 1 >= MAX_SHARDS || storage_bind(st, len, limit, 1) == 0 {
+        return 0;
+    }
+    SHARDS[SHARD_COUNT] = st;
+    SHARD_COUNT += 1;
+    1
+}
+
+unsafe fn ensure_bound(st: *mut u8) {
+    if header(st).magic == [0; 4] {
+        let len = data_len(st);
+        storage_bind(st, len, len, 1);
+    }
+}
+
+/// Table holding `key`, or `None` if its shard was not registered.
+unsafe fn route(st: *mut u8, key: &[u8]) -> Option<*mut u8> {
+    ensure_bound(st);
+    let h = header(st);
+    if h.flags & FLAG_SHARDED == 0 {
+        return Some(st);
+    }
+    match key[0] as usize % h.shards as usize {
+        0 => Some(st),
+        i if i <= SHARD_COUNT => {
+            let shard = SHARDS[i - 1];
+            ensure_bound(shard);
+            Some(shard)
+        }
+        _ => None,
+    }
+}
+
+unsafe fn find(st: *const u8, key: &[u8]) -> Option<usize> {
+    if key.iter().all(|&b| b == 0) {
+        return Some(0);
+    }
+    (1..header(st).count as usize)
+        .map(|i| i * SLOT_SIZE)
+        .find(|&loc| core::slice::from_raw_parts(st.add(loc), 32) == key)
+}
+
+/// Make room for one more slot, reallocating the account on Solana.
+#[cfg(feature = "solana")]
+unsafe fn grow(st: *mut u8, h: &mut Header) -> bool {
+    let capacity = core::cmp::min(h.capacity as u64 + GROW_SLOTS, h.limit / SLOT_SIZE as u64);
+    if capacity <= h.capacity as u64 {
+        return false;
+    }
+    core::ptr::write_unaligned(st.sub(8) as *mut u64, capacity * SLOT_SIZE as u64);
+    h.capacity = capacity as u32;
+    true
+}
+
+#[cfg(not(feature = "solana"))]
+unsafe fn grow(_st: *mut u8, _h: &mut Header) -> bool {
+    false
+}
+
+/// Load the value stored under the key at `key`, overwriting the key.
+#[no_mangle]
+pub unsafe extern "C" fn sload(st: *mut u8, key: *mut u8) {
+    let key = core::slice::from_raw_parts_mut(key, 32);
+    let st = match route(st, key) {
+        Some(st) => st,
+        None => {
+            // the host keeps running after revert, so do not leave the key as the value
+            key.iter_mut().for_each(|b| *b = 0);
+            return revert();
+        }
+    };
+    match find(st, key) {
+        Some(loc) => key.copy_from_slice(core::slice::from_raw_parts(st.add(loc + 32), 32)),
+        None => key.iter_mut().for_each(|b| *b = 0),
+    }
+}
+
+/// Store the value at `val` under the key at `key`.
+#[no_mangle]
+pub unsafe extern "C" fn sstore(st: *mut u8, key: *mut u8, val: *mut u8) {
+    let key = core::slice::from_raw_parts(key, 32);
+    let st = match route(st, key) {
+        Some(st) => st,
+        None => return revert(),
+    };
+    let loc = match find(st, key) {
+        Some(loc) => loc,
+        None => {
+            let mut h = header(st);
+            if h.count == h.capacity && !grow(st, &mut h) {
+                return revert();
+            }
+            let loc = h.count as usize * SLOT_SIZE;
+            h.count += 1;
+            set_header(st, &h);
+            core::ptr::copy_nonoverlapping(key.as_ptr(), st.add(loc), 32);
+            loc
+        }
+    };
+    core::ptr::copy_nonoverlapping(val, st.add(loc + 32), 32);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn word(x: u8) -> [u8; 32] {
+        let mut w = [0u8; 32];
+        w[0] = x;
+        w
+    }
+
+    #[test]
+    fn test_sstore_sload() {
+        let mut st = vec![0u8; SLOTS * SLOT_SIZE];
+        let mut key = word(1);
+        let mut val = word(0x41);
+
+        let mut slot = key;
+        unsafe { sload(st.as_mut_ptr(), slot.as_mut_ptr()) };
+        assert_eq!(slot, [0u8; 32]);
+
+        unsafe { sstore(st.as_mut_ptr(), key.as_mut_ptr(), val.as_mut_ptr()) };
+        let mut slot = key;
+        unsafe { sload(st.as_mut_ptr(), slot.as_mut_ptr()) };
+        assert_eq!(slot, val);
+    }
+
+    #[test]
+    fn test_header() {
+        let mut st = vec![0u8; SLOTS * SLOT_SIZE];
+        let (mut zero, mut one, mut val) = (word(0), word(1), word(7));
+        unsafe {
+            sstore(st.as_mut_ptr(), zero.as_mut_ptr(), val.as_mut_ptr());
+            sstore(st.as_mut_ptr(), one.as_mut_ptr(), val.as_mut_ptr());
+            sstore(st.as_mut_ptr(), one.as_mut_ptr(), val.as_mut_ptr());
+        }
+        let h = unsafe { header(st.as_ptr()) };
+        assert_eq!(h.magic, MAGIC);
+        assert_eq!(h.version, VERSION);
+        assert_eq!(h.count, 2);
+        assert_eq!(h.capacity as usize, SLOTS);
+        // the zero key keeps slot 0
+        assert_eq!(st[32..64], val);
+
+        st[0] = b'X';
+        assert_eq!(unsafe { storage_bind(st.as_mut_ptr(), st.len() as u64, 0, 1) }, 0);
+    }
+
+    #[test]
+    fn test_full_table() {
+        let mut st = vec![0u8; 2 * SLOT_SIZE];
+        let len = st.len() as u64;
+        assert_eq!(unsafe { storage_bind(st.as_mut_ptr(), len, len, 1) }, 1);
+        let (mut one, mut two, mut val) = (word(1), word(2), word(7));
+        unsafe {
+            sstore(st.as_mut_ptr(), one.as_mut_ptr(), val.as_mut_ptr());
+            sstore(st.as_mut_ptr(), two.as_mut_ptr(), val.as_mut_ptr());
+            sload(st.as_mut_ptr(), two.as_mut_ptr());
+        }
+        assert_eq!(two, [0u8; 32]);
+    }
+
+    #[test]
+    fn test_shards() {
+        let mut st = vec![0u8; SLOTS * SLOT_SIZE];
+        let mut shard = vec![0u8; SLOTS * SLOT_SIZE];
+        let len = st.len() as u64;
+        unsafe {
+            assert_eq!(storage_bind(st.as_mut_ptr(), len, len, 2), 1);
+            assert_eq!(storage_add_shard(shard.as_mut_ptr(), len, len), 1);
+        }
+        let (mut even, mut odd, mut val) = (word(2), word(3), word(9));
+        unsafe {
+            sstore(st.as_mut_ptr(), even.as_mut_ptr(), val.as_mut_ptr());
+            sstore(st.as_mut_ptr(), odd.as_mut_ptr(), val.as_mut_ptr());
+        }
+        assert_eq!(st[64..96], even);
+        assert_eq!(shard[64..96], odd);
+
+        unsafe { sload(st.as_mut_ptr(), odd.as_mut_ptr()) };
+        assert_eq!(odd, val);
+    }
+
+    #[test]
+    fn test_missing_shard() {
+        let mut st = vec![0u8; SLOTS * SLOT_SIZE];
+        let len = st.len() as u64;
+        let mut key = word(MAX_SHARDS as u8 - 1);
+        unsafe {
+            assert_eq!(storage_bind(st.as_mut_ptr(), len, len, MAX_SHARDS as u8), 1);
+            sload(st.as_mut_ptr(), key.as_mut_ptr());
+        }
+        assert_eq!(key, [0u8; 32]);
+    }
+}
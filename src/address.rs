@@ -0,0 +1,138 @@
+//! Mapping between 20 byte EVM addresses and 32 byte Solana public keys.
+//!
+//! An address is derived from a pubkey the way Ethereum derives it from a
+//! public key: the last 20 bytes of its keccak256 hash. The derivation is one
+//! way, so the reverse lookup goes through a registry kept in account data.
+//! CALL targets are not mapped, as message calls are not compiled.
+//!
+//! Registry layout, shared with `solenoid-rt`:
+//!
+//! ```text
+//! count: u32 (little endian)
+//! count * (address: [u8; 20], pubkey: [u8; 32])
+//! ```
+
+use ethereum_types::Address;
+use tiny_keccak::Keccak;
+
+/// Solana public key
+pub type Pubkey = [u8; 32];
+
+const HEADER_SIZE: usize = 4;
+const ENTRY_SIZE: usize = 20 + 32;
+
+/// Derive the EVM address of a Solana public key
+pub fn pubkey_to_address(pubkey: &Pubkey) -> Address {
+    let mut hash = [0u8; 32];
+    let mut sponge = Keccak::new_keccak256();
+    sponge.update(pubkey);
+    sponge.finalize(&mut hash);
+    Address::from_slice(&hash[12..])
+}
+
+/// Reverse lookup table from addresses to the pubkeys they were derived from
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Registry {
+    entries: Vec<(Address, Pubkey)>,
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register `pubkey` and return its address
+    pub fn register(&mut self, pubkey: &Pubkey) -> Address {
+        let address = pubkey_to_address(pubkey);
+        if self.lookup(&address).is_none() {
+            self.entries.push((address, *pubkey));
+        }
+        address
+    }
+
+    /// Find the pubkey registered under `address`
+    pub fn lookup(&self, address: &Address) -> Option<&Pubkey> {
+        self.entries.iter()
+            .find(|(a, _)| a == address)
+            .map(|(_, pubkey)| pubkey)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Account data size needed to hold `n` entries
+    pub fn account_size(n: usize) -> usize {
+        HEADER_SIZE + n * ENTRY_SIZE
+    }
+
+    /// Read a registry from account data
+    pub fn from_bytes(data: &[u8]) -> Option<Self> {
+        if data.len() < HEADER_SIZE {
+            return None;
+        }
+        let mut count = [0u8; HEADER_SIZE];
+        count.copy_from_slice(&data[..HEADER_SIZE]);
+        let count = u32::from_le_bytes(count) as usize;
+        if data.len() < Self::account_size(count) {
+            return None;
+        }
+
+        let entries = data[HEADER_SIZE..Self::account_size(count)]
+            .chunks(ENTRY_SIZE)
+            .map(|entry| {
+                let mut pubkey = [0u8; 32];
+                pubkey.copy_from_slice(&entry[20..]);
+                (Address::from_slice(&entry[..20]), pubkey)
+            })
+            .collect();
+        Some(Self { entries })
+    }
+
+    /// Serialize into account data
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut data = Vec::with_capacity(Self::account_size(self.len()));
+        data.extend_from_slice(&(self.len() as u32).to_le_bytes());
+        for (address, pubkey) in &self.entries {
+            data.extend_from_slice(address.as_bytes());
+            data.extend_from_slice(pubkey);
+        }
+        data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hex::FromHex;
+
+    #[test]
+    fn test_pubkey_to_address() {
+        let address = pubkey_to_address(&[0u8; 32]);
+        let expected: Vec<u8> = "88386fc84ba6bc95484008f6362f93160ef3e563".from_hex().unwrap();
+        assert_eq!(address.as_bytes(), &expected[..]);
+    }
+
+    #[test]
+    fn test_registry() {
+        let mut registry = Registry::new();
+        let alice = [1u8; 32];
+        let bob = [2u8; 32];
+        let alice_addr = registry.register(&alice);
+        let bob_addr = registry.register(&bob);
+        registry.register(&alice);
+        assert_eq!(registry.len(), 2);
+
+        let data = registry.to_bytes();
+        assert_eq!(data.len(), Registry::account_size(2));
+        let registry = Registry::from_bytes(&data).unwrap();
+        assert_eq!(registry.lookup(&alice_addr), Some(&alice));
+        assert_eq!(registry.lookup(&bob_addr), Some(&bob));
+        assert_eq!(registry.lookup(&Address::zero()), None);
+        assert_eq!(Registry::from_bytes(&data[..data.len() - 1]), None);
+    }
+}
@@ -0,0 +1,226 @@
+//! Load contracts from the JSON artifacts of other toolchains.
+//!
+//! Foundry (`out/<Source>.sol/<Name>.json`), Hardhat (`artifacts/**/<Name>.json`)
+//! and Truffle (`build/contracts/<Name>.json`) artifacts are mapped into
+//! `solc::Contract`, keeping the bytecode they were built with.
+
+use serde::Deserialize;
+use serde_json::Value;
+use crate::solc::{self, Contract, LinkReferences};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug)]
+pub enum ArtifactError {
+    IOError(PathBuf, std::io::Error),
+    InvalidJson(PathBuf, serde_json::Error),
+    /// Not an artifact of a known toolchain
+    UnknownFormat(PathBuf),
+}
+
+impl std::fmt::Display for ArtifactError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::IOError(path, err) => write!(f, "Unable to read {}: {}", path.display(), err),
+            Self::InvalidJson(path, err) => write!(f, "Invalid artifact {}: {}", path.display(), err),
+            Self::UnknownFormat(path) => write!(f, "{} is not a Foundry, Hardhat or Truffle artifact", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for ArtifactError {}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Artifact {
+    contract_name: Option<String>,
+    /// Hardhat
+    source_name: Option<String>,
+    /// Truffle
+    source_path: Option<String>,
+    abi: Option<Value>,
+    bytecode: Option<Bytecode>,
+    deployed_bytecode: Option<Bytecode>,
+    /// Hardhat keeps link references beside the bytecode
+    link_references: Option<LinkReferences>,
+    deployed_link_references: Option<LinkReferences>,
+    /// Truffle keeps source maps beside the bytecode
+    source_map: Option<String>,
+    deployed_source_map: Option<String>,
+    #[serde(default)]
+    method_identifiers: BTreeMap<String, String>,
+    storage_layout: Option<Value>,
+    /// solc metadata, a string in Truffle artifacts
+    metadata: Option<Value>,
+}
+
+/// Hex string for Hardhat and Truffle, solc's bytecode object for Foundry
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Bytecode {
+    Hex(String),
+    #[serde(rename_all = "camelCase")]
+    Object {
+        object: String,
+        source_map: Option<String>,
+        link_references: Option<LinkReferences>,
+    },
+}
+
+impl Artifact {
+    /// Bytecode without its `0x` prefix, source map and link references
+    fn split(bytecode: Option<Bytecode>, source_map: Option<String>, refs: Option<LinkReferences>)
+        -> (String, String, Option<LinkReferences>) {
+        let (hex, source_map, refs) = match bytecode {
+            Some(Bytecode::Hex(hex)) => (hex, source_map, refs),
+            Some(Bytecode::Object { object, source_map, link_references }) => (object, source_map, link_references),
+            None => (String::new(), source_map, refs),
+        };
+        (hex.trim_start_matches("0x").to_owned(), source_map.unwrap_or_default(), refs)
+    }
+
+    /// `compilationTarget` of the solc metadata, source and contract name
+    fn compilation_target(&self) -> Option<(String, String)> {
+        let metadata = match &self.metadata {
+            Some(Value::String(metadata)) => serde_json::from_str(metadata).ok()?,
+            Some(metadata) => metadata.clone(),
+            None => return None,
+        };
+        let target = metadata["settings"]["compilationTarget"].as_object()?;
+        let (source, name) = target.iter().next()?;
+        Some((source.clone(), name.as_str()?.to_owned()))
+    }
+}
+
+/// Contract of the artifact at `path`, named `<source>:<name>`
+///
+/// Interfaces and abstract contracts have no bytecode and give `None`.
+pub fn load(path: &Path) -> Result<Option<(String, Contract)>, ArtifactError> {
+    let json = fs::read_to_string(path).map_err(|e| ArtifactError::IOError(path.to_owned(), e))?;
+    let artifact: Artifact = serde_json::from_str(&json).map_err(|e| ArtifactError::InvalidJson(path.to_owned(), e))?;
+    let abi = match (&artifact.abi, &artifact.bytecode) {
+        (Some(abi), Some(_)) => abi.to_string(),
+        _ => return Err(ArtifactError::UnknownFormat(path.to_owned())),
+    };
+
+    // Foundry names the directory after the source and the file after the contract
+    let target = artifact.compilation_target();
+    let name = artifact.contract_name.clone()
+        .or_else(|| target.as_ref().map(|(_, name)| name.clone()))
+        .or_else(|| path.file_stem().map(|stem| stem.to_string_lossy().to_string()))
+        .unwrap_or_default();
+    let source = artifact.source_name.clone()
+        .or_else(|| artifact.source_path.clone())
+        .or_else(|| target.map(|(source, _)| source))
+        .or_else(|| path.parent()?.file_name().map(|dir| dir.to_string_lossy().to_string()))
+        .unwrap_or_default();
+
+    let (bin, srcmap, refs) = Artifact::split(artifact.bytecode, artifact.source_map, artifact.link_references);
+    let (bin_runtime, srcmap_runtime, refs_runtime) =
+        Artifact::split(artifact.deployed_bytecode, artifact.deployed_source_map, artifact.deployed_link_references);
+    if bin.is_empty() {
+        return Ok(None);
+    }
+    // Truffle does not record where its placeholders are
+    let link_references = refs.map(solc::link_references).unwrap_or_else(|| solc::placeholders(&bin));
+    let link_references_runtime = refs_runtime.map(solc::link_references).unwrap_or_else(|| solc::placeholders(&bin_runtime));
+
+    Ok(Some((format!("{}:{}", source, name), Contract {
+        abi,
+        bin,
+        bin_runtime,
+        srcmap,
+        srcmap_runtime,
+        method_identifiers: artifact.method_identifiers,
+        storage_layout: artifact.storage_layout,
+        link_references,
+        link_references_runtime,
+    })))
+}
+
+/// Contracts of the artifact at `path`, or of every artifact under it if a directory
+///
+/// Hardhat debug files, `build-info` directories and JSON files of no known
+/// format met while walking a directory are skipped.
+pub fn load_all(path: &Path) -> Result<BTreeMap<String, Contract>, ArtifactError> {
+    let mut contracts = BTreeMap::new();
+    if !path.is_dir() {
+        contracts.extend(load(path)?);
+        return Ok(contracts);
+    }
+    let mut entries = fs::read_dir(path).map_err(|e| ArtifactError::IOError(path.to_owned(), e))?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| ArtifactError::IOError(path.to_owned(), e))?;
+    entries.sort();
+    for entry in entries {
+        let file_name = entry.file_name().unwrap_or_default().to_string_lossy();
+        if entry.is_dir() {
+            if file_name != "build-info" {
+                contracts.extend(load_all(&entry)?);
+            }
+        } else if file_name.ends_with(".json") && !file_name.ends_with(".dbg.json") {
+            match load(&entry) {
+                Err(ArtifactError::UnknownFormat(_)) | Err(ArtifactError::InvalidJson(..)) => continue,
+                loaded => contracts.extend(loaded?),
+            }
+        }
+    }
+    Ok(contracts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn load_json(name: &str, json: &str) -> Option<(String, Contract)> {
+        let root = std::env::temp_dir().join(format!("solenoid-artifact-{}", std::process::id()));
+        let path = root.join(name).join("C.json");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, json).unwrap();
+        let loaded = load(&path).unwrap();
+        fs::remove_dir_all(&root).unwrap();
+        loaded
+    }
+
+    #[test]
+    fn test_load() {
+        let foundry = r#"{
+            "abi": [],
+            "bytecode": {"object": "0x6080__$0123456789abcdef0123456789abcdef01$__00", "sourceMap": "0:1:0:-:0",
+                "linkReferences": {"src/L.sol": {"L": [{"start": 2, "length": 20}]}}},
+            "deployedBytecode": {"object": "0x6001", "sourceMap": "1:1:0:-:0", "linkReferences": {}},
+            "methodIdentifiers": {"flip()": "cde4efa9"},
+            "metadata": {"settings": {"compilationTarget": {"src/C.sol": "C"}}}
+        }"#;
+        let (name, contract) = load_json("C.sol", foundry).unwrap();
+        assert_eq!(name, "src/C.sol:C");
+        assert_eq!((contract.bin_runtime.as_str(), contract.srcmap.as_str()), ("6001", "0:1:0:-:0"));
+        assert_eq!(contract.method_identifiers["flip()"], "cde4efa9");
+        assert_eq!(contract.unlinked_libraries().into_iter().collect::<Vec<_>>(), ["src/L.sol:L"]);
+
+        let hardhat = r#"{
+            "_format": "hh-sol-artifact-1", "contractName": "C", "sourceName": "contracts/C.sol",
+            "abi": [], "bytecode": "0x6080", "deployedBytecode": "0x6001",
+            "linkReferences": {}, "deployedLinkReferences": {}
+        }"#;
+        let (name, contract) = load_json("C.sol", hardhat).unwrap();
+        assert_eq!(name, "contracts/C.sol:C");
+        assert_eq!((contract.bin.as_str(), contract.bin_runtime.as_str()), ("6080", "6001"));
+        assert!(contract.unlinked_libraries().is_empty());
+
+        let truffle = r#"{
+            "contractName": "C", "sourcePath": "/build/contracts/C.sol", "abi": [],
+            "bytecode": "0x6080", "deployedBytecode": "0x73__L_____________________________________00",
+            "sourceMap": "0:1:0:-:0", "deployedSourceMap": "1:1:0:-:0"
+        }"#;
+        let (name, contract) = load_json("contracts", truffle).unwrap();
+        assert_eq!(name, "/build/contracts/C.sol:C");
+        assert_eq!(contract.link_references_runtime[0].start, 1);
+        assert_eq!(contract.unlinked_libraries().into_iter().collect::<Vec<_>>(), ["L"]);
+
+        let interface = r#"{"contractName": "I", "abi": [], "bytecode": "0x", "deployedBytecode": "0x"}"#;
+        assert!(load_json("I.sol", interface).is_none());
+    }
+}
@@ -1,91 +1,533 @@
 use inkwell::context::Context;
+use inkwell::module::Module;
 use libsolenoid::compiler::Compiler;
-use libsolenoid::solc;
-use libsolenoid::cffi::CFFIGenerator;
+use libsolenoid::solc::{self, Contract};
+use libsolenoid::artifact;
+use libsolenoid::vyper;
+use libsolenoid::runner;
+use libsolenoid::cffi::{self, CFFIGenerator};
+use libsolenoid::rustgen::RustGenerator;
+use libsolenoid::tsgen::TsGenerator;
+use libsolenoid::symbols::Symbols;
+use libsolenoid::config::{self, BuildConfig, Config, Target};
+use libsolenoid::ethabi::{Function, ParamType, Token, token::{LenientTokenizer, Tokenizer}};
 use structopt::StructOpt;
+use std::collections::BTreeMap;
+use std::io::Read;
+use ethereum_types::Address;
 use std::path::PathBuf;
-use uint::rustc_hex::FromHex;
-use libsolenoid::evm::Disassembly;
-use log::{info, debug, error};
+use uint::rustc_hex::{FromHex, ToHex};
+use libsolenoid::evm::{self, asm, Disassembly};
+use log::{info, debug, warn, error};
 
+type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// Contracts to build, from sources, artifacts or deployed bytecode
 #[derive(Debug, StructOpt)]
-#[structopt(name = "solenoid", about = "solenoid compiler toolchain")]
-struct Opt {
-    /// Print opcodes then exit
+struct InputOpt {
+    /// Project configuration, `solenoid.toml` in the working directory by default
+    #[structopt(long, parse(from_os_str))]
+    config: Option<PathBuf>,
+
+    /// Input contract, repeat to compile several sources together. `.vy` files
+    /// are compiled with vyper, JSON files and directories are read as Foundry,
+    /// Hardhat or Truffle artifacts
+    #[structopt(parse(from_os_str))]
     #[structopt(short, long)]
-    print_opcodes: bool,
+    input: Vec<PathBuf>,
+
+    /// Deployed bytecode to compile without its source, hex or a file holding it
+    #[structopt(long, requires_all = &["abi", "name"])]
+    runtime: Option<String>,
+
+    /// Init code of the deployed bytecode, hex or a file holding it
+    #[structopt(long, requires = "runtime")]
+    init: Option<String>,
+
+    /// JSON ABI of the deployed bytecode
+    #[structopt(long, parse(from_os_str))]
+    abi: Option<PathBuf>,
+
+    /// Contract name of the deployed bytecode
+    #[structopt(long)]
+    name: Option<String>,
+
+    /// Import remapping passed to solc, `prefix=path`
+    #[structopt(long = "remap")]
+    remappings: Vec<String>,
+
+    /// Enable the solc optimizer, tuned for this many runs
+    #[structopt(long)]
+    optimize_runs: Option<u32>,
+
+    /// EVM version solc targets
+    #[structopt(long)]
+    evm_version: Option<String>,
+
+    /// Compile through the solc IR pipeline, true or false, overriding the configuration
+    #[structopt(long)]
+    via_ir: Option<bool>,
+
+    /// Address of a linked library, `[<source>:]<library>=<address>`
+    #[structopt(long = "library", parse(try_from_str = solc::parse_library))]
+    libraries: Vec<(String, Address)>,
 
+    /// solc binary to compile with, a path or an installed version
+    #[structopt(long)]
+    solc: Option<String>,
+
+    /// Directory of installed solc versions, `~/.solenoid/solc` by default
+    #[structopt(long, parse(from_os_str))]
+    solc_dir: Option<PathBuf>,
+
+    /// Contract to compile, by name or `<source>:<name>`, with the contracts it
+    /// creates and the libraries it links. Repeat for several, all by default
+    #[structopt(long = "contract")]
+    contracts: Vec<String>,
+}
+
+/// What is generated besides the LLVM module
+#[derive(Debug, StructOpt)]
+struct BuildOpt {
     /// Debug
     #[structopt(short, long)]
     debug: bool,
 
-    /// Input contract
-    #[structopt(parse(from_os_str))]
+    /// Contract exposed as the Solana program entrypoint
     #[structopt(short, long)]
-    input: Option<PathBuf>,
+    entrypoint: Option<String>,
+
+    /// Number of accounts the entrypoint contract storage is sharded across, 1 to 16
+    #[structopt(long, default_value = "1", parse(try_from_str = cffi::parse_shards))]
+    shards: u8,
+
+    /// Also generate TypeScript client bindings
+    #[structopt(long)]
+    typescript: bool,
+
+    /// Mapping from function signature to ABI helper symbol, to pin symbol names
+    #[structopt(parse(from_os_str))]
+    #[structopt(long)]
+    symbols: Option<PathBuf>,
+
+    /// Default target of the generated Makefile, host or bpf
+    #[structopt(long)]
+    target: Option<Target>,
+
+    /// Optimization level of llc and the C sources
+    #[structopt(short = "O", long, possible_values = &["0", "1", "2", "3"])]
+    opt_level: Option<u8>,
 
-    /// Output directory
+    /// Cargo feature to build the runtime with, repeat for several
+    #[structopt(long = "feature", possible_values = config::RUNTIME_FEATURES)]
+    features: Vec<String>,
+
+    /// Directory holding llc and ld.lld, found on PATH by default
+    #[structopt(long, parse(from_os_str))]
+    llvm_dir: Option<PathBuf>,
+}
+
+#[derive(Debug, StructOpt)]
+struct CompileOpt {
+    #[structopt(flatten)]
+    input: InputOpt,
+
+    #[structopt(flatten)]
+    build: BuildOpt,
+
+    /// Output directory, `out` by default
     #[structopt(parse(from_os_str))]
     #[structopt(short, long)]
     output_dir: Option<PathBuf>,
+}
 
-    /// Compile raw hex-encoded opcodes
+#[derive(Debug, StructOpt)]
+enum AbiCommand {
+    /// Encode the calldata of a call, e.g. `transfer(address,uint256) 0x.. 100`
+    Encode {
+        /// Function signature, in the human-readable ABI form
+        signature: String,
+        args: Vec<String>,
+    },
+    /// Decode the return data of a function as JSON
+    Decode {
+        /// Function signature, in the human-readable ABI form
+        signature: String,
+        /// Data, hex or a file holding hex
+        data: String,
+        /// Decode calldata, starting with the selector, instead
+        #[structopt(long)]
+        calldata: bool,
+    },
+}
+
+#[derive(Debug, StructOpt)]
+struct RunOpt {
+    #[structopt(flatten)]
+    input: InputOpt,
+
+    #[structopt(flatten)]
+    build: BuildOpt,
+
+    /// Contract to deploy
+    contract: String,
+
+    /// Calldata of the call, hex or a file holding hex
+    #[structopt(long, default_value = "")]
+    calldata: String,
+
+    /// ABI encoded constructor arguments, hex or a file holding hex
+    #[structopt(long, default_value = "")]
+    ctor_args: String,
+
+    /// Caller address, zero by default
     #[structopt(long)]
-    opcodes: Option<String>,
+    caller: Option<String>,
+
+    /// Address of the contract, zero by default
+    #[structopt(long)]
+    address: Option<String>,
+
+    /// Output directory, a temporary one by default
+    #[structopt(parse(from_os_str))]
+    #[structopt(short, long)]
+    output_dir: Option<PathBuf>,
 }
 
-fn main() {
-    env_logger::init();
+#[derive(Debug, StructOpt)]
+#[structopt(name = "solenoid", about = "solenoid compiler toolchain")]
+enum Opt {
+    /// Compile contracts into LLVM IR, C headers and bindings
+    Compile(CompileOpt),
+    /// Print the offset and instruction of every opcode in EVM bytecode
+    Disasm {
+        /// Bytecode, hex or files holding hex
+        #[structopt(required = true)]
+        bytecode: Vec<String>,
+        /// Print as assembly with labels, which `asm` reads back
+        #[structopt(long)]
+        asm: bool,
+    },
+    /// Assemble instructions, one per line with labels and comments, into hex bytecode
+    Asm {
+        /// Assembly file, stdin by default
+        #[structopt(parse(from_os_str))]
+        input: Option<PathBuf>,
+    },
+    /// Encode and decode ABI data
+    Abi(AbiCommand),
+    /// Deploy a contract on the host, make one call and print what it returned
+    ///
+    /// Exits with 2 if the call reverts.
+    Run(RunOpt),
+}
 
-    let opt = Opt::from_args();
-    let outdir = opt.output_dir.unwrap_or(PathBuf::from("out/"));
-    let context = Context::create();
-    let module = context.create_module("contracts");
+/// Hex string without its `0x` prefix, read from the file `arg` names if there is one
+fn read_hex(arg: &str) -> std::result::Result<String, String> {
+    let text = std::fs::read_to_string(arg).unwrap_or_else(|_| arg.to_owned());
+    let hex = text.trim().trim_start_matches("0x").to_owned();
+    match hex.from_hex::<Vec<u8>>() {
+        Ok(_) => Ok(hex),
+        Err(_) => Err(format!("{} is neither hex nor a file holding hex", arg)),
+    }
+}
+
+fn read_bytes(arg: &str) -> std::result::Result<Vec<u8>, String> {
+    Ok(read_hex(arg)?.from_hex::<Vec<u8>>().unwrap_or_default())
+}
+
+/// 20 byte address given for `what`, zero if there is none
+fn read_address(what: &str, arg: &Option<String>) -> std::result::Result<[u8; 20], String> {
+    let mut address = [0u8; 20];
+    if let Some(arg) = arg {
+        let bytes = read_bytes(arg)?;
+        if bytes.len() != 20 {
+            return Err(format!("{} must be 20 bytes", what));
+        }
+        address.copy_from_slice(&bytes);
+    }
+    Ok(address)
+}
+
+/// `Name` of a contract keyed `<source>:Name`
+fn contract_name(name: &str) -> &str {
+    name.rsplit(':').next().unwrap_or(name)
+}
+
+/// Fill what the command line leaves unset from the configuration
+fn apply_config(opt: &mut InputOpt, config: &Config) -> Result<()> {
+    if opt.input.is_empty() && opt.runtime.is_none() {
+        opt.input = config.sources.clone();
+    }
+    if opt.remappings.is_empty() {
+        opt.remappings = config.solc.remappings.clone();
+    }
+    opt.optimize_runs = opt.optimize_runs.or(config.solc.optimize_runs);
+    opt.evm_version = opt.evm_version.take().or_else(|| config.solc.evm_version.clone());
+    opt.via_ir = opt.via_ir.or(Some(config.solc.via_ir));
+    opt.solc = opt.solc.take().or_else(|| config.solc.version.clone());
+    opt.solc_dir = opt.solc_dir.take().or_else(|| config.solc.dir.clone());
+    for (name, address) in &config.solc.libraries {
+        if !opt.libraries.iter().any(|(library, _)| library == name) {
+            opt.libraries.push(solc::parse_library(&format!("{}={}", name, address))?);
+        }
+    }
+    if opt.contracts.is_empty() {
+        opt.contracts = config.contracts.clone();
+    }
+    Ok(())
+}
+
+fn build_config(opt: &BuildOpt, config: &Config) -> BuildConfig {
+    BuildConfig {
+        target: opt.target.unwrap_or(config.build.target),
+        opt_level: opt.opt_level.unwrap_or(config.build.opt_level),
+        features: if opt.features.is_empty() { config.build.features.clone() } else { opt.features.clone() },
+        llvm_dir: opt.llvm_dir.clone().or_else(|| config.build.llvm_dir.clone()),
+    }
+}
+
+/// Compile, load and link the contracts `opt` describes
+fn load_contracts(opt: &InputOpt) -> Result<BTreeMap<String, Contract>> {
+    if opt.input.is_empty() && opt.runtime.is_none() {
+        return Err(format!("Nothing to do, pass --input or --runtime or list sources in {}", config::FILE_NAME).into());
+    }
+    let options = solc::SolcOptions {
+        remappings: opt.remappings.clone(),
+        optimizer_runs: opt.optimize_runs,
+        evm_version: opt.evm_version.clone(),
+        via_ir: opt.via_ir.unwrap_or(false),
+        solc: opt.solc.clone(),
+        solc_dir: opt.solc_dir.clone(),
+    };
+    let has_extension = |path: &PathBuf, extension: &str| path.extension().map_or(false, |ext| ext == extension);
+    let (artifacts, sources): (Vec<_>, Vec<_>) = opt.input.iter().cloned()
+        .partition(|path| path.is_dir() || has_extension(path, "json"));
+    let (vyper_sources, sources): (Vec<_>, Vec<_>) = sources.into_iter()
+        .partition(|path| has_extension(path, "vy"));
+    let mut contracts = BTreeMap::new();
+    if !sources.is_empty() {
+        let output = solc::compile(&sources, &options)?;
+        for diagnostic in &output.diagnostics {
+            warn!("{}", diagnostic);
+        }
+        contracts.extend(output.contracts);
+    }
+    for path in &vyper_sources {
+        let (name, contract) = vyper::compile(path)?;
+        contracts.insert(name, contract);
+    }
+    for path in &artifacts {
+        contracts.extend(artifact::load_all(path)?);
+    }
+    if let (Some(runtime), Some(abi), Some(name)) = (&opt.runtime, &opt.abi, &opt.name) {
+        let abi = std::fs::read_to_string(abi)
+            .map_err(|e| format!("Unable to read {}: {}", abi.display(), e))?;
+        let init = opt.init.as_deref().map(read_hex).transpose()?;
+        let contract = Contract::from_bytecode(abi, read_hex(runtime)?, init)
+            .map_err(|e| format!("Unable to compile {}: {}", name, e))?;
+        contracts.insert(name.clone(), contract);
+    }
+    if !opt.contracts.is_empty() {
+        contracts = solc::select(contracts, &opt.contracts)?;
+        info!("Compiling {}", contracts.keys().cloned().collect::<Vec<_>>().join(", "));
+    }
+    // generated symbols are named after the contract alone
+    let mut names = BTreeMap::new();
+    for name in contracts.keys() {
+        if let Some(first) = names.insert(contract_name(name), name) {
+            return Err(format!("{} and {} are both named {}, select one with --contract", first, name, contract_name(name)).into());
+        }
+    }
+    let libraries = opt.libraries.iter().cloned().collect();
+    for (name, contract) in &mut contracts {
+        contract.link(&libraries);
+        let unlinked = contract.unlinked_libraries();
+        if !unlinked.is_empty() {
+            let libraries = unlinked.into_iter().collect::<Vec<_>>().join(", ");
+            return Err(format!("{} has unresolved library references: {}, pass their addresses with --library", name, libraries).into());
+        }
+    }
+    Ok(contracts)
+}
+
+/// Compile `contracts` into `module` and generate the output directory
+fn build<'ctx>(context: &'ctx Context, module: &Module<'ctx>, contracts: &BTreeMap<String, Contract>, opt: &BuildOpt, config: &BuildConfig, outdir: &PathBuf) -> Result<()> {
     let builder = context.create_builder();
     let mut ffi = CFFIGenerator::new();
+    let mut rustgen = RustGenerator::new();
+    let mut tsgen = TsGenerator::new();
+    let mut symbols = match &opt.symbols {
+        Some(path) => Symbols::load(path)?,
+        None => Symbols::new(),
+    };
 
-    if let Some(hex_opcodes) = opt.opcodes {
-        let bytes: Vec<u8> = hex_opcodes.from_hex().expect("Invalid Hex String");
-        let instrs =  Disassembly::from_bytes(&bytes).unwrap().instructions;
-
-        let mut compiler = Compiler::new(&context, &module, false);
-        compiler.compile(&builder, &instrs, &bytes, "test", false);
-        // compiler.dbg();
-        module.print_to_file("out.ll").unwrap();
-    } else if let Some(input) = &opt.input {
-        let contracts = solc::solc_compile(input);
-        for (name, contract) in &contracts {
-            let abi = libsolenoid::ethabi::Contract::load(contract.abi.as_bytes()).unwrap();
-
-            let contract_name = name.split(":").last().unwrap();
-            ffi.add_contract_name(contract_name);
-            let mut compiler = Compiler::new(&context, &module, opt.debug);
-            let (ctor_bytes, rt_bytes, ctor_opcodes, rt_opcodes) = contract.parse();
-
-            debug!("Constructor instrs: {:#?}", ctor_opcodes);
-            debug!("Runtime instrs: {:#?}", rt_opcodes);
-
-            if opt.print_opcodes {
-                continue;
-            }
+    for (name, contract) in contracts {
+        let abi = libsolenoid::ethabi::Contract::load(contract.abi.as_bytes())
+            .map_err(|e| format!("Invalid ABI of {}: {}", name, e))?;
+
+        let contract_name = contract_name(name);
+        ffi.add_contract_name(contract_name);
+        let mut compiler = Compiler::new(context, module, opt.debug);
+        let (ctor_bytes, rt_bytes, ctor_opcodes, rt_opcodes) = contract.parse()
+            .map_err(|e| format!("Unable to disassemble {}: {}", name, e))?;
+
+        debug!("Constructor instrs: {:#?}", ctor_opcodes);
+        debug!("Runtime instrs: {:#?}", rt_opcodes);
 
-            info!("Compiling {} constructor", contract_name);
-            compiler.compile(&builder, &ctor_opcodes, &ctor_bytes, contract_name, false);
+        info!("Compiling {} constructor", contract_name);
+        compiler.compile(&builder, &ctor_opcodes, &ctor_bytes, contract_name, false);
 
-            info!("Compiling {} runtime", contract_name);
-            compiler.compile(&builder, &rt_opcodes, &rt_bytes, contract_name, true);
+        info!("Compiling {} runtime", contract_name);
+        compiler.compile(&builder, &rt_opcodes, &rt_bytes, contract_name, true);
 
-            compiler.compile_abi(&builder, &abi, contract_name);
+        symbols.add_contract(contract_name, &abi)?;
+        compiler.compile_abi(&builder, &abi, contract_name, &symbols);
 
-            ffi.add_contract(contract_name, abi);
+        rustgen.add_contract(contract_name, &abi, ctor_bytes.len() + rt_bytes.len(), &symbols);
+        tsgen.add_contract(contract_name, &abi, &symbols);
+        ffi.add_contract(contract_name, abi, &symbols);
+    }
+
+    if let Some(entrypoint) = &opt.entrypoint {
+        ffi.set_entrypoint(entrypoint);
+        ffi.set_storage_shards(opt.shards)?;
+    }
+
+    let write_error = |e: std::io::Error| format!("Unable to write {}: {}", outdir.display(), e);
+    ffi.generate(outdir, &module.print_to_string().to_string()).map_err(write_error)?;
+    rustgen.generate(outdir).map_err(write_error)?;
+    symbols.generate(outdir).map_err(write_error)?;
+    config.generate(outdir).map_err(write_error)?;
+    if opt.typescript {
+        tsgen.generate(outdir).map_err(write_error)?;
+    }
+    Ok(())
+}
+
+fn compile(mut opt: CompileOpt) -> Result<()> {
+    let config = Config::find(opt.input.config.as_deref())?;
+    apply_config(&mut opt.input, &config)?;
+    let contracts = load_contracts(&opt.input)?;
+    let outdir = opt.output_dir.clone()
+        .or_else(|| config.output_dir.clone())
+        .unwrap_or_else(|| PathBuf::from("out"));
+    let context = Context::create();
+    let module = context.create_module("contracts");
+    build(&context, &module, &contracts, &opt.build, &build_config(&opt.build, &config), &outdir)
+}
+
+fn disasm(bytecode: &[String], as_asm: bool) -> Result<()> {
+    for arg in bytecode {
+        if bytecode.len() > 1 {
+            println!("; {}", arg);
+        }
+        let disassembly = Disassembly::from_bytes(&read_bytes(arg)?)?;
+        if as_asm {
+            print!("{}", asm::print(&disassembly));
+            continue;
+        }
+        for (offset, instr) in disassembly.instructions {
+            println!("{:04x}  {}", offset, instr);
+        }
+    }
+    Ok(())
+}
+
+fn asm(input: Option<&PathBuf>) -> Result<()> {
+    let text = match input {
+        Some(path) => std::fs::read_to_string(path).map_err(|e| format!("Unable to read {}: {}", path.display(), e))?,
+        None => {
+            let mut text = String::new();
+            std::io::stdin().read_to_string(&mut text)?;
+            text
         }
+    };
+    let instrs = asm::parse(&text)?;
+    println!("{}", evm::assemble_instructions(&instrs).to_hex::<String>());
+    Ok(())
+}
 
-        if opt.print_opcodes {
-            return;
+fn abi(command: &AbiCommand) -> Result<()> {
+    match command {
+        AbiCommand::Encode { signature, args } => {
+            let function: Function = signature.parse()?;
+            if args.len() != function.inputs.len() {
+                return Err(format!("{} takes {} arguments, got {}", function.signature(), function.inputs.len(), args.len()).into());
+            }
+            let tokens = function.inputs.iter().zip(args)
+                .map(|(param, arg)| match param.kind {
+                    ParamType::Address | ParamType::Bytes | ParamType::FixedBytes(_) => {
+                        LenientTokenizer::tokenize(&param.kind, arg.trim_start_matches("0x"))
+                    }
+                    _ => LenientTokenizer::tokenize(&param.kind, arg),
+                })
+                .collect::<std::result::Result<Vec<_>, _>>()?;
+            println!("0x{}", function.encode_input(&tokens)?.to_hex::<String>());
+        }
+        AbiCommand::Decode { signature, data, calldata } => {
+            let function: Function = signature.parse()?;
+            let data = read_bytes(data)?;
+            let json = if *calldata {
+                if data.len() < 4 || data[..4] != function.short_signature()[..] {
+                    return Err(format!("calldata does not start with the selector of {}", function.name).into());
+                }
+                Token::to_json_params(&function.inputs, &function.decode_input(&data[4..])?)
+            } else {
+                Token::to_json_params(&function.outputs, &function.decode_output(&data)?)
+            };
+            println!("{}", json);
         }
+    }
+    Ok(())
+}
+
+fn run(mut opt: RunOpt) -> Result<()> {
+    let config = Config::find(opt.input.config.as_deref())?;
+    apply_config(&mut opt.input, &config)?;
+    // only what the deployed contract needs
+    opt.input.contracts = vec![opt.contract.clone()];
+    let contracts = load_contracts(&opt.input)?;
+    // the constructor is passed its init code followed by the arguments
+    let code_size = contracts.values().next().map(|contract| contract.bin.len() / 2).unwrap_or(0);
+    let outdir = opt.output_dir.clone()
+        .unwrap_or_else(|| std::env::temp_dir().join(format!("solenoid-run-{}", std::process::id())));
+    let context = Context::create();
+    let module = context.create_module("contracts");
+    let build_config = build_config(&opt.build, &config);
+    build(&context, &module, &contracts, &opt.build, &build_config, &outdir)?;
+
+    let call = runner::Call {
+        ctor_args: read_bytes(&opt.ctor_args)?,
+        calldata: read_bytes(&opt.calldata)?,
+        caller: read_address("caller", &opt.caller)?,
+        address: read_address("address", &opt.address)?,
+    };
+    let outcome = runner::run(&module, &outdir, contract_name(&opt.contract), code_size, &call, &build_config)?;
+    for log in &outcome.logs {
+        let topics = log.topics.iter().map(|topic| format!("0x{}", topic.to_hex::<String>())).collect::<Vec<_>>();
+        println!("log [{}] 0x{}", topics.join(", "), log.data.to_hex::<String>());
+    }
+    println!("0x{}", outcome.data.to_hex::<String>());
+    if outcome.reverted {
+        error!("{} reverted", opt.contract);
+        std::process::exit(2);
+    }
+    Ok(())
+}
+
+fn main() {
+    env_logger::init();
 
-        ffi.generate(&outdir, &module.print_to_string().to_string());
-    } else {
-        error!("Nothing to do. Use --help to show instructions.");
+    let result = match Opt::from_args() {
+        Opt::Compile(opt) => compile(opt),
+        Opt::Disasm { bytecode, asm } => disasm(&bytecode, asm),
+        Opt::Asm { input } => asm(input.as_ref()),
+        Opt::Abi(command) => abi(&command),
+        Opt::Run(opt) => run(opt),
+    };
+    if let Err(e) = result {
+        error!("{}", e);
+        std::process::exit(1);
     }
 }
@@ -1,14 +1,35 @@
 use crate::compiler::Compiler;
-use crate::ethabi::{Function, Contract, param_type::ParamType::*};
+use crate::cstruct::StructGenerator;
+use crate::symbols::Symbols;
+use crate::ethabi::{Function, Contract, ParamType, StateMutability, param_type::ParamType::*};
 use std::path::PathBuf;
 use std::fs;
+use std::io;
 use std::string::String;
+use log::error;
+
+/// Most accounts the runtime spreads storage over, `MAX_SHARDS` in its `storage.rs`
+pub const MAX_SHARDS: u8 = 16;
+
+fn check_shards(shards: u8) -> Result<u8, String> {
+    if !(1..=MAX_SHARDS).contains(&shards) {
+        return Err(format!("storage is sharded over 1 to {} accounts, got {}", MAX_SHARDS, shards));
+    }
+    Ok(shards)
+}
+
+/// Shard count, between 1 and `MAX_SHARDS`
+pub fn parse_shards(s: &str) -> Result<u8, String> {
+    check_shards(s.parse().map_err(|_| format!("invalid shard count {}", s))?)
+}
 
 /// Generate C header file containing the functions in compiled contracts
 pub struct CFFIGenerator {
     fn_names: Vec<String>,
     fn_stubs: Vec<String>,
     contract_names: Vec<String>,
+    entrypoint: Option<String>,
+    storage_shards: u8,
 }
 
 impl CFFIGenerator {
@@ -20,11 +41,17 @@ impl CFFIGenerator {
             fn_stubs,
             fn_names,
             contract_names,
+            entrypoint: None,
+            storage_shards: 1,
         }
     }
 
     fn add_stub(&mut self, fn_name: &str, params: &[String]) {
-        let fn_stub = format!("extern void {}({});", fn_name, params.join(", "));
+        self.add_stub_with_ret(fn_name, "void", params);
+    }
+
+    fn add_stub_with_ret(&mut self, fn_name: &str, ret: &str, params: &[String]) {
+        let fn_stub = format!("extern {} {}({});", ret, fn_name, params.join(", "));
         self.fn_stubs.push(fn_stub.to_string());
         self.fn_names.push(fn_name.to_owned());
     }
@@ -55,18 +82,37 @@ impl CFFIGenerator {
         self.add_stub(&fn_name, &params);
     }
 
-    pub fn add_contract(&mut self, contract_name: &str, contract: Contract) {
+    pub fn add_contract(&mut self, contract_name: &str, contract: Contract, symbols: &Symbols) {
         let inputs = contract.constructor.as_ref().map(|i|i.inputs.clone()).unwrap_or(vec![]);
-        self.add_abi_function(contract_name, &Function {
+        let constructor = Function {
             name: "constructor".to_owned(),
             inputs,
             outputs: vec![],
             constant: false,
-        }, 0);
+            state_mutability: StateMutability::NonPayable,
+        };
+        self.add_abi_function(&constructor, &symbols.get(contract_name, &constructor));
         // abi formatters
+        let mut structs = StructGenerator::new(contract_name);
         for (_name, funs) in &contract.functions {
-            for (idx, fun) in funs.iter().enumerate() {
-                self.add_abi_function(contract_name, fun, idx);
+            for fun in funs {
+                let symbol = symbols.get(contract_name, fun);
+                self.add_abi_function(fun, &symbol);
+                self.add_abi_decoder(fun, &symbol);
+                structs.add_function(fun, &symbol);
+            }
+        }
+        // tuple params as structs
+        let structs = structs.generate();
+        if !structs.is_empty() {
+            self.fn_stubs.push(structs);
+        }
+        // custom error selectors, as the first word of revert data
+        for errors in contract.errors.values() {
+            for (idx, error) in errors.iter().enumerate() {
+                let selector = error.selector().iter().map(|b| format!("{:02x}", b)).collect::<String>();
+                self.fn_stubs.push(format!("/* {} */\n#define {}_{}_SELECTOR 0x{}",
+                    error.signature(), contract_name, Symbols::error_name(error, idx > 0), selector));
             }
         }
         self.add_constructor(contract_name);
@@ -77,41 +123,228 @@ impl CFFIGenerator {
         self.contract_names.push(contract_name.to_owned());
     }
 
-    pub fn add_abi_function(&mut self, contract_name: &str, fun: &Function, idx: usize) {
-        let fn_name = Compiler::format_abi_fn_name(contract_name, fun, idx);
+    /// Expose the runtime of `contract_name` as the Solana program entrypoint
+    pub fn set_entrypoint(&mut self, contract_name: &str) {
+        self.entrypoint = Some(contract_name.to_owned());
+    }
+
+    /// Spread the entrypoint contract storage over `shards` accounts, at most `MAX_SHARDS`
+    pub fn set_storage_shards(&mut self, shards: u8) -> Result<(), String> {
+        self.storage_shards = check_shards(shards)?;
+        Ok(())
+    }
+
+    /// Solana entrypoint: the instruction data is the calldata, the first account
+    /// holds the contract storage and the first signer is the caller. An optional
+    /// second account holds the address registry the signer is added to.
+    ///
+    /// With more than one shard, the third account is the system program and the
+    /// following ones are the shards, program derived from the storage account
+    /// key and the shard index. Missing shards are created, paid by the signer.
+    fn format_entrypoint(contract_name: &str, shards: u8) -> String {
+        let runtime = Compiler::format_fn_name(contract_name, true);
+        let mut contents = String::new();
+        contents += &format!("/* automatically generated by solenoid {} */\n", env!("CARGO_PKG_VERSION"));
+        contents += "\n";
+        contents += "#include \"rt.h\"\n";
+        contents += "#include \"contracts.h\"\n";
+        contents += "\n";
+        contents += "#ifdef SOLANA\n";
+        contents += "\n";
+        contents += "#define STORAGE_ACCOUNT 0\n";
+        contents += "#define REGISTRY_ACCOUNT 1\n";
+        contents += "#define SYSTEM_ACCOUNT 2\n";
+        contents += "#define SHARD_ACCOUNT 3\n";
+        contents += &format!("#define STORAGE_SHARDS {}\n", shards);
+        // storage, registry, system program, the other shards and the signer
+        contents += &format!("#define MAX_ACCOUNTS {}\n", std::cmp::max(8, shards as usize + 3));
+        contents += "\n";
+        contents += "#ifndef MAX_PERMITTED_DATA_INCREASE\n";
+        contents += "#define MAX_PERMITTED_DATA_INCREASE (1024 * 10)\n";
+        contents += "#endif\n";
+        contents += "\n";
+        if shards > 1 {
+            contents += &Self::format_bind_shard();
+        }
+        contents += "extern uint64_t entrypoint(const uint8_t *input) {\n";
+        contents += "    SolAccountInfo accounts[MAX_ACCOUNTS];\n";
+        contents += "    SolParameters params = (SolParameters) { .ka = accounts };\n";
+        contents += "\n";
+        contents += "    if (!sol_deserialize(input, &params, SOL_ARRAY_SIZE(accounts))) {\n";
+        contents += "        return ERROR_INVALID_ARGUMENT;\n";
+        contents += "    }\n";
+        contents += "    /* ka_num counts every account passed, ka only holds the first MAX_ACCOUNTS */\n";
+        contents += "    if (params.ka_num > MAX_ACCOUNTS) {\n";
+        contents += "        params.ka_num = MAX_ACCOUNTS;\n";
+        contents += "    }\n";
+        contents += "    if (params.ka_num <= STORAGE_ACCOUNT) {\n";
+        contents += "        return ERROR_NOT_ENOUGH_ACCOUNT_KEYS;\n";
+        contents += "    }\n";
+        contents += "\n";
+        contents += "    SolAccountInfo *storage_account = &params.ka[STORAGE_ACCOUNT];\n";
+        contents += "    if (!storage_account->is_writable || !SolPubkey_same(storage_account->owner, params.program_id)) {\n";
+        contents += "        return ERROR_INVALID_ACCOUNT_DATA;\n";
+        contents += "    }\n";
+        contents += "\n";
+        contents += "    SolAccountInfo *signer = NULL;\n";
+        contents += "    for (int i = 0; i < params.ka_num; i++) {\n";
+        contents += "        if (params.ka[i].is_signer) {\n";
+        contents += "            signer = &params.ka[i];\n";
+        contents += "            break;\n";
+        contents += "        }\n";
+        contents += "    }\n";
+        contents += "    if (signer == NULL) {\n";
+        contents += "        return ERROR_MISSING_REQUIRED_SIGNATURES;\n";
+        contents += "    }\n";
+        contents += "\n";
+        contents += "    i8 caller[20];\n";
+        contents += "    SolAccountInfo *registry = params.ka_num > REGISTRY_ACCOUNT ? &params.ka[REGISTRY_ACCOUNT] : NULL;\n";
+        contents += "    if (registry && registry->is_writable && SolPubkey_same(registry->owner, params.program_id)) {\n";
+        contents += "        if (!register_address(registry->data, registry->data_len, signer->key->x, caller)) {\n";
+        contents += "            return ERROR_ACCOUNT_DATA_TOO_SMALL;\n";
+        contents += "        }\n";
+        contents += "    } else {\n";
+        contents += "        pubkey_to_address(signer->key->x, caller);\n";
+        contents += "    }\n";
+        contents += &format!("    pubkey_to_address(storage_account->key->x, {}_address);\n", contract_name);
+        contents += "\n";
+        contents += "    uint64_t limit = storage_account->data_len + MAX_PERMITTED_DATA_INCREASE;\n";
+        contents += "    if (!storage_bind(storage_account->data, storage_account->data_len, limit, STORAGE_SHARDS)) {\n";
+        contents += "        return ERROR_INVALID_ACCOUNT_DATA;\n";
+        contents += "    }\n";
+        if shards > 1 {
+            contents += "    if (params.ka_num < SHARD_ACCOUNT + STORAGE_SHARDS - 1) {\n";
+            contents += "        return ERROR_NOT_ENOUGH_ACCOUNT_KEYS;\n";
+            contents += "    }\n";
+            contents += "    for (uint8_t i = 1; i < STORAGE_SHARDS; i++) {\n";
+            contents += "        uint64_t result = bind_shard(&params, signer, storage_account, i);\n";
+            contents += "        if (result != SUCCESS) {\n";
+            contents += "            return result;\n";
+            contents += "        }\n";
+            contents += "    }\n";
+        }
+        contents += "\n";
+        contents += "    long offset = 0, length = 0;\n";
+        contents += &format!("    {}((i8*)params.data, params.data_len, &offset, &length, storage_account->data, caller);\n", runtime);
+        contents += &format!("    sol_set_return_data({}_mem + offset, length);\n", contract_name);
+        contents += "    return SUCCESS;\n";
+        contents += "}\n";
+        contents += "\n";
+        contents += "#endif\n";
+        contents
+    }
+
+    /// Check a shard is the expected program derived account, create it if it
+    /// is empty, and register it with the runtime.
+    fn format_bind_shard() -> String {
+        let mut contents = String::new();
+        contents += "/* initial shard size, and its rent exempt minimum under the default rent */\n";
+        contents += "#define SHARD_SIZE (64 * 16)\n";
+        contents += "#define SHARD_LAMPORTS ((128 + SHARD_SIZE) * 3480 * 2)\n";
+        contents += "\n";
+        contents += "static uint64_t bind_shard(SolParameters *params, SolAccountInfo *payer, SolAccountInfo *storage_account, uint8_t index) {\n";
+        contents += "    SolAccountInfo *shard = &params->ka[SHARD_ACCOUNT + index - 1];\n";
+        contents += "    SolPubkey expected;\n";
+        contents += "    uint8_t bump;\n";
+        contents += "    const SolSignerSeed seeds[] = {{storage_account->key->x, SIZE_PUBKEY}, {&index, 1}, {&bump, 1}};\n";
+        contents += "    if (sol_try_find_program_address(seeds, 2, params->program_id, &expected, &bump) != SUCCESS\n";
+        contents += "        || !SolPubkey_same(&expected, shard->key) || !shard->is_writable) {\n";
+        contents += "        return ERROR_INVALID_SEEDS;\n";
+        contents += "    }\n";
+        contents += "\n";
+        contents += "    if (shard->data_len == 0) {\n";
+        contents += "        /* SystemInstruction::CreateAccount { lamports, space, owner } */\n";
+        contents += "        uint8_t data[4 + 8 + 8 + SIZE_PUBKEY] = {0};\n";
+        contents += "        *(uint64_t *)(data + 4) = SHARD_LAMPORTS;\n";
+        contents += "        *(uint64_t *)(data + 12) = SHARD_SIZE;\n";
+        contents += "        sol_memcpy(data + 20, params->program_id, SIZE_PUBKEY);\n";
+        contents += "        SolAccountMeta metas[] = {{payer->key, true, true}, {shard->key, true, true}};\n";
+        contents += "        const SolInstruction instruction = {params->ka[SYSTEM_ACCOUNT].key, metas, SOL_ARRAY_SIZE(metas), data, SOL_ARRAY_SIZE(data)};\n";
+        contents += "        const SolSignerSeeds signers[] = {{seeds, SOL_ARRAY_SIZE(seeds)}};\n";
+        contents += "        uint64_t result = sol_invoke_signed(&instruction, params->ka, params->ka_num, signers, SOL_ARRAY_SIZE(signers));\n";
+        contents += "        if (result != SUCCESS) {\n";
+        contents += "            return result;\n";
+        contents += "        }\n";
+        contents += "        shard->data_len = SHARD_SIZE;\n";
+        contents += "    }\n";
+        contents += "\n";
+        contents += "    if (!storage_add_shard(shard->data, shard->data_len, shard->data_len + MAX_PERMITTED_DATA_INCREASE)) {\n";
+        contents += "        return ERROR_INVALID_ACCOUNT_DATA;\n";
+        contents += "    }\n";
+        contents += "    return SUCCESS;\n";
+        contents += "}\n";
+        contents += "\n";
+        contents
+    }
+
+    pub fn add_abi_function(&mut self, fun: &Function, symbol: &str) {
         let mut params = Vec::new();
         params.push("i8* tx".to_owned());
         params.push("int* tx_size".to_owned());
         for param in &fun.inputs {
-            let ty = match param.kind {
-                Address => "i8*",
-                Bytes => "i8*",
-                Int(8) | Uint(8) => "byte",
-                Int(16) | Uint(16) => "short",
-                Int(32) | Uint(32) => "int",
-                Int(64) | Uint(64) => "long",
-                Int(_) | Uint(_) => "i8*",
-                Bool => "int",
-                String => "i8*",
-                Array(_) => "i8*",
-                FixedBytes(_) => "i8*",
-                FixedArray(_, _) => "i8*",
-                Tuple(_) => "i8*",
-            };
+            // dynamic params are passed as pointer and length
+            if param.kind.is_dynamic() {
+                params.push(format!("i8* {}", param.name));
+                params.push(format!("long {}_len", param.name));
+                continue;
+            }
+
+            let ty = Self::c_type(&param.kind);
             params.push(format!("{} {}", ty, param.name));
         }
 
-        self.add_stub(&fn_name, &params);
+        self.add_stub(symbol, &params);
     }
 
-    pub fn generate(&self, outdir: &PathBuf, ir: &str) {
-        let outdir = outdir.as_os_str().to_str().unwrap();
+    /// int abi_{C}_{fn}_decode(i8* ret, long ret_len, outs..)
+    pub fn add_abi_decoder(&mut self, fun: &Function, symbol: &str) {
+        let fn_name = format!("{}_decode", symbol);
+        let mut params = Vec::new();
+        params.push("i8* ret".to_owned());
+        params.push("long ret_len".to_owned());
+        for (i, param) in fun.outputs.iter().enumerate() {
+            let name = if param.name.is_empty() { format!("out{}", i) } else { param.name.clone() };
+            // dynamic outputs point into ret
+            if param.kind.is_dynamic() {
+                params.push(format!("i8** {}", name));
+                params.push(format!("long* {}_len", name));
+                continue;
+            }
+            let ty = match Self::c_type(&param.kind) {
+                "i8*" => "i8*".to_owned(),
+                ty => format!("{}*", ty),
+            };
+            params.push(format!("{} {}", ty, name));
+        }
+
+        self.add_stub_with_ret(&fn_name, "int", &params);
+    }
+
+    /// C type of a static param
+    pub(crate) fn c_type(kind: &ParamType) -> &'static str {
+        match kind {
+            Address => "i8*",
+            Bytes => "i8*",
+            Int(8) | Uint(8) => "i8",
+            Int(16) | Uint(16) => "short",
+            Int(32) | Uint(32) => "int",
+            Int(64) | Uint(64) => "long",
+            Int(_) | Uint(_) => "i8*",
+            Bool => "int",
+            String => "i8*",
+            Array(_) => "i8*",
+            FixedBytes(_) => "i8*",
+            FixedArray(_, _) => "i8*",
+            Tuple(_) => "i8*",
+        }
+    }
+
+    pub fn generate(&self, outdir: &PathBuf, ir: &str) -> io::Result<()> {
         // create folders
-        std::fs::create_dir_all(outdir).expect("unable to create output directory");
-        std::fs::create_dir_all(format!("{}/src", outdir)).expect("unable to create output/src directory");
+        fs::create_dir_all(outdir.join("src"))?;
 
         // write ir
-        fs::write(format!("{}/src/contracts.ll", outdir), ir).expect("cannot write ir");
+        fs::write(outdir.join("src/contracts.ll"), ir)?;
 
         // format header
         let mut contents = String::new();
@@ -123,36 +356,49 @@ impl CFFIGenerator {
             contents += &format!("extern long {}_pc;\n", contract_name);
             contents += &format!("extern i8 {}_stack[];\n", contract_name);
             contents += &format!("extern i8 {}_mem[];\n", contract_name);
+            contents += &format!("extern i8 {}_address[];\n", contract_name);
             contents += "\n";
         }
         contents += "\n";
         contents += &self.fn_stubs.join("\n\n");
         contents += "\n";
 
-        let contract_header = format!("{}/src/contracts.h", outdir);
-        let runtime_header = format!("{}/src/rt.h", outdir);
-        let bindings_filename = format!("{}/src/bindings.rs", outdir);
+        let contract_header = outdir.join("src/contracts.h");
+        let runtime_header = outdir.join("src/rt.h");
+        let bindings_filename = outdir.join("src/bindings.rs");
 
         // write c header
-        fs::write(&contract_header, contents).expect("unable to write contracts.h header");
-        Self::copy_deps(&outdir);
+        fs::write(&contract_header, contents)?;
+        Self::copy_deps(outdir)?;
+
+        // write solana entrypoint
+        if let Some(contract_name) = &self.entrypoint {
+            if self.contract_names.contains(contract_name) {
+                fs::write(outdir.join("src/entrypoint.c"), Self::format_entrypoint(contract_name, self.storage_shards))?;
+            } else {
+                error!("Entrypoint contract {} was not compiled", contract_name);
+            }
+        }
 
         // write rust bindings
         let mut builder = bindgen::builder()
-            .header(&contract_header)
-            .header(&runtime_header);
+            .header(contract_header.to_string_lossy())
+            .header(runtime_header.to_string_lossy());
         for f in &self.fn_names {
             builder = builder.whitelist_function(f);
         }
 
-        for f in &["prt"] {
+        for f in &["prt", "log_count", "log_get", "log_clear"] {
             builder = builder.whitelist_function(f);
         }
 
         let vars = [
             "\\w+_stack",
+            "\\w+_mem",
+            "\\w+_address",
             "\\w+storage",
             "\\w+sp",
+            "reverted",
         ];
         for var in &vars {
             builder = builder.whitelist_var(var);
@@ -160,30 +406,72 @@ impl CFFIGenerator {
 
         let bindings = builder
             .generate()
-            .expect("unable to generate bindings");
-        bindings.write_to_file(bindings_filename).expect("unable to write bindings");
+            .map_err(|()| io::Error::new(io::ErrorKind::Other, "unable to generate bindings"))?;
+        bindings.write_to_file(bindings_filename)
     }
 
-    pub fn copy_deps(outdir: &str) {
+    pub fn copy_deps(outdir: &PathBuf) -> io::Result<()> {
         macro_rules! include {
             (root: $($x:expr,)*) => {
                 $(
-                    fs::write(&format!("{}/{}", outdir, $x), include_str!(concat!("../runtime/", $x))).unwrap();
+                    fs::write(outdir.join($x), include_str!(concat!("../runtime/", $x)))?;
                 )*
             };
             (src: $($x:expr,)*) => {
                 $(
-                    fs::write(&format!("{}/src/{}", outdir, $x), include_str!(concat!("../runtime/", $x))).unwrap();
+                    fs::write(outdir.join("src").join($x), include_str!(concat!("../runtime/", $x)))?;
                 )*
             };
         }
+        fs::create_dir_all(outdir.join("solenoid-rt/src"))?;
         include!(root:
             "Makefile",
+            "solenoid-rt/Cargo.toml",
+            "solenoid-rt/src/lib.rs",
+            "solenoid-rt/src/abi.rs",
+            "solenoid-rt/src/address.rs",
+            "solenoid-rt/src/arith.rs",
+            "solenoid-rt/src/debug.rs",
+            "solenoid-rt/src/keccak.rs",
+            "solenoid-rt/src/log.rs",
+            "solenoid-rt/src/storage.rs",
         );
         include!(src:
-            "rt.c",
             "rt.h",
-            "arith.ll",
         );
+        Ok(())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entrypoint() {
+        let entrypoint = CFFIGenerator::format_entrypoint("ERC20", 1);
+        let clamp = entrypoint.find("params.ka_num = MAX_ACCOUNTS;").unwrap();
+        // before the accounts are first looked at
+        assert!(clamp < entrypoint.find("params.ka[STORAGE_ACCOUNT]").unwrap());
+        assert!(entrypoint.contains("    ERC20_runtime((i8*)params.data, params.data_len, &offset, &length, storage_account->data, caller);\n"));
+        assert!(!entrypoint.contains("bind_shard"));
+        assert!(CFFIGenerator::format_entrypoint("ERC20", 4).contains("bind_shard(&params, signer, storage_account, i)"));
+
+        assert!(entrypoint.contains("#define MAX_ACCOUNTS 8\n"));
+        // the signer follows the 9 other shards
+        assert!(CFFIGenerator::format_entrypoint("ERC20", 10).contains("#define MAX_ACCOUNTS 13\n"));
+        assert!(CFFIGenerator::format_entrypoint("ERC20", 16).contains("#define MAX_ACCOUNTS 19\n"));
+    }
+
+    #[test]
+    fn test_storage_shards() {
+        let mut ffi = CFFIGenerator::new();
+        assert!(ffi.set_storage_shards(16).is_ok());
+        assert_eq!(ffi.set_storage_shards(17).unwrap_err(), "storage is sharded over 1 to 16 accounts, got 17");
+        assert!(ffi.set_storage_shards(0).is_err());
+        assert_eq!(ffi.storage_shards, 16);
+        assert_eq!(parse_shards("4"), Ok(4));
+        assert!(parse_shards("255").is_err());
+        assert!(parse_shards("x").is_err());
+    }
+}
@@ -14,22 +14,21 @@ use inkwell::builder::Builder;
 use inkwell::context::Context;
 use inkwell::module::Module;
 
-use crate::ethabi::{Function, Constructor, Contract, param_type::ParamType::*, ParamType};
+use crate::ethabi::{Function, Constructor, Contract, param_type::ParamType::*, ParamType, StateMutability};
+use crate::symbols::Symbols;
 
 use log::{info, warn, error, debug};
 
+/// Size of the head of an encoded param: the value itself when static, the
+/// offset of its tail when dynamic
 fn param_type_size(kind: &ParamType) -> u64 {
+    if kind.is_dynamic() {
+        return 32;
+    }
     match kind {
-        Bool | Int(_) | Uint(_) | Address => 32,
-        crate::ethabi::ParamType::String | Array(_) | Bytes => {
-            error!("solenoid does not support dynamic sized input yet");
-            0
-        }
         FixedArray(ty, n) => (*n as u64) * param_type_size(ty),
-        Tuple(_) | FixedBytes(_) => {
-            error!("unimpl {:?}", kind);
-            0
-        }
+        Tuple(tys) => tys.iter().map(|ty| param_type_size(ty)).sum(),
+        _ => 32,
     }
 }
 
@@ -63,6 +62,8 @@ pub struct Compiler<'a, 'ctx> {
     code: Option<GlobalValue<'ctx>>,
     code_ptr: Option<GlobalValue<'ctx>>,
     code_size: u64,
+    address: Option<GlobalValue<'ctx>>,
+    topics: Option<GlobalValue<'ctx>>,
     fun: Option<FunctionValue<'ctx>>,
     jumpbb: Option<BasicBlock<'ctx>>,
     errbb: Option<BasicBlock<'ctx>>,
@@ -72,29 +73,38 @@ pub struct Compiler<'a, 'ctx> {
 }
 
 impl<'a, 'ctx> Compiler<'a, 'ctx> {
-    pub fn compile_abi(&self, builder: &'a Builder<'ctx>, contract: &Contract, contract_name: &str) {
+    pub fn compile_abi(&self, builder: &'a Builder<'ctx>, contract: &Contract, contract_name: &str, symbols: &Symbols) {
         let inputs = contract.constructor.as_ref().map(|i|i.inputs.to_owned()).unwrap_or(vec![]);
         let fun = Function {
             name: "constructor".to_owned(),
             inputs,
             outputs: vec![],
             constant: false,
+            state_mutability: StateMutability::NonPayable,
         };
-        self.compile_abi_function(builder, contract_name, &fun, 0, true);
+        self.compile_abi_function(builder, contract_name, &fun, &symbols.get(contract_name, &fun), true);
         for (_name, funs) in &contract.functions {
-            for (idx, fun) in funs.iter().enumerate() {
-                self.compile_abi_function(builder, contract_name, fun, idx, false);
+            for fun in funs {
+                let symbol = symbols.get(contract_name, fun);
+                self.compile_abi_function(builder, contract_name, fun, &symbol, false);
+                self.compile_abi_decoder(builder, fun, &symbol);
             }
         }
     }
     
     /// void get(char* out_buf, int* buf_length, params..)
-    fn compile_abi_function(&self, builder: &'a Builder<'ctx>, contract_name: &str, fun: &Function, idx: usize, is_ctor: bool) {
+    fn compile_abi_function(&self, builder: &'a Builder<'ctx>, contract_name: &str, fun: &Function, symbol: &str, is_ctor: bool) {
         let char_ptr_ty = self.context.i8_type().ptr_type(AddressSpace::Generic).into();
         let buf_len_ty = self.context.i32_type().ptr_type(AddressSpace::Generic).into();
         let mut param_types: Vec<BasicTypeEnum<'ctx>> = vec![char_ptr_ty, buf_len_ty];
 
         for param in &fun.inputs {
+            // dynamic params are passed as pointer and length
+            if param.kind.is_dynamic() {
+                param_types.push(char_ptr_ty);
+                param_types.push(self.context.i64_type().into());
+                continue;
+            }
 
             let ty = match param.kind {
                 Address => char_ptr_ty,
@@ -122,9 +132,8 @@ impl<'a, 'ctx> Compiler<'a, 'ctx> {
             param_types.push(ty);
         }
 
-        let fun_name = Self::format_abi_fn_name(contract_name, fun, idx);
         let fn_ty = self.context.void_type().fn_type(param_types.as_slice(),false);
-        let llvm_fun = self.module.add_function(&fun_name, fn_ty, None);
+        let llvm_fun = self.module.add_function(symbol, fn_ty, None);
         let basic_block = self.context.append_basic_block(llvm_fun, "entry");
 
         builder.position_at_end(basic_block);
@@ -132,6 +141,101 @@ impl<'a, 'ctx> Compiler<'a, 'ctx> {
         builder.build_return(None);
     }
 
+    /// int get_decode(char* ret, long ret_len, outs..)
+    ///
+    /// `ret` is the returned contract memory, whose words are converted in place
+    /// to ABI byte order. Static outputs are written to typed out-params, dynamic
+    /// ones as a pointer into `ret` and a length. Returns 0 if the return data is
+    /// malformed.
+    fn compile_abi_decoder(&self, builder: &'a Builder<'ctx>, fun: &Function, symbol: &str) {
+        let char_ptr_ty = self.context.i8_type().ptr_type(AddressSpace::Generic);
+        let i64_ty = self.context.i64_type();
+        let mut param_types: Vec<BasicTypeEnum<'ctx>> = vec![char_ptr_ty.into(), i64_ty.into()];
+        for param in &fun.outputs {
+            if param.kind.is_dynamic() {
+                param_types.push(char_ptr_ty.ptr_type(AddressSpace::Generic).into());
+                param_types.push(i64_ty.ptr_type(AddressSpace::Generic).into());
+                continue;
+            }
+            let ty = match param.kind {
+                Int(8) | Uint(8) => self.context.i8_type().ptr_type(AddressSpace::Generic),
+                Int(16) | Uint(16) => self.context.i16_type().ptr_type(AddressSpace::Generic),
+                Int(32) | Uint(32) | Bool => self.context.i32_type().ptr_type(AddressSpace::Generic),
+                Int(64) | Uint(64) => i64_ty.ptr_type(AddressSpace::Generic),
+                _ => char_ptr_ty,
+            };
+            param_types.push(ty.into());
+        }
+
+        let fun_name = format!("{}_decode", symbol);
+        let fn_ty = self.context.i32_type().fn_type(param_types.as_slice(), false);
+        let llvm_fun = self.module.add_function(&fun_name, fn_ty, None);
+        let entry = self.context.append_basic_block(llvm_fun, "entry");
+        let decode = self.context.append_basic_block(llvm_fun, "decode");
+        let short = self.context.append_basic_block(llvm_fun, "short");
+
+        let ret = llvm_fun.get_nth_param(0).unwrap().into_pointer_value();
+        let ret_len = llvm_fun.get_nth_param(1).unwrap().into_int_value();
+        let head_size: u64 = fun.outputs.iter().map(|param| param_type_size(&param.kind)).sum();
+
+        builder.position_at_end(short);
+        builder.build_return(Some(&self.i32(0)));
+
+        builder.position_at_end(entry);
+        let is_short = builder.build_int_compare(IntPredicate::ULT, ret_len, self.i64(head_size), "is_short");
+        builder.build_conditional_branch(is_short, short, decode);
+
+        builder.position_at_end(decode);
+        builder.build_call(self.swap_words(), &[ret.into(), ret_len.into()], "swap");
+        let mut ok = self.i32(1);
+        let mut pos = 0;
+        let mut arg = 2;
+        for param in &fun.outputs {
+            let out = llvm_fun.get_nth_param(arg).unwrap().into_pointer_value();
+            arg += 1;
+            let head = unsafe { builder.build_gep(ret, &[self.i64(pos)], "head") };
+            if param.kind.is_dynamic() {
+                let out_len = llvm_fun.get_nth_param(arg).unwrap();
+                arg += 1;
+                let ty = builder.build_global_string_ptr(&param.kind.to_string(), "abi_ty").as_pointer_value();
+                let res = builder.build_call(self.abi_decode_tail(), &[ret.into(), ret_len.into(), head.into(), ty.into(), out.into(), out_len], "res")
+                    .try_as_basic_value().left().unwrap().into_int_value();
+                ok = builder.build_and(ok, res, "ok");
+                pos += 32;
+                continue;
+            }
+
+            let size = param_type_size(&param.kind);
+            match &param.kind {
+                Uint(8) | Uint(16) | Uint(32) | Uint(64) | Bool |
+                Int(8) | Int(16) | Int(32) | Int(64) => {
+                    // big endian word to native integer
+                    let word = builder.build_alloca(self.i256_ty, "word");
+                    let word_ptr = builder.build_pointer_cast(word, char_ptr_ty, "word_ptr");
+                    builder.build_memcpy(word_ptr, 1, head, 1, self.i32(32)).unwrap();
+                    builder.build_call(self.swap_endianness(), &[word_ptr.into()], "swap");
+                    let value = builder.build_load(word, "value").into_int_value();
+                    let out_ty = out.get_type().get_element_type().into_int_type();
+                    let value = builder.build_int_truncate(value, out_ty, &param.name);
+                    builder.build_store(out, value);
+                }
+                Address => {
+                    let addr = unsafe { builder.build_gep(head, &[self.i32(12)], "addr") };
+                    builder.build_memcpy(out, 1, addr, 1, self.i32(20)).unwrap();
+                }
+                FixedBytes(n) => {
+                    builder.build_memcpy(out, 1, head, 1, self.i32(*n as u64)).unwrap();
+                }
+                _ => {
+                    // wide integers stay big endian, static aggregates encoded
+                    builder.build_memcpy(out, 1, head, 1, self.i32(size)).unwrap();
+                }
+            }
+            pos += size;
+        }
+        builder.build_return(Some(&ok));
+    }
+
     fn build_abi_conversion(&self, builder: &'a Builder<'ctx>, contract_name: &str, fun: &Function, llvm_fun: FunctionValue<'ctx>, is_ctor: bool) {
         let buf = llvm_fun.get_nth_param(0).unwrap().into_pointer_value();
         let len_ptr = llvm_fun.get_nth_param(1).unwrap().into_pointer_value();
@@ -149,23 +253,46 @@ impl<'a, 'ctx> Compiler<'a, 'ctx> {
             let sig_glb = self.module.add_global(
                 self.context.i8_type().array_type(sig.len() as u32),
                 Some(AddressSpace::Generic),
-                &format!("{}_sig", fun.name));
+                &format!("{}_sig", llvm_fun.get_name().to_str().unwrap()));
             let sig_buf = self.context.const_string(&sig, false);
             sig_glb.set_initializer(&sig_buf);
             builder.build_memcpy(buf, 1, sig_glb.as_pointer_value(), 1, self.i32(sig.len() as u64)).unwrap();
             len = builder.build_int_add(len, self.i32(4), "len");
         }
 
-        for (idx, param) in fun.inputs.iter().enumerate() {
-            let x = llvm_fun.get_nth_param(idx as u32 + 2).unwrap();
+        // heads are written in place, tails are appended after the last head
+        let head_size: u64 = fun.inputs.iter().map(|param| param_type_size(&param.kind)).sum();
+        let tail_start = builder.build_int_add(len, self.i32(head_size), "tail_start");
+        let mut tail = self.i32(0);
+        let mut arg = 2;
+        for param in &fun.inputs {
+            let x = llvm_fun.get_nth_param(arg).unwrap();
+            arg += 1;
+            if param.kind.is_dynamic() {
+                let x_len = llvm_fun.get_nth_param(arg).unwrap().into_int_value();
+                arg += 1;
+
+                let offset = builder.build_int_add(tail, self.i32(head_size), "offset");
+                self.build_abi_word(builder, buf, len, offset, false);
+                len = builder.build_int_add(len, self.i32(32), "len");
+
+                let pos = builder.build_int_add(tail_start, tail, "pos");
+                let tail_ptr = unsafe { builder.build_gep(buf, &[pos], "tail") };
+                let ty = builder.build_global_string_ptr(&param.kind.to_string(), "abi_ty").as_pointer_value();
+                let size = builder.build_call(self.abi_encode_tail(), &[tail_ptr.into(), ty.into(), x, x_len.into()], "size")
+                    .try_as_basic_value().left().unwrap().into_int_value();
+                let size = builder.build_int_truncate(size, self.context.i32_type(), "size");
+                tail = builder.build_int_add(tail, size, "tail");
+                continue;
+            }
+
             match &param.kind {
-                Uint(8) | Uint(16) | Uint(32) | Uint(64) |
-                Int(8) | Int(16) | Int(32) | Int(64) | Bool => {
-                    let x = x.into_int_value();
-                    let value = builder.build_int_z_extend(x, self.i256_ty, &param.name);
-                    let ptr = unsafe { builder.build_gep(buf, &[len], "ptr") };
-                    let ptr = builder.build_pointer_cast(ptr, self.i256_ty.ptr_type(AddressSpace::Generic), "ptr");
-                    builder.build_store(ptr, value);
+                Uint(8) | Uint(16) | Uint(32) | Uint(64) | Bool => {
+                    self.build_abi_word(builder, buf, len, x.into_int_value(), false);
+                    len = builder.build_int_add(len, self.i32(32), "len");
+                },
+                Int(8) | Int(16) | Int(32) | Int(64) => {
+                    self.build_abi_word(builder, buf, len, x.into_int_value(), true);
                     len = builder.build_int_add(len, self.i32(32), "len");
                 },
                 Uint(bits) | Int(bits) => {
@@ -185,22 +312,55 @@ impl<'a, 'ctx> Compiler<'a, 'ctx> {
                     builder.build_memcpy(ptr, 1, x, 1, self.i32(20));
                     len = builder.build_int_add(len, self.i32(20), "len");
                 }
+                FixedBytes(n) => {
+                    // left aligned, zero padded
+                    let x = x.into_pointer_value();
+                    let ptr = unsafe { builder.build_gep(buf, &[len], "ptr") };
+                    let word = builder.build_pointer_cast(ptr, self.i256_ty.ptr_type(AddressSpace::Generic), "word");
+                    builder.build_store(word, self.i256(0));
+                    builder.build_memcpy(ptr, 1, x, 1, self.i32(*n as u64)).unwrap();
+                    len = builder.build_int_add(len, self.i32(32), "len");
+                }
+                FixedArray(_, _) | Tuple(_) => {
+                    // static aggregates are passed already encoded
+                    let x = x.into_pointer_value();
+                    let size = param_type_size(&param.kind);
+                    let ptr = unsafe { builder.build_gep(buf, &[len], "ptr") };
+                    builder.build_memcpy(ptr, 1, x, 1, self.i32(size)).unwrap();
+                    len = builder.build_int_add(len, self.i32(size), "len");
+                }
                 _ => {
                     error!("unimpl {:?}", &param.kind);
                 }
             }
         }
+        len = builder.build_int_add(len, tail, "len");
+
+        if is_ctor {
+            // constructor args are read with CODECOPY and MLOAD, which see the
+            // code as contract memory, so they are stored in native word order
+            let args = unsafe { builder.build_gep(buf, &[self.i32(self.code_size)], "args") };
+            let args_len = builder.build_int_sub(len, self.i32(self.code_size), "args_len");
+            let args_len = builder.build_int_z_extend(args_len, self.context.i64_type(), "args_len");
+            builder.build_call(self.swap_words(), &[args.into(), args_len.into()], "swap");
+        }
 
         builder.build_store(len_ptr, len);
     }
 
-    pub fn format_abi_fn_name(contract_name: &str, fun: &Function, idx: usize) -> String {
-        if idx == 0 {
-            format!("abi_{}_{}", contract_name, fun.name)
+    /// Store `x` as a big endian word at `buf + pos`
+    fn build_abi_word(&self, builder: &'a Builder<'ctx>, buf: PointerValue<'ctx>, pos: IntValue<'ctx>, x: IntValue<'ctx>, signed: bool) {
+        let value = if signed {
+            builder.build_int_s_extend(x, self.i256_ty, "value")
         } else {
-            format!("abi_{}_{}_{}", contract_name, fun.name, idx)
-        }
+            builder.build_int_z_extend(x, self.i256_ty, "value")
+        };
+        let ptr = unsafe { builder.build_gep(buf, &[pos], "ptr") };
+        let word = builder.build_pointer_cast(ptr, self.i256_ty.ptr_type(AddressSpace::Generic), "word");
+        builder.build_store(word, value);
+        builder.build_call(self.swap_endianness(), &[ptr.into()], "swap");
     }
+
 }
 
 impl<'a, 'ctx> Compiler<'a, 'ctx> {
@@ -221,6 +381,8 @@ impl<'a, 'ctx> Compiler<'a, 'ctx> {
             code: None,
             code_ptr: None,
             code_size: 0,
+            address: None,
+            topics: None,
             fun: None,
             jumpdests: BTreeMap::new(),
             jumpbb: None,
@@ -364,7 +526,7 @@ impl<'a, 'ctx> Compiler<'a, 'ctx> {
         let fn_ty = self.context.void_type().fn_type(
             &[
                     char_ptr_ty.into(),
-                    self.context.i16_type().into(),
+                    self.context.i64_type().into(),
                     char_ptr_ty.into(),
                 ],
                 false);
@@ -373,6 +535,39 @@ impl<'a, 'ctx> Compiler<'a, 'ctx> {
         sha3
     }
 
+    fn abi_encode_tail(&self) -> FunctionValue<'ctx> {
+        let name = "abi_encode_tail";
+        if let Some(f) = self.module.get_function(&name) {
+            return f;
+        }
+
+        let char_ptr_ty = self.context.i8_type().ptr_type(AddressSpace::Generic).into();
+        let int_ty = self.context.i64_type();
+        let fn_ty = int_ty.fn_type(&[char_ptr_ty, char_ptr_ty, char_ptr_ty, int_ty.into()], false);
+        let abi_encode_tail = self.module.add_function(name, fn_ty, Some(inkwell::module::Linkage::External));
+        abi_encode_tail
+    }
+
+    fn abi_decode_tail(&self) -> FunctionValue<'ctx> {
+        let name = "abi_decode_tail";
+        if let Some(f) = self.module.get_function(&name) {
+            return f;
+        }
+
+        let char_ptr_ty = self.context.i8_type().ptr_type(AddressSpace::Generic);
+        let int_ty = self.context.i64_type();
+        let fn_ty = self.context.i32_type().fn_type(&[
+                char_ptr_ty.into(),
+                int_ty.into(),
+                char_ptr_ty.into(),
+                char_ptr_ty.into(),
+                char_ptr_ty.ptr_type(AddressSpace::Generic).into(),
+                int_ty.ptr_type(AddressSpace::Generic).into(),
+            ], false);
+        let abi_decode_tail = self.module.add_function(name, fn_ty, Some(inkwell::module::Linkage::External));
+        abi_decode_tail
+    }
+
     fn revert(&self) -> FunctionValue<'ctx> {
         let name = "revert";
         if let Some(f) = self.module.get_function(&name) {
@@ -421,6 +616,33 @@ impl<'a, 'ctx> Compiler<'a, 'ctx> {
         swap_endianness
     }
 
+    fn emit_log(&self) -> FunctionValue<'ctx> {
+        let name = "emit_log";
+        if let Some(f) = self.module.get_function(&name) {
+            return f;
+        }
+
+        let char_ptr_ty = self.context.i8_type().ptr_type(AddressSpace::Generic);
+        let i64_ty = self.context.i64_type();
+        let fn_ty = self.context.void_type().fn_type(
+            &[char_ptr_ty.into(), i64_ty.into(), char_ptr_ty.into(), i64_ty.into()],
+            false);
+        let emit_log = self.module.add_function(name, fn_ty, Some(inkwell::module::Linkage::External));
+        emit_log
+    }
+
+    fn swap_words(&self) -> FunctionValue<'ctx> {
+        let name = "swap_words";
+        if let Some(f) = self.module.get_function(&name) {
+            return f;
+        }
+
+        let char_ptr_ty = self.context.i8_type().ptr_type(AddressSpace::Generic).into();
+        let fn_ty = self.context.void_type().fn_type(&[char_ptr_ty, self.context.i64_type().into()],false);
+        let swap_words = self.module.add_function(name, fn_ty, Some(inkwell::module::Linkage::External));
+        swap_words
+    }
+
     fn storage_ptr(&self) -> PointerValue<'ctx> {
         self.fun.unwrap().get_nth_param(4).unwrap().into_pointer_value()
     }
@@ -493,6 +715,18 @@ impl<'a, 'ctx> Compiler<'a, 'ctx> {
             &format!("{}_code_ptr", contract_name));
         code_ptr.set_initializer(&self.context.i8_type().ptr_type(AddressSpace::Generic).const_null());
         self.code_ptr = Some(code_ptr);
+
+        // address, set by the caller before running the contract
+        let address_ty = self.context.i8_type().array_type(20);
+        let address = self.module.add_global(address_ty, Some(AddressSpace::Generic), &format!("{}_address", contract_name));
+        address.set_initializer(&address_ty.const_zero());
+        self.address = Some(address);
+
+        // LOG topics, a global so that logs in loops do not grow the stack frame
+        let topics_ty = self.i256_ty.array_type(4);
+        let topics = self.module.add_global(topics_ty, Some(AddressSpace::Generic), &format!("{}_topics", contract_name));
+        topics.set_initializer(&topics_ty.const_zero());
+        self.topics = Some(topics);
     }
 
     pub fn build_function(&mut self, name: &str, is_runtime: bool) {
@@ -631,6 +865,16 @@ impl<'a, 'ctx> Compiler<'a, 'ctx> {
         sp
     }
 
+    /// Push the 20 byte big endian address at `x` as a word
+    fn build_push_address(&self, builder: &'a Builder<'ctx>, x: PointerValue<'ctx>) {
+        let sp = self.build_sp(builder);
+        self.build_push(builder, self.i256(0).into(), sp);
+        let tos = self.build_tos_ptr(builder, 1);
+        let ptr = unsafe { builder.build_gep(tos, &[self.i32(12)], "ptr") };
+        builder.build_memcpy(ptr, 1, x, 1, self.i32(20));
+        builder.build_call(self.swap_endianness(), &[tos.into()], "pos");
+    }
+
     /// return char pointer to tos
     fn build_tos_ptr(&self, builder: &'a Builder<'ctx>, idx: u64) -> PointerValue<'ctx> {
         let sp = self.build_sp(builder);
@@ -659,7 +903,6 @@ impl<'a, 'ctx> Compiler<'a, 'ctx> {
 
         builder.build_store(self.pc.unwrap().as_pointer_value(), self.i64(offset as u64));
         match instr {
-            Instruction::Addr |
             Instruction::Balance |
             Instruction::CallDataCopy |
             Instruction::GasPrice |
@@ -684,19 +927,23 @@ impl<'a, 'ctx> Compiler<'a, 'ctx> {
             Instruction::DelegateCall |
             Instruction::Create2 |
             Instruction::StaticCall => {
+                // message calls are out of scope of the address mapping and not
+                // compiled; they would resolve their target with the runtime's
+                // address_to_pubkey and invoke it
                 error!("unimpl: {:?}", instr);
             }
             Instruction::Origin |
             Instruction::Caller => {
                 let name = "caller";
                 self.push_label(name, builder);
-                let sp = self.build_sp(builder);
-                let sp = self.build_push(builder, self.i256(0).into(), sp);
-                let tos = self.build_tos_ptr(builder, 1);
                 let x = self.fun.unwrap().get_nth_param(5).unwrap().into_pointer_value();
-                let ptr = unsafe { builder.build_gep(tos, &[self.i32(12)], "ptr") };
-                builder.build_memcpy(ptr, 1, x, 1, self.i32(20));
-                builder.build_call(self.swap_endianness(), &[tos.into()], "pos");
+                self.build_push_address(builder, x);
+            }
+            Instruction::Addr => {
+                let name = "address";
+                self.push_label(name, builder);
+                let address = unsafe { builder.build_gep(self.address.unwrap().as_pointer_value(), &[self.i32(0), self.i32(0)], "address") };
+                self.build_push_address(builder, address);
             }
             Instruction::CodeSize => {
                 let name = "codesize";
@@ -765,7 +1012,7 @@ impl<'a, 'ctx> Compiler<'a, 'ctx> {
                 let offset = self.build_peek(builder, sp, 1, "offset");
                 let sp = self.build_decr(builder, sp, 2);
 
-                let length = builder.build_int_truncate_or_bit_cast(length, self.context.i16_type(), "length");
+                let length = builder.build_int_truncate_or_bit_cast(length, self.context.i64_type(), "length");
 
                 let mem = self.mem.unwrap().as_pointer_value();
                 let addr = unsafe { builder.build_in_bounds_gep(mem, &[self.context.i64_type().const_zero(), offset], "mem") };
@@ -802,8 +1049,29 @@ impl<'a, 'ctx> Compiler<'a, 'ctx> {
             Instruction::Log(n) => {
                 let name = "log";
                 self.push_label(name, builder);
-                error!("Event emission is unimpl: {:?}", instr);
                 let sp = self.build_sp(builder);
+                let offset = self.build_peek(builder, sp, 1, "offset");
+                let length = self.build_peek(builder, sp, 2, "length");
+                let length = builder.build_int_truncate_or_bit_cast(length, self.context.i64_type(), "length");
+
+                let mem = self.mem.unwrap().as_pointer_value();
+                let data = unsafe { builder.build_in_bounds_gep(mem, &[self.context.i64_type().const_zero(), offset], "mem") };
+                let data = builder.build_pointer_cast(data, self.context.i8_type().ptr_type(AddressSpace::Generic), "data");
+
+                // topics are handed over as big endian words
+                let count = self.context.i64_type().const_int(*n as u64, false);
+                let topics = self.topics.unwrap().as_pointer_value();
+                for i in 0..*n as u64 {
+                    let topic = self.build_peek(builder, sp, i + 3, "topic");
+                    let index = [self.context.i64_type().const_zero(), self.context.i64_type().const_int(i, false)];
+                    let ptr = unsafe { builder.build_in_bounds_gep(topics, &index, "topic_ptr") };
+                    builder.build_store(ptr, topic);
+                    let ptr = builder.build_pointer_cast(ptr, self.context.i8_type().ptr_type(AddressSpace::Generic), "topic_i8");
+                    builder.build_call(self.swap_endianness(), &[ptr.into()], "swap_endian");
+                }
+                let topics = builder.build_pointer_cast(topics, self.context.i8_type().ptr_type(AddressSpace::Generic), "topics");
+
+                builder.build_call(self.emit_log(), &[data.into(), length.into(), topics.into(), count.into()], "emit_log");
                 self.build_decr(builder, sp, *n as u64 + 2);
             }
             Instruction::Stop => {
@@ -896,6 +1164,20 @@ impl<'a, 'ctx> Compiler<'a, 'ctx> {
             Instruction::Revert => {
                 let name = "revert";
                 self.push_label(name, builder);
+                // the revert data is returned like RETURN data so callers can decode the reason
+                let sp = self.build_sp(builder);
+                let length = self.build_peek(builder, sp, 2, "length");
+                let offset = self.build_peek(builder, sp, 1, "offset");
+                let _sp = self.build_decr(builder, sp, 2);
+
+                let length = builder.build_int_truncate_or_bit_cast(length, self.context.i64_type(), "length");
+                let offset = builder.build_int_truncate_or_bit_cast(offset, self.context.i64_type(), "offset");
+
+                let offset_ptr = self.fun.unwrap().get_nth_param(2).unwrap().into_pointer_value();
+                let len_ptr = self.fun.unwrap().get_nth_param(3).unwrap().into_pointer_value();
+                builder.build_store(offset_ptr, offset);
+                builder.build_store(len_ptr, length);
+
                 builder.build_unconditional_branch(self.errbb.unwrap());
             }
             Instruction::Jump => {
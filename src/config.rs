@@ -0,0 +1,219 @@
+//! Project configuration, read from `solenoid.toml`.
+//!
+//! ```toml
+//! sources = ["contracts/Token.sol"]
+//! contracts = ["Token"]
+//! output-dir = "build"
+//!
+//! [solc]
+//! version = "0.8.19"
+//! optimize-runs = 200
+//!
+//! [solc.libraries]
+//! SafeMath = "0x00000000000000000000000000000000000000aa"
+//!
+//! [build]
+//! target = "bpf"
+//! opt-level = 2
+//! llvm-dir = "/opt/solana-llvm/bin"
+//! ```
+//!
+//! Relative paths are relative to the directory of the configuration file.
+//! Command line flags take precedence over every setting.
+
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const FILE_NAME: &str = "solenoid.toml";
+
+/// Cargo features of `solenoid-rt`
+pub const RUNTIME_FEATURES: &[&str] = &["solana"];
+
+#[derive(Debug)]
+pub enum ConfigError {
+    IOError(PathBuf, std::io::Error),
+    InvalidToml(PathBuf, toml::de::Error),
+    Invalid(PathBuf, String),
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::IOError(path, err) => write!(f, "Unable to read {}: {}", path.display(), err),
+            Self::InvalidToml(path, err) => write!(f, "Invalid configuration {}: {}", path.display(), err),
+            Self::Invalid(path, err) => write!(f, "Invalid configuration {}: {}", path.display(), err),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct Config {
+    /// Sources, artifacts and artifact directories to compile
+    pub sources: Vec<PathBuf>,
+    /// Contracts to compile, by name or `<source>:<name>`, all of them if empty
+    pub contracts: Vec<String>,
+    pub output_dir: Option<PathBuf>,
+    pub solc: SolcConfig,
+    pub build: BuildConfig,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct SolcConfig {
+    /// solc binary, a path or an installed version
+    pub version: Option<String>,
+    /// Directory of installed solc versions
+    pub dir: Option<PathBuf>,
+    pub remappings: Vec<String>,
+    pub optimize_runs: Option<u32>,
+    pub evm_version: Option<String>,
+    pub via_ir: bool,
+    /// Library addresses, by `[<source>:]<library>`
+    pub libraries: BTreeMap<String, String>,
+}
+
+/// Where the generated Makefile builds for, and how
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct BuildConfig {
+    pub target: Target,
+    /// Optimization level of llc and the C sources, 0 to 3
+    pub opt_level: u8,
+    /// Cargo features the runtime is built with
+    pub features: Vec<String>,
+    /// Directory holding `llc` and `ld.lld`, found on `PATH` if unset
+    pub llvm_dir: Option<PathBuf>,
+}
+
+impl Default for BuildConfig {
+    fn default() -> Self {
+        Self {
+            target: Target::Host,
+            opt_level: 3,
+            features: Vec::new(),
+            llvm_dir: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Target {
+    Host,
+    /// Solana BPF program
+    Bpf,
+}
+
+impl std::str::FromStr for Target {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "host" => Ok(Target::Host),
+            "bpf" => Ok(Target::Bpf),
+            _ => Err(format!("unknown target {}, expected host or bpf", s)),
+        }
+    }
+}
+
+impl Config {
+    /// Read the configuration at `path`, resolving its paths against its directory
+    pub fn load(path: &Path) -> Result<Self, ConfigError> {
+        let text = fs::read_to_string(path).map_err(|e| ConfigError::IOError(path.to_owned(), e))?;
+        let mut config: Config = toml::from_str(&text).map_err(|e| ConfigError::InvalidToml(path.to_owned(), e))?;
+        if config.build.opt_level > 3 {
+            return Err(ConfigError::Invalid(path.to_owned(), format!("opt-level {} is not between 0 and 3", config.build.opt_level)));
+        }
+        if let Some(feature) = config.build.features.iter().find(|f| !RUNTIME_FEATURES.contains(&f.as_str())) {
+            return Err(ConfigError::Invalid(path.to_owned(), format!("unknown runtime feature {}, expected one of {}", feature, RUNTIME_FEATURES.join(", "))));
+        }
+
+        let root = path.parent().unwrap_or_else(|| Path::new(""));
+        let resolve = |path: &mut PathBuf| *path = root.join(&*path);
+        config.sources.iter_mut().for_each(resolve);
+        config.output_dir.iter_mut().for_each(resolve);
+        config.solc.dir.iter_mut().for_each(resolve);
+        config.build.llvm_dir.iter_mut().for_each(resolve);
+        Ok(config)
+    }
+
+    /// Configuration at `path` if given, else `solenoid.toml` in the working directory if there is one
+    pub fn find(path: Option<&Path>) -> Result<Self, ConfigError> {
+        match path {
+            Some(path) => Self::load(path),
+            None if Path::new(FILE_NAME).is_file() => Self::load(Path::new(FILE_NAME)),
+            None => Ok(Self::default()),
+        }
+    }
+}
+
+impl BuildConfig {
+    /// `config.mk`, included by the generated Makefile
+    pub fn format_makefile(&self) -> String {
+        let mut contents = String::new();
+        contents += &format!("# automatically generated by solenoid {}\n", env!("CARGO_PKG_VERSION"));
+        contents += &format!("TARGET := {}\n", match self.target {
+            Target::Host => "host",
+            Target::Bpf => "bpf",
+        });
+        contents += &format!("OPT_LEVEL := {}\n", self.opt_level);
+        contents += &format!("RT_FEATURES := {}\n", self.features.join(" "));
+        if let Some(llvm_dir) = &self.llvm_dir {
+            contents += &format!("LLVM_DIR := {}\n", llvm_dir.display());
+        }
+        contents
+    }
+
+    pub fn generate(&self, outdir: &PathBuf) -> std::io::Result<()> {
+        fs::create_dir_all(outdir)?;
+        fs::write(outdir.join("config.mk"), self.format_makefile())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load() {
+        let root = std::env::temp_dir().join(format!("solenoid-config-{}", std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+        let path = root.join(FILE_NAME);
+        fs::write(&path, r#"
+            sources = ["contracts/Token.sol"]
+            output-dir = "build"
+
+            [solc]
+            version = "0.8.19"
+            optimize-runs = 200
+
+            [solc.libraries]
+            SafeMath = "0x00000000000000000000000000000000000000aa"
+
+            [build]
+            target = "bpf"
+            features = ["solana"]
+        "#).unwrap();
+        let config = Config::load(&path).unwrap();
+        assert_eq!(config.sources, [root.join("contracts/Token.sol")]);
+        assert_eq!(config.output_dir, Some(root.join("build")));
+        assert_eq!((config.solc.version.as_deref(), config.solc.optimize_runs), (Some("0.8.19"), Some(200)));
+        assert_eq!(config.solc.libraries["SafeMath"], "0x00000000000000000000000000000000000000aa");
+        assert_eq!(config.build.target, Target::Bpf);
+        assert_eq!(config.build.opt_level, 3);
+        assert!(config.build.format_makefile().ends_with("TARGET := bpf\nOPT_LEVEL := 3\nRT_FEATURES := solana\n"));
+
+        fs::write(&path, "[build]\nopt-level = 4\n").unwrap();
+        assert!(matches!(Config::load(&path), Err(ConfigError::Invalid(..))));
+        fs::write(&path, "[build]\nfeatures = [\"gas\"]\n").unwrap();
+        assert!(matches!(Config::load(&path), Err(ConfigError::Invalid(..))));
+        fs::write(&path, "source = []\n").unwrap();
+        assert!(matches!(Config::load(&path), Err(ConfigError::InvalidToml(..))));
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
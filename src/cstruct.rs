@@ -0,0 +1,519 @@
+//! C structs for tuple parameters, written to `contracts.h` with the code
+//! encoding and decoding them.
+//!
+//! A tuple becomes `struct <C>_<Name>`, named after the Solidity struct its
+//! `internalType` refers to, or after the function and parameter otherwise.
+//! Its fields are:
+//!
+//! - integers up to 64 bits and `bool`: the C integer the ABI helpers take.
+//! - wider integers: 32 big endian bytes.
+//! - `address`, `bytesN`: their bytes.
+//! - `bytes`, `string` and dynamic arrays: an `abi_slice`. Arrays point to
+//!   their elements, in the representation of their type, and give their count.
+//! - fixed arrays: C arrays, tuples: nested structs.
+//!
+//! Functions taking or returning tuples get `abi_<C>_<fn>_struct` encoding
+//! their calldata from structs, and `abi_<C>_<fn>_decode_struct` decoding
+//! their return data into structs. Decoded arrays are allocated from an
+//! `abi_arena` of scratch memory, `bytes` and `string` point into the return
+//! data.
+
+use crate::cffi::CFFIGenerator;
+use crate::ethabi::{Function, Param, ParamType, TupleParam};
+use crate::symbols::Symbols;
+
+/// C representation of an ABI type
+#[derive(Clone, PartialEq, Debug)]
+enum CType {
+    /// Any type but arrays and tuples
+    Value(ParamType),
+    Array(Box<CType>),
+    FixedArray(Box<CType>, usize),
+    /// Index of the struct in `StructGenerator::structs`
+    Struct(usize),
+}
+
+#[derive(PartialEq, Debug)]
+struct CStruct {
+    name: String,
+    kind: ParamType,
+    fields: Vec<(String, CType)>,
+}
+
+/// Size of the head of an encoded param: the value itself when static, the
+/// offset of its tail when dynamic
+fn head_size(kind: &ParamType) -> u64 {
+    if kind.is_dynamic() {
+        return 32;
+    }
+    match kind {
+        ParamType::FixedArray(ty, n) => (*n as u64) * head_size(ty),
+        ParamType::Tuple(tys) => tys.iter().map(|ty| head_size(ty)).sum(),
+        _ => 32,
+    }
+}
+
+fn has_tuple(kind: &ParamType) -> bool {
+    match kind {
+        ParamType::Tuple(_) => true,
+        ParamType::Array(ty) | ParamType::FixedArray(ty, _) => has_tuple(ty),
+        _ => false,
+    }
+}
+
+/// Integers the ABI helpers take as C integers
+fn small_int(kind: &ParamType) -> Option<usize> {
+    match kind {
+        ParamType::Uint(bits) | ParamType::Int(bits) if [8, 16, 32, 64].contains(bits) => Some(*bits),
+        _ => None,
+    }
+}
+
+/// `Order` for `struct Pool.Order[]`
+fn struct_name(internal_type: Option<&str>) -> Option<&str> {
+    let name = internal_type?.strip_prefix("struct ")?;
+    let name = name.split('[').next().unwrap_or(name);
+    name.rsplit('.').next()
+}
+
+/// Append an array dimension to a declarator, `*p` becoming `(*p)[n]`
+fn dimension(decl: &str, n: usize) -> String {
+    if decl.starts_with('*') {
+        format!("({})[{}]", decl, n)
+    } else {
+        format!("{}[{}]", decl, n)
+    }
+}
+
+/// Generate the structs of the tuples used by one contract
+pub struct StructGenerator {
+    contract_name: String,
+    structs: Vec<CStruct>,
+    fn_wrappers: Vec<String>,
+}
+
+impl StructGenerator {
+    pub fn new(contract_name: &str) -> Self {
+        Self {
+            contract_name: contract_name.to_owned(),
+            structs: Vec::new(),
+            fn_wrappers: Vec::new(),
+        }
+    }
+
+    fn c_type(&mut self, kind: &ParamType, components: &[TupleParam], internal_type: Option<&str>, name: &str) -> CType {
+        match kind {
+            ParamType::Array(ty) => CType::Array(Box::new(self.c_type(ty, components, internal_type, name))),
+            ParamType::FixedArray(ty, n) => CType::FixedArray(Box::new(self.c_type(ty, components, internal_type, name)), *n),
+            ParamType::Tuple(tys) => {
+                let name = struct_name(internal_type).unwrap_or(name).to_owned();
+                let fields = tys.iter().enumerate().map(|(i, ty)| {
+                    let component = components.get(i);
+                    let field = match component.and_then(|c| c.name.as_ref()) {
+                        Some(field) if !field.is_empty() => field.clone(),
+                        _ => format!("_{}", i),
+                    };
+                    let c_type = self.c_type(
+                        ty,
+                        component.map(|c| c.components.as_slice()).unwrap_or(&[]),
+                        component.and_then(|c| c.internal_type.as_deref()),
+                        &format!("{}_{}", name, field));
+                    (field, c_type)
+                }).collect();
+                CType::Struct(self.add_struct(&name, kind, fields))
+            }
+            kind => CType::Value(kind.clone()),
+        }
+    }
+
+    /// Index of the struct, numbering structs of the same name but different members
+    fn add_struct(&mut self, name: &str, kind: &ParamType, fields: Vec<(String, CType)>) -> usize {
+        let mut c_struct = CStruct {
+            name: format!("{}_{}", self.contract_name, name),
+            kind: kind.clone(),
+            fields,
+        };
+        for n in 1.. {
+            match self.structs.iter().position(|s| s.name == c_struct.name) {
+                Some(idx) if self.structs[idx] == c_struct => return idx,
+                Some(_) => c_struct.name = format!("{}_{}{}", self.contract_name, name, n),
+                None => break,
+            }
+        }
+        self.structs.push(c_struct);
+        self.structs.len() - 1
+    }
+
+    fn param_type(&mut self, fun: &Function, param: &Param, name: &str) -> CType {
+        self.c_type(&param.kind, &param.components, param.internal_type.as_deref(), &format!("{}_{}", fun.name, name))
+    }
+
+    fn kind(&self, c_type: &CType) -> ParamType {
+        match c_type {
+            CType::Value(kind) => kind.clone(),
+            CType::Array(ty) => ParamType::Array(Box::new(self.kind(ty))),
+            CType::FixedArray(ty, n) => ParamType::FixedArray(Box::new(self.kind(ty)), *n),
+            CType::Struct(idx) => self.structs[*idx].kind.clone(),
+        }
+    }
+
+    /// C declaration of `decl` of type `c_type`
+    fn declare(&self, c_type: &CType, decl: &str) -> String {
+        match c_type {
+            CType::Value(kind) => match kind {
+                ParamType::Bool => format!("int {}", decl),
+                ParamType::Address => format!("i8 {}", dimension(decl, 20)),
+                ParamType::FixedBytes(n) => format!("i8 {}", dimension(decl, *n)),
+                ParamType::Bytes | ParamType::String => format!("abi_slice {}", decl),
+                kind if small_int(kind).is_some() => format!("{} {}", CFFIGenerator::c_type(kind), decl),
+                _ => format!("i8 {}", dimension(decl, 32)),
+            },
+            CType::Array(_) => format!("abi_slice {}", decl),
+            CType::FixedArray(ty, n) => self.declare(ty, &dimension(decl, *n)),
+            CType::Struct(idx) => format!("struct {} {}", self.structs[*idx].name, decl),
+        }
+    }
+
+    /// Whether the C value of `c_type` is an array, which is passed by pointer
+    fn is_c_array(c_type: &CType) -> bool {
+        match c_type {
+            CType::Value(kind) => match kind {
+                ParamType::Bool | ParamType::Bytes | ParamType::String => false,
+                kind => small_int(kind).is_none(),
+            },
+            CType::FixedArray(_, _) => true,
+            _ => false,
+        }
+    }
+
+    /// Statements writing the static value `v` at `at`
+    fn encode_head(&self, c_type: &CType, v: &str, at: &str, ind: &str, depth: usize) -> String {
+        match c_type {
+            CType::Value(kind) => match kind {
+                ParamType::Address => format!("{}abi_put_word(_out, {}, {}, 20, 1);\n", ind, at, v),
+                ParamType::FixedBytes(n) => format!("{}abi_put_word(_out, {}, {}, {}, 0);\n", ind, at, v, n),
+                ParamType::Bool => format!("{}abi_put_uint(_out, {}, {} != 0);\n", ind, at, v),
+                ParamType::Uint(bits) if small_int(kind).is_some() => {
+                    format!("{}abi_put_uint(_out, {}, (uint{}_t){});\n", ind, at, bits, v)
+                }
+                ParamType::Int(bits) if small_int(kind).is_some() => {
+                    format!("{}abi_put_int(_out, {}, (int{}_t){});\n", ind, at, bits, v)
+                }
+                _ => format!("{}abi_put_word(_out, {}, {}, 32, 0);\n", ind, at, v),
+            },
+            CType::Struct(idx) => format!("{}{}_encode(_out, {}, &{});\n", ind, self.structs[*idx].name, at, v),
+            CType::FixedArray(ty, n) => {
+                let i = format!("_i{}", depth);
+                let mut code = format!("{}for (uint64_t {i} = 0; {i} < {}; {i}++) {{\n", ind, n, i = i);
+                let at = format!("{} + {} * {}", at, i, head_size(&self.kind(ty)));
+                code += &self.encode_head(ty, &format!("{}[{}]", v, i), &at, &format!("{}    ", ind), depth + 1);
+                code += &format!("{}}}\n", ind);
+                code
+            }
+            CType::Array(_) => unreachable!("dynamic array in head"),
+        }
+    }
+
+    /// Statements writing the dynamic value `v` at `at` and setting `size` to its size
+    fn encode_tail(&self, c_type: &CType, v: &str, at: &str, size: &str, ind: &str, depth: usize) -> String {
+        match c_type {
+            CType::Struct(idx) => format!("{}{} = {}_encode(_out, {}, &{});\n", ind, size, self.structs[*idx].name, at, v),
+            CType::Array(ty) => {
+                let elems = format!("((const {})({}.ptr))", self.declare(ty, "*"), v);
+                let mut code = format!("{}abi_put_uint(_out, {}, {}.len);\n", ind, at, v);
+                code += &self.encode_elements(ty, &elems, &format!("{}.len", v), &format!("{} + 32", at), size, ind, depth);
+                code += &format!("{}{} += 32;\n", ind, size);
+                code
+            }
+            CType::FixedArray(ty, n) => self.encode_elements(ty, v, &n.to_string(), at, size, ind, depth),
+            _ => format!("{}{} = abi_put_bytes(_out, {}, {}.ptr, {}.len);\n", ind, size, at, v, v),
+        }
+    }
+
+    /// Statements writing the `count` elements of `elems` as a sequence at `at`
+    fn encode_elements(&self, ty: &CType, elems: &str, count: &str, at: &str, size: &str, ind: &str, depth: usize) -> String {
+        let (b, h, t, i, n) = (format!("_b{}", depth), format!("_h{}", depth), format!("_t{}", depth), format!("_i{}", depth), format!("_n{}", depth));
+        let kind = self.kind(ty);
+        let elem_size = head_size(&kind);
+        let inner = format!("{}        ", ind);
+        let elem = format!("{}[{}]", elems, i);
+        let mut code = format!("{}{{\n", ind);
+        code += &format!("{}    uint64_t {b} = {}, {h} = {b}, {t} = {b} + {} * {};\n", ind, at, count, elem_size, b = b, h = h, t = t);
+        code += &format!("{}    for (uint64_t {i} = 0; {i} < {}; {i}++, {h} += {}) {{\n", ind, count, elem_size, i = i, h = h);
+        if kind.is_dynamic() {
+            code += &format!("{}uint64_t {};\n", inner, n);
+            code += &format!("{}abi_put_uint(_out, {}, {} - {});\n", inner, h, t, b);
+            code += &self.encode_tail(ty, &elem, &t, &n, &inner, depth + 1);
+            code += &format!("{}{} += {};\n", inner, t, n);
+        } else {
+            code += &self.encode_head(ty, &elem, &h, &inner, depth + 1);
+        }
+        code += &format!("{}    }}\n", ind);
+        code += &format!("{}    {} = {} - {};\n", ind, size, t, b);
+        code += &format!("{}}}\n", ind);
+        code
+    }
+
+    /// Statements writing `items` as a sequence at `_at`, advancing the tail `_t`
+    fn encode_items(&self, items: &[(CType, String)], ind: &str) -> String {
+        let mut code = String::new();
+        let mut head = 0;
+        for (c_type, v) in items {
+            let at = format!("_at + {}", head);
+            let kind = self.kind(c_type);
+            if kind.is_dynamic() {
+                code += &format!("{}abi_put_uint(_out, {}, _t - _at);\n", ind, at);
+                code += &format!("{}{{\n", ind);
+                code += &format!("{}    uint64_t _n;\n", ind);
+                code += &self.encode_tail(c_type, v, "_t", "_n", &format!("{}    ", ind), 0);
+                code += &format!("{}    _t += _n;\n", ind);
+                code += &format!("{}}}\n", ind);
+            } else {
+                code += &self.encode_head(c_type, v, &at, ind, 0);
+            }
+            head += head_size(&kind);
+        }
+        code
+    }
+
+    /// Statements reading the static value `v` at `at`
+    fn decode_head(&self, c_type: &CType, v: &str, at: &str, ind: &str, depth: usize) -> String {
+        match c_type {
+            CType::Value(kind) => match kind {
+                ParamType::Address => format!("{}if (!abi_get_word(_in, _size, {}, {}, 20, 1)) return 0;\n", ind, at, v),
+                ParamType::FixedBytes(n) => format!("{}if (!abi_get_word(_in, _size, {}, {}, {}, 0)) return 0;\n", ind, at, v, n),
+                ParamType::Bool => format!(
+                    "{}{{ uint64_t _x; if (!abi_get_uint(_in, _size, {}, 1, &_x)) return 0; {} = _x; }}\n", ind, at, v),
+                ParamType::Uint(bits) if small_int(kind).is_some() => format!(
+                    "{}{{ uint64_t _x; if (!abi_get_uint(_in, _size, {}, {}, &_x)) return 0; {} = _x; }}\n", ind, at, bits, v),
+                ParamType::Int(bits) if small_int(kind).is_some() => format!(
+                    "{}{{ int64_t _x; if (!abi_get_int(_in, _size, {}, {}, &_x)) return 0; {} = _x; }}\n", ind, at, bits, v),
+                _ => format!("{}if (!abi_get_word(_in, _size, {}, {}, 32, 0)) return 0;\n", ind, at, v),
+            },
+            CType::Struct(idx) => {
+                format!("{}if (!{}_decode(_in, _size, {}, &{}, _arena)) return 0;\n", ind, self.structs[*idx].name, at, v)
+            }
+            CType::FixedArray(ty, n) => {
+                let i = format!("_i{}", depth);
+                let mut code = format!("{}for (uint64_t {i} = 0; {i} < {}; {i}++) {{\n", ind, n, i = i);
+                let at = format!("{} + {} * {}", at, i, head_size(&self.kind(ty)));
+                code += &self.decode_head(ty, &format!("{}[{}]", v, i), &at, &format!("{}    ", ind), depth + 1);
+                code += &format!("{}}}\n", ind);
+                code
+            }
+            CType::Array(_) => unreachable!("dynamic array in head"),
+        }
+    }
+
+    /// Statements reading the dynamic value `v` at `at`
+    fn decode_tail(&self, c_type: &CType, v: &str, at: &str, ind: &str, depth: usize) -> String {
+        match c_type {
+            CType::Struct(idx) => {
+                format!("{}if (!{}_decode(_in, _size, {}, &{}, _arena)) return 0;\n", ind, self.structs[*idx].name, at, v)
+            }
+            CType::Array(ty) => {
+                let (len, p) = (format!("_len{}", depth), format!("_p{}", depth));
+                let mut code = format!("{}{{\n", ind);
+                code += &format!("{}    uint64_t {};\n", ind, len);
+                // every element takes a word at least
+                code += &format!("{}    if (!abi_get_uint(_in, _size, {}, 64, &{len}) || {len} > _size / 32) return 0;\n", ind, at, len = len);
+                code += &format!("{}    {} = abi_alloc(_arena, {} * sizeof(*{}));\n", ind, self.declare(ty, &format!("*{}", p)), len, p);
+                code += &format!("{}    if (!{}) return 0;\n", ind, p);
+                code += &format!("{}    {}.ptr = (const i8*){};\n", ind, v, p);
+                code += &format!("{}    {}.len = {};\n", ind, v, len);
+                code += &self.decode_elements(ty, &p, &len, &format!("{} + 32", at), &format!("{}    ", ind), depth);
+                code += &format!("{}}}\n", ind);
+                code
+            }
+            CType::FixedArray(ty, n) => self.decode_elements(ty, v, &n.to_string(), at, ind, depth),
+            _ => format!("{}if (!abi_get_bytes(_in, _size, {}, &{})) return 0;\n", ind, at, v),
+        }
+    }
+
+    /// Statements reading the `count` elements of `elems` from a sequence at `at`
+    fn decode_elements(&self, ty: &CType, elems: &str, count: &str, at: &str, ind: &str, depth: usize) -> String {
+        let (b, i, o) = (format!("_b{}", depth), format!("_i{}", depth), format!("_o{}", depth));
+        let kind = self.kind(ty);
+        let inner = format!("{}        ", ind);
+        let elem = format!("{}[{}]", elems, i);
+        let head = format!("{} + {} * {}", b, i, head_size(&kind));
+        let mut code = format!("{}{{\n", ind);
+        code += &format!("{}    uint64_t {} = {};\n", ind, b, at);
+        code += &format!("{}    for (uint64_t {i} = 0; {i} < {}; {i}++) {{\n", ind, count, i = i);
+        if kind.is_dynamic() {
+            code += &format!("{}uint64_t {};\n", inner, o);
+            code += &format!("{}if (!abi_get_uint(_in, _size, {}, 64, &{o}) || {o} > _size) return 0;\n", inner, head, o = o);
+            code += &self.decode_tail(ty, &elem, &format!("{} + {}", b, o), &inner, depth + 1);
+        } else {
+            code += &self.decode_head(ty, &elem, &head, &inner, depth + 1);
+        }
+        code += &format!("{}    }}\n", ind);
+        code += &format!("{}}}\n", ind);
+        code
+    }
+
+    /// Statements reading `items` from a sequence at `_at`
+    fn decode_items(&self, items: &[(CType, String)], ind: &str) -> String {
+        let mut code = String::new();
+        let mut head = 0;
+        for (c_type, v) in items {
+            let at = format!("_at + {}", head);
+            let kind = self.kind(c_type);
+            if kind.is_dynamic() {
+                code += &format!("{}{{\n", ind);
+                code += &format!("{}    uint64_t _o;\n", ind);
+                code += &format!("{}    if (!abi_get_uint(_in, _size, {}, 64, &_o) || _o > _size) return 0;\n", ind, at);
+                code += &self.decode_tail(c_type, v, "_at + _o", &format!("{}    ", ind), 0);
+                code += &format!("{}}}\n", ind);
+            } else {
+                code += &self.decode_head(c_type, v, &at, ind, 0);
+            }
+            head += head_size(&kind);
+        }
+        code
+    }
+
+    /// Encoder and decoder of `fun` with its tuples as structs, if it has any
+    pub fn add_function(&mut self, fun: &Function, symbol: &str) {
+        if fun.inputs.iter().any(|param| has_tuple(&param.kind)) {
+            let mut params = vec!["i8* tx".to_owned(), "int* tx_size".to_owned()];
+            let mut items = Vec::new();
+            for (i, param) in fun.inputs.iter().enumerate() {
+                let name = if param.name.is_empty() { format!("arg{}", i) } else { param.name.clone() };
+                let c_type = self.param_type(fun, param, &name);
+                match c_type {
+                    CType::Struct(_) => {
+                        params.push(format!("const {}", self.declare(&c_type, &format!("*{}", name))));
+                        items.push((c_type, format!("(*{})", name)));
+                    }
+                    _ if Self::is_c_array(&c_type) => {
+                        params.push(format!("const {}", self.declare(&c_type, &name)));
+                        items.push((c_type, name));
+                    }
+                    _ => {
+                        params.push(self.declare(&c_type, &name));
+                        items.push((c_type, name));
+                    }
+                }
+            }
+            let heads: u64 = fun.inputs.iter().map(|param| head_size(&param.kind)).sum();
+            let selector = fun.short_signature().iter().map(|b| format!("\\x{:02x}", b)).collect::<String>();
+
+            let mut code = format!("/* {} calldata, a null tx gives its size only */\n", Symbols::signature(fun));
+            code += &format!("static inline void {}_struct({}) {{\n", symbol, params.join(", "));
+            code += "    i8* _out = tx;\n";
+            code += &format!("    uint64_t _at = 4, _t = _at + {};\n", heads);
+            code += "    if (tx) {\n";
+            code += &format!("        memcpy(tx, \"{}\", 4);\n", selector);
+            code += "    }\n";
+            code += &self.encode_items(&items, "    ");
+            code += "    *tx_size = _t;\n";
+            code += "}";
+            self.fn_wrappers.push(code);
+        }
+
+        if fun.outputs.iter().any(|param| has_tuple(&param.kind)) {
+            let mut params = vec!["i8* ret".to_owned(), "long ret_len".to_owned()];
+            let mut items = Vec::new();
+            for (i, param) in fun.outputs.iter().enumerate() {
+                let name = if param.name.is_empty() { format!("out{}", i) } else { param.name.clone() };
+                let c_type = self.param_type(fun, param, &name);
+                if Self::is_c_array(&c_type) {
+                    params.push(self.declare(&c_type, &name));
+                    items.push((c_type, name));
+                } else {
+                    params.push(self.declare(&c_type, &format!("*{}", name)));
+                    items.push((c_type, format!("(*{})", name)));
+                }
+            }
+            params.push("abi_arena* arena".to_owned());
+
+            let mut code = format!("/* {} return data, converted in place like {}_decode does */\n", fun.signature(), symbol);
+            code += &format!("static inline int {}_decode_struct({}) {{\n", symbol, params.join(", "));
+            code += "    const i8* _in = ret;\n";
+            code += "    uint64_t _size = ret_len, _at = 0;\n";
+            code += "    abi_arena* _arena = arena;\n";
+            code += "    swap_words(ret, ret_len);\n";
+            code += &self.decode_items(&items, "    ");
+            code += "    return 1;\n";
+            code += "}";
+            self.fn_wrappers.push(code);
+        }
+    }
+
+    /// Struct definitions, their encoders and decoders, and the function wrappers
+    pub fn generate(&self) -> String {
+        let mut sections = Vec::new();
+        for c_struct in &self.structs {
+            let mut code = format!("/* {} */\n", c_struct.kind);
+            code += &format!("struct {} {{\n", c_struct.name);
+            for (field, c_type) in &c_struct.fields {
+                code += &format!("    {}; /* {} */\n", self.declare(c_type, field), self.kind(c_type));
+            }
+            code += "};";
+            sections.push(code);
+        }
+        for c_struct in &self.structs {
+            let items = c_struct.fields.iter()
+                .map(|(field, c_type)| (c_type.clone(), format!("_v->{}", field)))
+                .collect::<Vec<_>>();
+
+            let mut code = "/* writes the encoding at out + at and returns its size, a null out gives the size only */\n".to_owned();
+            code += &format!("static inline uint64_t {}_encode(i8* _out, uint64_t _at, const struct {}* _v) {{\n", c_struct.name, c_struct.name);
+            let heads: u64 = c_struct.fields.iter().map(|(_, c_type)| head_size(&self.kind(c_type))).sum();
+            code += &format!("    uint64_t _t = _at + {};\n", heads);
+            code += &self.encode_items(&items, "    ");
+            code += "    return _t - _at;\n";
+            code += "}\n\n";
+
+            code += "/* reads the encoding at in + at, returns 0 if it is malformed or the arena is exhausted */\n";
+            code += &format!("static inline int {}_decode(const i8* _in, uint64_t _size, uint64_t _at, struct {}* _v, abi_arena* _arena) {{\n", c_struct.name, c_struct.name);
+            code += &self.decode_items(&items, "    ");
+            code += "    return 1;\n";
+            code += "}";
+            sections.push(code);
+        }
+        sections.extend(self.fn_wrappers.iter().cloned());
+        sections.join("\n\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ethabi::Contract;
+
+    #[test]
+    fn test_struct_names() {
+        let abi = r#"[
+            {"type":"function","name":"fill","inputs":[
+                {"name":"order","type":"tuple","internalType":"struct Pool.Order","components":[
+                    {"name":"maker","type":"address"},
+                    {"name":"legs","type":"tuple[]","internalType":"struct Pool.Leg[]","components":[
+                        {"name":"amount","type":"uint64"},{"name":"memo","type":"string"}]}]},
+                {"name":"point","type":"tuple","components":[{"name":"x","type":"int16"},{"name":"","type":"uint256[2]"}]}],
+             "outputs":[],"stateMutability":"nonpayable"},
+            {"type":"function","name":"cancel","inputs":[
+                {"name":"order","type":"tuple","internalType":"struct Pool.Order","components":[
+                    {"name":"maker","type":"address"},
+                    {"name":"legs","type":"tuple[]","internalType":"struct Pool.Leg[]","components":[
+                        {"name":"amount","type":"uint64"},{"name":"memo","type":"string"}]}]}],
+             "outputs":[{"name":"","type":"tuple","internalType":"struct Pool.Leg","components":[{"name":"amount","type":"uint256"}]}],
+             "stateMutability":"nonpayable"}
+        ]"#;
+        let contract = Contract::load(abi.as_bytes()).unwrap();
+        let mut structs = StructGenerator::new("Pool");
+        structs.add_function(&contract.functions["fill"][0], "abi_Pool_fill");
+        structs.add_function(&contract.functions["cancel"][0], "abi_Pool_cancel");
+
+        let names = structs.structs.iter().map(|s| s.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, ["Pool_Leg", "Pool_Order", "Pool_fill_point", "Pool_Leg1"]);
+        let code = structs.generate();
+        assert!(code.contains("    abi_slice legs; /* (uint64,string)[] */\n"));
+        assert!(code.contains("    i8 _1[2][32]; /* uint256[2] */\n"));
+        assert!(code.contains("static inline void abi_Pool_fill_struct(i8* tx, int* tx_size, \
+            const struct Pool_Order *order, const struct Pool_fill_point *point)"));
+        assert!(code.contains("static inline int abi_Pool_cancel_decode_struct(i8* ret, long ret_len, \
+            struct Pool_Leg1 *out0, abi_arena* arena)"));
+    }
+}
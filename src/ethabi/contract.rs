@@ -7,7 +7,7 @@
 // except according to those terms.
 
 use crate::ethabi::operation::Operation;
-use crate::ethabi::{errors, Constructor, Error, Event, Function};
+use crate::ethabi::{errors, AbiError, Constructor, Error, Event, Function};
 use serde::de::{SeqAccess, Visitor};
 use serde::{Deserialize, Deserializer};
 use serde_json;
@@ -25,8 +25,12 @@ pub struct Contract {
 	pub functions: BTreeMap<String, Vec<Function>>,
 	/// Contract events, maps signature to event.
 	pub events: BTreeMap<String, Vec<Event>>,
+	/// Contract custom errors.
+	pub errors: BTreeMap<String, Vec<AbiError>>,
 	/// Contract has fallback function.
 	pub fallback: bool,
+	/// Contract has receive function.
+	pub receive: bool,
 }
 
 impl<'a> Deserialize<'a> for Contract {
@@ -51,24 +55,17 @@ impl<'a> Visitor<'a> for ContractVisitor {
 	where
 		A: SeqAccess<'a>,
 	{
-		let mut result =
-			Contract { constructor: None, functions: BTreeMap::default(), events: BTreeMap::default(), fallback: false };
+		let mut result = Contract {
+			constructor: None,
+			functions: BTreeMap::default(),
+			events: BTreeMap::default(),
+			errors: BTreeMap::default(),
+			fallback: false,
+			receive: false,
+		};
 
 		while let Some(operation) = seq.next_element()? {
-			match operation {
-				Operation::Constructor(constructor) => {
-					result.constructor = Some(constructor);
-				}
-				Operation::Function(func) => {
-					result.functions.entry(func.name.clone()).or_default().push(func);
-				}
-				Operation::Event(event) => {
-					result.events.entry(event.name.clone()).or_default().push(event);
-				}
-				Operation::Fallback => {
-					result.fallback = true;
-				}
-			}
+			result.push(operation);
 		}
 
 		Ok(result)
@@ -76,11 +73,72 @@ impl<'a> Visitor<'a> for ContractVisitor {
 }
 
 impl Contract {
-	/// Loads contract from json.
+	/// Loads contract from json, either a JSON ABI or an array of human-readable fragments.
 	pub fn load<T: std::io::Read>(reader: T) -> errors::Result<Self> {
 		serde_json::from_reader(reader).map_err(From::from)
 	}
 
+	/// Parses a human-readable ABI, one fragment per line. Blank lines and
+	/// `//` comments are skipped.
+	pub fn from_human_readable(abi: &str) -> errors::Result<Self> {
+		let mut result = Contract {
+			constructor: None,
+			functions: BTreeMap::default(),
+			events: BTreeMap::default(),
+			errors: BTreeMap::default(),
+			fallback: false,
+			receive: false,
+		};
+		for line in abi.lines().map(str::trim) {
+			if line.is_empty() || line.starts_with("//") {
+				continue;
+			}
+			result.push(line.parse()?);
+		}
+		Ok(result)
+	}
+
+	/// Writes the contract as a human-readable ABI, one fragment per line.
+	pub fn to_human_readable(&self) -> String {
+		let mut lines = Vec::new();
+		if let Some(constructor) = &self.constructor {
+			lines.push(constructor.to_string());
+		}
+		lines.extend(self.functions().map(ToString::to_string));
+		lines.extend(self.events().map(ToString::to_string));
+		lines.extend(self.errors.values().flatten().map(ToString::to_string));
+		if self.fallback {
+			lines.push(Operation::Fallback.to_string());
+		}
+		if self.receive {
+			lines.push(Operation::Receive.to_string());
+		}
+		lines.join("\n")
+	}
+
+	fn push(&mut self, operation: Operation) {
+		match operation {
+			Operation::Constructor(constructor) => {
+				self.constructor = Some(constructor);
+			}
+			Operation::Function(func) => {
+				self.functions.entry(func.name.clone()).or_default().push(func);
+			}
+			Operation::Event(event) => {
+				self.events.entry(event.name.clone()).or_default().push(event);
+			}
+			Operation::Error(error) => {
+				self.errors.entry(error.name.clone()).or_default().push(error);
+			}
+			Operation::Fallback => {
+				self.fallback = true;
+			}
+			Operation::Receive => {
+				self.receive = true;
+			}
+		}
+	}
+
 	/// Creates constructor call builder.
 	pub fn constructor(&self) -> Option<&Constructor> {
 		self.constructor.as_ref()
@@ -102,6 +160,16 @@ impl Contract {
 		self.events.get(name).ok_or_else(|| Error::InvalidName(name.to_owned()))
 	}
 
+	/// Get the custom error named `name`, the first if there are multiple.
+	pub fn error(&self, name: &str) -> errors::Result<&AbiError> {
+		self.errors.get(name).into_iter().flatten().next().ok_or_else(|| Error::InvalidName(name.to_owned()))
+	}
+
+	/// Get all custom errors named `name`.
+	pub fn errors_by_name(&self, name: &str) -> errors::Result<&Vec<AbiError>> {
+		self.errors.get(name).ok_or_else(|| Error::InvalidName(name.to_owned()))
+	}
+
 	/// Get all functions named `name`.
 	pub fn functions_by_name(&self, name: &str) -> errors::Result<&Vec<Function>> {
 		self.functions.get(name).ok_or_else(|| Error::InvalidName(name.to_owned()))
@@ -121,6 +189,11 @@ impl Contract {
 	pub fn fallback(&self) -> bool {
 		self.fallback
 	}
+
+	/// Returns true if contract has receive
+	pub fn receive(&self) -> bool {
+		self.receive
+	}
 }
 
 /// Contract functions iterator.
@@ -0,0 +1,264 @@
+// Copyright 2015-2020 Parity Technologies
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! EIP-712 typed structured data hashing.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::str::FromStr;
+
+use serde::Deserialize;
+use serde_json::Value;
+use tiny_keccak::keccak256;
+
+use crate::ethabi::param_type::Reader;
+use crate::ethabi::token::{LenientTokenizer, Tokenizer};
+use crate::ethabi::{encode, Bytes, Error, Hash, ParamType, Result, Token, Uint};
+
+/// Member of a struct type.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Field {
+	/// Member name.
+	pub name: String,
+	/// Member type, an atomic type, a struct type or an array of those.
+	#[serde(rename = "type")]
+	pub kind: String,
+}
+
+/// Typed data document, as signed by `eth_signTypedData_v4`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TypedData {
+	/// Struct types by name.
+	pub types: BTreeMap<String, Vec<Field>>,
+	/// Type of `message`.
+	pub primary_type: String,
+	/// Signing domain, an `EIP712Domain` value.
+	pub domain: Value,
+	/// Message to sign.
+	pub message: Value,
+}
+
+/// Domain members in the order of the specification, with their types.
+const DOMAIN_FIELDS: &[(&str, &str)] = &[
+	("name", "string"),
+	("version", "string"),
+	("chainId", "uint256"),
+	("verifyingContract", "address"),
+	("salt", "bytes32"),
+];
+
+fn parse_uint(value: &str) -> Result<Uint> {
+	if value.starts_with("0x") {
+		Uint::from_str(&value[2..]).map_err(|_| Error::InvalidData)
+	} else {
+		Uint::from_dec_str(value).map_err(From::from)
+	}
+}
+
+impl TypedData {
+	/// Loads typed data from json.
+	pub fn load<T: std::io::Read>(reader: T) -> Result<Self> {
+		let mut typed_data: TypedData = serde_json::from_reader(reader)?;
+		// the domain type can be left out, it then has the members the domain sets
+		if !typed_data.types.contains_key("EIP712Domain") {
+			let fields = DOMAIN_FIELDS
+				.iter()
+				.filter(|(name, _)| typed_data.domain.get(name).is_some())
+				.map(|(name, kind)| Field { name: (*name).to_owned(), kind: (*kind).to_owned() })
+				.collect();
+			typed_data.types.insert("EIP712Domain".to_owned(), fields);
+		}
+		Ok(typed_data)
+	}
+
+	fn fields(&self, name: &str) -> Result<&Vec<Field>> {
+		self.types.get(name).ok_or_else(|| Error::InvalidName(name.to_owned()))
+	}
+
+	/// Struct type `kind` refers to, stripped of array dimensions.
+	fn struct_type<'a>(&self, kind: &'a str) -> Option<&'a str> {
+		let base = kind.split('[').next().unwrap_or(kind);
+		match self.types.contains_key(base) {
+			true => Some(base),
+			false => None,
+		}
+	}
+
+	fn dependencies(&self, name: &str, found: &mut BTreeSet<String>) -> Result<()> {
+		for field in self.fields(name)? {
+			if let Some(dependency) = self.struct_type(&field.kind) {
+				if found.insert(dependency.to_owned()) {
+					self.dependencies(dependency, found)?;
+				}
+			}
+		}
+		Ok(())
+	}
+
+	/// Returns the type encoding, e.g. `Mail(Person from,Person to,string contents)Person(string name,address wallet)`.
+	pub fn encode_type(&self, name: &str) -> Result<String> {
+		let mut dependencies = BTreeSet::new();
+		self.dependencies(name, &mut dependencies)?;
+		dependencies.remove(name);
+
+		let mut result = String::new();
+		for name in Some(name).into_iter().chain(dependencies.iter().map(String::as_str)) {
+			let fields = self.fields(name)?.iter().map(|f| format!("{} {}", f.kind, f.name)).collect::<Vec<_>>();
+			result += &format!("{}({})", name, fields.join(","));
+		}
+		Ok(result)
+	}
+
+	/// Returns the hash of the type encoding.
+	pub fn type_hash(&self, name: &str) -> Result<Hash> {
+		Ok(keccak256(self.encode_type(name)?.as_bytes()).into())
+	}
+
+	/// Encodes struct `value` of type `name`: its type hash followed by one word per member.
+	pub fn encode_data(&self, name: &str, value: &Value) -> Result<Bytes> {
+		let mut result = self.type_hash(name)?.as_bytes().to_vec();
+		for field in self.fields(name)? {
+			let member = value.get(&field.name).ok_or_else(|| Error::Other(format!("missing {}.{}", name, field.name)))?;
+			result.extend_from_slice(&self.encode_value(&field.kind, member)?);
+		}
+		Ok(result)
+	}
+
+	/// Returns the hash of struct `value` of type `name`.
+	pub fn hash_struct(&self, name: &str, value: &Value) -> Result<Hash> {
+		Ok(keccak256(&self.encode_data(name, value)?).into())
+	}
+
+	/// Returns the hash of the signing domain.
+	pub fn domain_separator(&self) -> Result<Hash> {
+		self.hash_struct("EIP712Domain", &self.domain)
+	}
+
+	/// Returns the digest to sign, `keccak256("\x19\x01" ‖ domainSeparator ‖ hashStruct(message))`.
+	pub fn digest(&self) -> Result<Hash> {
+		let mut data = vec![0x19, 0x01];
+		data.extend_from_slice(self.domain_separator()?.as_bytes());
+		data.extend_from_slice(self.hash_struct(&self.primary_type, &self.message)?.as_bytes());
+		Ok(keccak256(&data).into())
+	}
+
+	/// Encodes a member of type `kind` into one word.
+	fn encode_value(&self, kind: &str, value: &Value) -> Result<[u8; 32]> {
+		// arrays hash the concatenated encoding of their elements
+		if kind.ends_with(']') {
+			let open = kind.rfind('[').ok_or_else(|| Error::InvalidName(kind.to_owned()))?;
+			let elements = value.as_array().ok_or(Error::InvalidData)?;
+			let len = &kind[open + 1..kind.len() - 1];
+			if !len.is_empty() && len.parse::<usize>()? != elements.len() {
+				return Err(Error::InvalidData);
+			}
+			let mut data = Vec::new();
+			for element in elements {
+				data.extend_from_slice(&self.encode_value(&kind[..open], element)?);
+			}
+			return Ok(keccak256(&data));
+		}
+		if self.types.contains_key(kind) {
+			return Ok(self.hash_struct(kind, value)?.to_fixed_bytes());
+		}
+
+		let text = match value {
+			Value::String(s) => s.clone(),
+			Value::Number(n) => n.to_string(),
+			Value::Bool(b) => b.to_string(),
+			_ => return Err(Error::InvalidData),
+		};
+		let hex = text.trim_start_matches("0x");
+		let token = match Reader::read(kind)? {
+			ParamType::String => return Ok(keccak256(text.as_bytes())),
+			ParamType::Bytes => return Ok(keccak256(&LenientTokenizer::tokenize_bytes(hex)?)),
+			ParamType::Uint(_) => Token::Uint(parse_uint(&text)?),
+			ParamType::Int(_) if text.starts_with('-') => Token::Int((!parse_uint(&text[1..])?).overflowing_add(Uint::one()).0),
+			ParamType::Int(_) => Token::Int(parse_uint(&text)?),
+			param @ ParamType::Address | param @ ParamType::FixedBytes(_) => LenientTokenizer::tokenize(&param, hex)?,
+			param @ ParamType::Bool => LenientTokenizer::tokenize(&param, &text)?,
+			_ => return Err(Error::InvalidName(kind.to_owned())),
+		};
+		let mut word = [0u8; 32];
+		word.copy_from_slice(&encode(&[token]));
+		Ok(word)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use hex::FromHex;
+
+	fn hash(hex: &str) -> Hash {
+		Hash::from_slice(&hex.from_hex::<Vec<u8>>().unwrap())
+	}
+
+	#[test]
+	fn test_mail() {
+		// the example of the EIP-712 specification
+		let json = r#"{
+			"types": {
+				"EIP712Domain": [
+					{"name": "name", "type": "string"},
+					{"name": "version", "type": "string"},
+					{"name": "chainId", "type": "uint256"},
+					{"name": "verifyingContract", "type": "address"}
+				],
+				"Person": [
+					{"name": "name", "type": "string"},
+					{"name": "wallet", "type": "address"}
+				],
+				"Mail": [
+					{"name": "from", "type": "Person"},
+					{"name": "to", "type": "Person"},
+					{"name": "contents", "type": "string"}
+				]
+			},
+			"primaryType": "Mail",
+			"domain": {
+				"name": "Ether Mail",
+				"version": "1",
+				"chainId": 1,
+				"verifyingContract": "0xCcCCccccCCCCcCCCCCCcCcCccCcCCCcCcccccccC"
+			},
+			"message": {
+				"from": {"name": "Cow", "wallet": "0xCD2a3d9F938E13CD947Ec05AbC7FE734Df8DD826"},
+				"to": {"name": "Bob", "wallet": "0xbBbBBBBbbBBBbbbBbbBbbbbBBbBbbbbBbBbbBBbB"},
+				"contents": "Hello, Bob!"
+			}
+		}"#;
+		let typed_data = TypedData::load(json.as_bytes()).unwrap();
+		assert_eq!(
+			typed_data.encode_type("Mail").unwrap(),
+			"Mail(Person from,Person to,string contents)Person(string name,address wallet)"
+		);
+		assert_eq!(
+			typed_data.type_hash("Mail").unwrap(),
+			hash("a0cedeb2dc280ba39b857546d74f5549c3a1d7bdc2dd96bf881f76108e23dac2")
+		);
+		assert_eq!(
+			typed_data.hash_struct("Mail", &typed_data.message).unwrap(),
+			hash("c52c0ee5d84264471806290a3f2c4cecfc5490626bf912d01f240d7a274b371e")
+		);
+		assert_eq!(
+			typed_data.domain_separator().unwrap(),
+			hash("f2cee375fa42b42143804025fc449deafd50cc031ca257e0b194a650a912090f")
+		);
+		assert_eq!(typed_data.digest().unwrap(), hash("be609aee343fb3c4b28e1df9e632fca64fcfaede20f02e86244efddf30957bd2"));
+
+		// the domain type defaults to the members the domain sets
+		let mut value: Value = serde_json::from_str(json).unwrap();
+		value["types"].as_object_mut().unwrap().remove("EIP712Domain");
+		let implicit = TypedData::load(value.to_string().as_bytes()).unwrap();
+		assert_eq!(implicit.digest().unwrap(), typed_data.digest().unwrap());
+
+		assert!(typed_data.hash_struct("Mail", &serde_json::json!({"contents": "Hello, Bob!"})).is_err());
+		assert!(typed_data.encode_type("Letter").is_err());
+	}
+}
@@ -9,7 +9,7 @@
 //! ABI encoder.
 
 use crate::ethabi::util::pad_u32;
-use crate::ethabi::{Bytes, Token, Word};
+use crate::ethabi::{Bytes, Error, Result, Token, Word};
 
 fn pad_bytes(bytes: &[u8]) -> Vec<Word> {
 	let mut result = vec![pad_u32(bytes.len() as u32)];
@@ -128,6 +128,35 @@ pub fn encode(tokens: &[Token]) -> Bytes {
 	encode_head_tail(mediates).iter().flat_map(|word| word.to_vec()).collect()
 }
 
+/// Encodes vector of tokens like Solidity `abi.encodePacked`: static values
+/// take their minimal size, bytes and strings are not length prefixed and
+/// array elements are padded to 32 bytes. Tokens do not carry the integer
+/// size, integers are encoded as `uint256` and `int256`; pass the big endian
+/// bytes as `Token::FixedBytes` for narrower types. Tuples, and arrays of
+/// dynamic values, have no packed encoding.
+pub fn encode_packed(tokens: &[Token]) -> Result<Bytes> {
+	let mut result = Vec::new();
+	for token in tokens {
+		match *token {
+			Token::Address(ref address) => result.extend_from_slice(address.as_ref()),
+			Token::Bytes(ref bytes) | Token::FixedBytes(ref bytes) => result.extend_from_slice(bytes),
+			Token::String(ref s) => result.extend_from_slice(s.as_bytes()),
+			Token::Int(int) | Token::Uint(int) => result.extend_from_slice(&<[u8; 32]>::from(int)),
+			Token::Bool(b) => result.push(b as u8),
+			Token::Array(ref tokens) | Token::FixedArray(ref tokens) => {
+				for token in tokens {
+					if token.is_dynamic() || matches!(token, Token::Tuple(_)) {
+						return Err(Error::InvalidData);
+					}
+					result.extend(encode(&[token.clone()]));
+				}
+			}
+			Token::Tuple(_) => return Err(Error::InvalidData),
+		}
+	}
+	Ok(result)
+}
+
 fn encode_token(token: &Token) -> Mediate {
 	match *token {
 		Token::Address(ref address) => {
@@ -172,4 +201,45 @@ fn encode_token(token: &Token) -> Mediate {
 			Mediate::RawTuple(mediates)
 		}
 	}
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::ethabi::{Address, Uint};
+	use hex::FromHex;
+
+	fn bytes(hex: &str) -> Bytes {
+		hex.from_hex().unwrap()
+	}
+
+	#[test]
+	fn test_encode_packed() {
+		// abi.encodePacked(int16(-1), bytes1(0x42), uint16(0x03), string("Hello, world!")), from the Solidity docs
+		let tokens = [
+			Token::FixedBytes(vec![0xff, 0xff]),
+			Token::FixedBytes(vec![0x42]),
+			Token::FixedBytes(vec![0x00, 0x03]),
+			Token::String("Hello, world!".to_owned()),
+		];
+		assert_eq!(encode_packed(&tokens).unwrap(), bytes("ffff42000348656c6c6f2c20776f726c6421"));
+
+		let address = Address::from_low_u64_be(0xaa);
+		let tokens = [Token::Address(address), Token::Bool(true), Token::Uint(Uint::from(1)), Token::Bytes(vec![0x12, 0x34])];
+		let mut expected = address.as_bytes().to_vec();
+		expected.push(1);
+		expected.extend_from_slice(&bytes(&format!("{:064x}", 1)));
+		expected.extend_from_slice(&[0x12, 0x34]);
+		assert_eq!(encode_packed(&tokens).unwrap(), expected);
+
+		// array elements take a full word
+		let tokens = [Token::Array(vec![Token::Address(address), Token::Bool(true)]), Token::FixedArray(vec![Token::Uint(Uint::from(2))])];
+		let expected = format!("{:064x}{:064x}{:064x}", 0xaa, 1, 2);
+		assert_eq!(encode_packed(&tokens).unwrap(), bytes(&expected));
+		assert_eq!(encode_packed(&[Token::Array(vec![])]).unwrap(), Bytes::new());
+
+		assert!(encode_packed(&[Token::Array(vec![Token::String("a".to_owned())])]).is_err());
+		assert!(encode_packed(&[Token::Array(vec![Token::Tuple(vec![Token::Bool(true)])])]).is_err());
+		assert!(encode_packed(&[Token::Tuple(vec![Token::Bool(true)])]).is_err());
+	}
+}
@@ -0,0 +1,106 @@
+// Copyright 2015-2020 Parity Technologies
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Contract custom error.
+
+use serde::Deserialize;
+
+use crate::ethabi::{decode, encode, signature::short_signature, Bytes, Error, Param, ParamType, Result, Token};
+
+/// Custom error a contract reverts with, solc >= 0.8.4.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct AbiError {
+	/// Error name.
+	pub name: String,
+	/// Error input.
+	pub inputs: Vec<Param>,
+}
+
+impl AbiError {
+	/// Returns all input params of given error.
+	fn param_types(&self) -> Vec<ParamType> {
+		self.inputs.iter().map(|p| p.kind.clone()).collect()
+	}
+
+	/// Returns the canonical signature, e.g. `InsufficientBalance(uint256,uint256)`.
+	pub fn signature(&self) -> String {
+		let inputs = self.inputs.iter().map(|p| p.kind.to_string()).collect::<Vec<_>>().join(",");
+		format!("{}({})", self.name, inputs)
+	}
+
+	/// Returns the 4 byte selector the revert data starts with.
+	pub fn selector(&self) -> [u8; 4] {
+		short_signature(&self.name, &self.param_types())
+	}
+
+	/// Prepares revert data with given input params.
+	pub fn encode(&self, tokens: &[Token]) -> Result<Bytes> {
+		if !Token::types_check(tokens, &self.param_types()) {
+			return Err(Error::InvalidData);
+		}
+		Ok(self.selector().iter().cloned().chain(encode(tokens)).collect())
+	}
+
+	/// Parses revert data, which must start with the selector of this error.
+	pub fn decode(&self, data: &[u8]) -> Result<Vec<Token>> {
+		if data.len() < 4 || data[..4] != self.selector() {
+			return Err(Error::InvalidData);
+		}
+		decode(&self.param_types(), &data[4..])
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::ethabi::Uint;
+	use hex::FromHex;
+
+	fn error(json: &str) -> AbiError {
+		serde_json::from_str(json).unwrap()
+	}
+
+	#[test]
+	fn test_selector() {
+		// the errors solc reverts with on its own
+		let reason = error(r#"{"name": "Error", "inputs": [{"name": "", "type": "string"}]}"#);
+		assert_eq!(reason.selector(), [0x08, 0xc3, 0x79, 0xa0]);
+		let panic = error(r#"{"name": "Panic", "inputs": [{"name": "code", "type": "uint256"}]}"#);
+		assert_eq!((panic.signature().as_str(), panic.selector()), ("Panic(uint256)", [0x4e, 0x48, 0x7b, 0x71]));
+	}
+
+	#[test]
+	fn test_decode() {
+		let reason = error(r#"{"name": "Error", "inputs": [{"name": "", "type": "string"}]}"#);
+		// revert("Not enough Ether provided."), as in the Solidity docs
+		let data: Vec<u8> = concat!(
+			"08c379a0",
+			"0000000000000000000000000000000000000000000000000000000000000020",
+			"000000000000000000000000000000000000000000000000000000000000001a",
+			"4e6f7420656e6f7567682045746865722070726f76696465642e000000000000",
+		)
+		.from_hex()
+		.unwrap();
+		let tokens = vec![Token::String("Not enough Ether provided.".to_owned())];
+		assert_eq!(reason.decode(&data).unwrap(), tokens);
+		assert_eq!(reason.encode(&tokens).unwrap(), data);
+
+		let balance = error(r#"{"name": "InsufficientBalance", "inputs": [
+			{"name": "available", "type": "uint256"}, {"name": "required", "type": "uint256"}]}"#);
+		let tokens = vec![Token::Uint(Uint::from(1)), Token::Uint(Uint::from(2))];
+		let data = balance.encode(&tokens).unwrap();
+		assert_eq!((data.len(), &data[..4]), (68, &balance.selector()[..]));
+		assert_eq!(balance.decode(&data).unwrap(), tokens);
+
+		// other selectors, short data and mistyped tokens
+		assert!(reason.decode(&data).is_err());
+		assert!(balance.decode(&data[..3]).is_err());
+		assert!(balance.decode(&data[..36]).is_err());
+		assert!(balance.encode(&[Token::Bool(true)]).is_err());
+	}
+}
@@ -8,6 +8,7 @@
 
 //! Event param specification.
 
+use crate::ethabi::tuple_param::with_components;
 use crate::ethabi::{ParamType, TupleParam};
 use serde::de::{Error, MapAccess, Visitor};
 use serde::{Deserialize, Deserializer};
@@ -82,14 +83,8 @@ impl<'a> Visitor<'a> for EventParamVisitor {
 			}
 		}
 		let name = name.ok_or_else(|| Error::missing_field("name"))?;
-		let kind = kind.ok_or_else(|| Error::missing_field("kind")).and_then(|param_type| {
-			if let ParamType::Tuple(_) = param_type {
-				let tuple_params = components.ok_or_else(|| Error::missing_field("components"))?;
-				Ok(ParamType::Tuple(tuple_params.into_iter().map(|param| param.kind).map(Box::new).collect()))
-			} else {
-				Ok(param_type)
-			}
-		})?;
+		let kind = kind.ok_or_else(|| Error::missing_field("kind"))?;
+		let kind = with_components(kind, &components).map_err(Error::missing_field)?;
 		let indexed = indexed.unwrap_or(false);
 		Ok(EventParam { name, kind, indexed })
 	}
@@ -11,7 +11,7 @@
 use std::string::ToString;
 
 use crate::ethabi::signature::short_signature;
-use crate::ethabi::{decode, encode, Bytes, Error, Param, ParamType, Result, Token};
+use crate::ethabi::{decode, encode, Bytes, Error, Param, ParamType, Result, StateMutability, Token};
 use serde::Deserialize;
 
 /// Contract function specification.
@@ -23,9 +23,12 @@ pub struct Function {
 	pub inputs: Vec<Param>,
 	/// Function output.
 	pub outputs: Vec<Param>,
-	/// Constant function.
+	/// Constant function, kept for ABIs older than `stateMutability`.
 	#[serde(default)]
 	pub constant: bool,
+	/// Function state mutability.
+	#[serde(rename = "stateMutability", default)]
+	pub state_mutability: StateMutability,
 }
 
 impl Function {
@@ -0,0 +1,420 @@
+// Copyright 2015-2020 Parity Technologies
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Human-readable ABI, one Solidity-like declaration per fragment:
+//!
+//! ```text
+//! constructor(string symbol)
+//! function balanceOf(address owner) view returns (uint256)
+//! event Transfer(address indexed from, address indexed to, uint256 value)
+//! error InsufficientBalance(uint256 available, uint256 required)
+//! ```
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::ethabi::operation::Operation;
+use crate::ethabi::param_type::{Reader, Writer};
+use crate::ethabi::{AbiError, Constructor, Error, Event, EventParam, Function, Param, ParamType, Result, StateMutability, TupleParam};
+
+/// Position of the parenthesis closing the one at `open`.
+fn closing_paren(s: &str, open: usize) -> Result<usize> {
+	let mut nested = 0;
+	for (pos, c) in s.char_indices().skip_while(|&(pos, _)| pos < open) {
+		match c {
+			'(' => nested += 1,
+			')' => {
+				nested -= 1;
+				if nested == 0 {
+					return Ok(pos);
+				}
+			}
+			_ => (),
+		}
+	}
+	Err(Error::InvalidName(s.to_owned()))
+}
+
+/// Splits `s` at the commas outside of parentheses.
+fn split_params(s: &str) -> Vec<&str> {
+	if s.trim().is_empty() {
+		return Vec::new();
+	}
+	let mut params = Vec::new();
+	let (mut nested, mut start) = (0, 0);
+	for (pos, c) in s.char_indices() {
+		match c {
+			'(' => nested += 1,
+			')' => nested -= 1,
+			',' if nested == 0 => {
+				params.push(s[start..pos].trim());
+				start = pos + 1;
+			}
+			_ => (),
+		}
+	}
+	params.push(s[start..].trim());
+	params
+}
+
+/// Parses `type [indexed] [location] [name]`, returning the type, tuple components, name and indexed flag.
+fn parse_param(s: &str) -> Result<(ParamType, Vec<TupleParam>, String, bool)> {
+	let s = s.trim();
+	let (mut kind, components, rest) = if s.starts_with('(') || s.starts_with("tuple(") {
+		let open = s.find('(').unwrap_or(0);
+		let close = closing_paren(s, open)?;
+		let components = split_params(&s[open + 1..close])
+			.into_iter()
+			.map(|param| {
+				parse_param(param).map(|(kind, components, name, _)| TupleParam {
+					name: if name.is_empty() { None } else { Some(name) },
+					kind,
+					components,
+					internal_type: None,
+				})
+			})
+			.collect::<Result<Vec<_>>>()?;
+		let kind = ParamType::Tuple(components.iter().map(|param| Box::new(param.kind.clone())).collect());
+		(kind, components, &s[close + 1..])
+	} else {
+		let end = s.find(char::is_whitespace).unwrap_or_else(|| s.len());
+		(Reader::read(&s[..end])?, Vec::new(), &s[end..])
+	};
+
+	// array suffixes of a tuple
+	let mut rest = rest.trim_start();
+	while rest.starts_with('[') {
+		let close = rest.find(']').ok_or_else(|| Error::InvalidName(s.to_owned()))?;
+		kind = match rest[1..close].trim() {
+			"" => ParamType::Array(Box::new(kind)),
+			len => ParamType::FixedArray(Box::new(kind), len.parse()?),
+		};
+		rest = rest[close + 1..].trim_start();
+	}
+
+	let mut name = String::new();
+	let mut indexed = false;
+	for word in rest.split_whitespace() {
+		match word {
+			"indexed" => indexed = true,
+			"memory" | "calldata" | "storage" => (),
+			word if name.is_empty() && word.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$') => {
+				name = word.to_owned()
+			}
+			_ => return Err(Error::InvalidName(s.to_owned())),
+		}
+	}
+	Ok((kind, components, name, indexed))
+}
+
+fn parse_params(s: &str) -> Result<Vec<Param>> {
+	split_params(s)
+		.into_iter()
+		.map(|param| {
+			parse_param(param).map(|(kind, components, name, _)| Param { name, kind, components, internal_type: None })
+		})
+		.collect()
+}
+
+fn write_params(params: &[Param]) -> String {
+	params
+		.iter()
+		.map(|param| match param.name.as_str() {
+			"" => Writer::write(&param.kind),
+			name => format!("{} {}", Writer::write(&param.kind), name),
+		})
+		.collect::<Vec<_>>()
+		.join(", ")
+}
+
+impl FromStr for Operation {
+	type Err = Error;
+
+	fn from_str(fragment: &str) -> Result<Self> {
+		let fragment = fragment.trim().trim_end_matches(';');
+		let (keyword, rest) = match fragment.find(|c: char| c == '(' || c.is_whitespace()) {
+			Some(pos) => (fragment[..pos].trim(), &fragment[pos..]),
+			None => return Err(Error::InvalidName(fragment.to_owned())),
+		};
+		// a bare signature is a function
+		let (keyword, rest) = match keyword {
+			"function" | "event" | "error" | "constructor" | "fallback" | "receive" => (keyword, rest.trim_start()),
+			_ => ("function", fragment),
+		};
+
+		let open = rest.find('(').ok_or_else(|| Error::InvalidName(fragment.to_owned()))?;
+		let close = closing_paren(rest, open)?;
+		let name = rest[..open].trim().to_owned();
+		let params = &rest[open + 1..close];
+		let mut modifiers = rest[close + 1..].trim();
+
+		let mut outputs = Vec::new();
+		if let Some(pos) = modifiers.find("returns") {
+			let returns = modifiers[pos + "returns".len()..].trim();
+			if !returns.starts_with('(') || closing_paren(returns, 0)? != returns.len() - 1 {
+				return Err(Error::InvalidName(fragment.to_owned()));
+			}
+			outputs = parse_params(&returns[1..returns.len() - 1])?;
+			modifiers = modifiers[..pos].trim();
+		}
+
+		let mut state_mutability = StateMutability::NonPayable;
+		let mut anonymous = false;
+		for word in modifiers.split_whitespace() {
+			match word {
+				"pure" => state_mutability = StateMutability::Pure,
+				"view" | "constant" => state_mutability = StateMutability::View,
+				"payable" => state_mutability = StateMutability::Payable,
+				"nonpayable" => state_mutability = StateMutability::NonPayable,
+				"anonymous" if keyword == "event" => anonymous = true,
+				"external" | "public" | "virtual" | "override" => (),
+				_ => return Err(Error::InvalidName(fragment.to_owned())),
+			}
+		}
+
+		let operation = match keyword {
+			"function" => Operation::Function(Function {
+				name,
+				inputs: parse_params(params)?,
+				outputs,
+				constant: state_mutability.is_read_only(),
+				state_mutability,
+			}),
+			"event" => {
+				let inputs = split_params(params)
+					.into_iter()
+					.map(|param| parse_param(param).map(|(kind, _, name, indexed)| EventParam { name, kind, indexed }))
+					.collect::<Result<Vec<_>>>()?;
+				Operation::Event(Event { name, inputs, anonymous })
+			}
+			"error" => Operation::Error(AbiError { name, inputs: parse_params(params)? }),
+			"constructor" => Operation::Constructor(Constructor { inputs: parse_params(params)? }),
+			"fallback" => Operation::Fallback,
+			_ => Operation::Receive,
+		};
+		Ok(operation)
+	}
+}
+
+impl fmt::Display for Operation {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Operation::Constructor(constructor) => constructor.fmt(f),
+			Operation::Function(function) => function.fmt(f),
+			Operation::Event(event) => event.fmt(f),
+			Operation::Error(error) => error.fmt(f),
+			Operation::Fallback => write!(f, "fallback() external"),
+			Operation::Receive => write!(f, "receive() external payable"),
+		}
+	}
+}
+
+impl FromStr for Function {
+	type Err = Error;
+
+	fn from_str(fragment: &str) -> Result<Self> {
+		match fragment.parse()? {
+			Operation::Function(function) => Ok(function),
+			_ => Err(Error::InvalidName(fragment.to_owned())),
+		}
+	}
+}
+
+impl fmt::Display for Function {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "function {}({})", self.name, write_params(&self.inputs))?;
+		if self.state_mutability != StateMutability::NonPayable {
+			write!(f, " {}", self.state_mutability)?;
+		}
+		if !self.outputs.is_empty() {
+			write!(f, " returns ({})", write_params(&self.outputs))?;
+		}
+		Ok(())
+	}
+}
+
+impl FromStr for Event {
+	type Err = Error;
+
+	fn from_str(fragment: &str) -> Result<Self> {
+		match fragment.parse()? {
+			Operation::Event(event) => Ok(event),
+			_ => Err(Error::InvalidName(fragment.to_owned())),
+		}
+	}
+}
+
+impl fmt::Display for Event {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let inputs = self
+			.inputs
+			.iter()
+			.map(|param| {
+				let mut input = Writer::write(&param.kind);
+				if param.indexed {
+					input += " indexed";
+				}
+				if !param.name.is_empty() {
+					input = format!("{} {}", input, param.name);
+				}
+				input
+			})
+			.collect::<Vec<_>>();
+		write!(f, "event {}({})", self.name, inputs.join(", "))?;
+		if self.anonymous {
+			write!(f, " anonymous")?;
+		}
+		Ok(())
+	}
+}
+
+impl FromStr for Constructor {
+	type Err = Error;
+
+	fn from_str(fragment: &str) -> Result<Self> {
+		match fragment.parse()? {
+			Operation::Constructor(constructor) => Ok(constructor),
+			_ => Err(Error::InvalidName(fragment.to_owned())),
+		}
+	}
+}
+
+impl fmt::Display for Constructor {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "constructor({})", write_params(&self.inputs))
+	}
+}
+
+impl FromStr for AbiError {
+	type Err = Error;
+
+	fn from_str(fragment: &str) -> Result<Self> {
+		match fragment.parse()? {
+			Operation::Error(error) => Ok(error),
+			_ => Err(Error::InvalidName(fragment.to_owned())),
+		}
+	}
+}
+
+impl fmt::Display for AbiError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "error {}({})", self.name, write_params(&self.inputs))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::ethabi::Contract;
+
+	/// `fragment` parsed, written back and parsed again.
+	fn round_trip<T: FromStr<Err = Error> + fmt::Display + PartialEq + fmt::Debug>(fragment: &str) -> T {
+		let parsed = fragment.parse::<T>().unwrap();
+		assert_eq!(parsed.to_string(), fragment);
+		assert_eq!(parsed.to_string().parse::<T>().unwrap(), parsed);
+		parsed
+	}
+
+	#[test]
+	fn test_function() {
+		let function: Function = round_trip("function balanceOf(address owner) view returns (uint256)");
+		assert_eq!(function.signature(), "balanceOf(address):(uint256)");
+		assert_eq!(function.outputs[0].kind, ParamType::Uint(256));
+		assert!(function.constant);
+		round_trip::<Function>("function transfer(address to, uint256 value) returns (bool)");
+		round_trip::<Function>("function deposit() payable");
+		round_trip::<Function>("function hash(bytes data, uint8[2][] pairs) pure returns (bytes32, string)");
+
+		// bare signatures, modifiers and data locations
+		let function: Function = "transfer(address,uint256)".parse().unwrap();
+		assert_eq!(function.to_string(), "function transfer(address, uint256)");
+		let function: Function = "function name(string calldata s) external view virtual returns (string memory);".parse().unwrap();
+		assert_eq!(function.to_string(), "function name(string s) view returns (string)");
+		assert_eq!(function.state_mutability, StateMutability::View);
+		assert_eq!("function f() constant".parse::<Function>().unwrap().state_mutability, StateMutability::View);
+	}
+
+	#[test]
+	fn test_event() {
+		let event: Event = round_trip("event Transfer(address indexed from, address indexed to, uint256 value)");
+		assert_eq!(event.inputs.iter().map(|param| param.indexed).collect::<Vec<_>>(), [true, true, false]);
+		assert!(!event.anonymous);
+		let event: Event = round_trip("event Log(string indexed, bytes) anonymous");
+		assert!(event.anonymous && event.inputs[0].indexed && event.inputs[0].name.is_empty());
+	}
+
+	#[test]
+	fn test_error() {
+		let error: AbiError = round_trip("error InsufficientBalance(uint256 available, uint256 required)");
+		assert_eq!(error.inputs.len(), 2);
+		round_trip::<AbiError>("error Unauthorized()");
+		round_trip::<Constructor>("constructor(string symbol)");
+	}
+
+	#[test]
+	fn test_tuple() {
+		let function: Function = "function place((uint256 price, address[] owners) order, (bool, bytes32)[2] flags)".parse().unwrap();
+		let order = ParamType::Tuple(vec![Box::new(ParamType::Uint(256)), Box::new(ParamType::Array(Box::new(ParamType::Address)))]);
+		assert_eq!(function.inputs[0].kind, order);
+		let names = function.inputs[0].components.iter().map(|param| param.name.clone().unwrap()).collect::<Vec<_>>();
+		assert_eq!(names, ["price", "owners"]);
+		let flags = ParamType::Tuple(vec![Box::new(ParamType::Bool), Box::new(ParamType::FixedBytes(32))]);
+		assert_eq!(function.inputs[1].kind, ParamType::FixedArray(Box::new(flags), 2));
+		assert_eq!(function.inputs[1].components[0].name, None);
+		assert_eq!(function.signature(), "place((uint256,address[]),(bool,bytes32)[2])");
+		// components are written by type only
+		let written = "function place((uint256,address[]) order, (bool,bytes32)[2] flags)";
+		assert_eq!(function.to_string(), written);
+		assert_eq!(written.parse::<Function>().unwrap().signature(), function.signature());
+		assert_eq!("function f(tuple(uint256, bool) t)".parse::<Function>().unwrap().signature(), "f((uint256,bool))");
+	}
+
+	#[test]
+	fn test_contract() {
+		let abi = "
+			// token
+			constructor(string symbol)
+			function balanceOf(address owner) view returns (uint256)
+			event Transfer(address indexed from, address indexed to, uint256 value)
+			error Unauthorized()
+			fallback()
+			receive() external payable
+		";
+		let contract = Contract::from_human_readable(abi).unwrap();
+		assert!(contract.constructor.is_some() && contract.fallback && contract.receive);
+		assert_eq!(Contract::from_human_readable(&contract.to_human_readable()).unwrap(), contract);
+		let json = r#"["function balanceOf(address owner) view returns (uint256)", {"type": "error", "name": "Unauthorized", "inputs": []}]"#;
+		let contract = Contract::load(json.as_bytes()).unwrap();
+		assert_eq!((contract.functions.len(), contract.errors.len()), (1, 1));
+	}
+
+	#[test]
+	fn test_malformed() {
+		for fragment in &[
+			"",
+			"function",
+			"function f(",
+			"function f(uint256",
+			"function f(uint256[x])",
+			"function f(address payable to)",
+			"function f() returns uint256",
+			"function f() returns (uint256",
+			"function f() returns (uint256) view",
+			"function f() internal",
+			"function f(uint256 a b)",
+			"function f((uint256, bool) t[)",
+			"error E() anonymous",
+		] {
+			assert!(fragment.parse::<Operation>().is_err(), "{}", fragment);
+		}
+		assert!("event E()".parse::<Function>().is_err());
+		assert!("function f()".parse::<Event>().is_err());
+		assert!("function f()".parse::<AbiError>().is_err());
+		assert!(Contract::from_human_readable("function f()\nfunction g(").is_err());
+	}
+}
@@ -13,17 +13,21 @@
 mod constructor;
 mod contract;
 mod decoder;
+pub mod eip712;
 mod encoder;
+mod error;
 mod errors;
 mod event;
 mod event_param;
 mod filter;
 mod function;
+mod human_readable;
 mod log;
 mod operation;
 mod param;
 pub mod param_type;
 mod signature;
+mod state_mutability;
 pub mod token;
 mod tuple_param;
 mod util;
@@ -32,7 +36,8 @@ pub use crate::ethabi::{
 	constructor::Constructor,
 	contract::{Contract, Events, Functions},
 	decoder::decode,
-	encoder::encode,
+	encoder::{encode, encode_packed},
+	error::AbiError,
 	errors::{Error, Result},
 	event::Event,
 	event_param::EventParam,
@@ -41,6 +46,7 @@ pub use crate::ethabi::{
 	log::{Log, LogFilter, LogParam, ParseLog, RawLog},
 	param::Param,
 	param_type::ParamType,
+	state_mutability::StateMutability,
 	token::Token,
 	tuple_param::TupleParam,
 };
@@ -8,7 +8,7 @@
 
 //! Operation type.
 
-use crate::ethabi::{Constructor, Event, Function};
+use crate::ethabi::{AbiError, Constructor, Event, Function, StateMutability};
 use serde::de::Error as SerdeError;
 use serde::{Deserialize, Deserializer};
 use serde_json::value::from_value;
@@ -23,8 +23,12 @@ pub enum Operation {
 	Function(Function),
 	/// Contract event.
 	Event(Event),
-	/// Fallback, ignored.
+	/// Contract custom error.
+	Error(AbiError),
+	/// Fallback function.
 	Fallback,
+	/// Receive function, called with empty calldata.
+	Receive,
 }
 
 impl<'a> Deserialize<'a> for Operation {
@@ -33,6 +37,10 @@ impl<'a> Deserialize<'a> for Operation {
 		D: Deserializer<'a>,
 	{
 		let v: Value = Deserialize::deserialize(deserializer)?;
+		// human-readable fragment
+		if let Value::String(fragment) = &v {
+			return fragment.parse().map_err(|e: crate::ethabi::Error| D::Error::custom(e.to_string()));
+		}
 		let map = v.as_object().ok_or_else(|| SerdeError::custom("Invalid operation"))?;
 		let s = map.get("type").and_then(Value::as_str).ok_or_else(|| SerdeError::custom("Invalid operation type"))?;
 
@@ -44,19 +52,103 @@ impl<'a> Deserialize<'a> for Operation {
 			}
 		}
 
+		// ABIs before solc 0.5 only have the `constant` and `payable` flags
+		let has_mutability = map.contains_key("stateMutability");
+		let payable = map.get("payable").and_then(Value::as_bool).unwrap_or(false);
+
 		let result = match s {
 			"constructor" => from_value(v).map(Operation::Constructor),
 			"function" => from_value(v).map(|mut f: Function| {
 				sanitize_name(&mut f.name);
+				if !has_mutability {
+					f.state_mutability = match (f.constant, payable) {
+						(true, _) => StateMutability::View,
+						(false, true) => StateMutability::Payable,
+						(false, false) => StateMutability::NonPayable,
+					};
+				}
+				f.constant = f.state_mutability.is_read_only();
 				Operation::Function(f)
 			}),
 			"event" => from_value(v).map(|mut e: Event| {
 				sanitize_name(&mut e.name);
 				Operation::Event(e)
 			}),
+			"error" => from_value(v).map(|mut e: AbiError| {
+				sanitize_name(&mut e.name);
+				Operation::Error(e)
+			}),
 			"fallback" => Ok(Operation::Fallback),
+			"receive" => Ok(Operation::Receive),
 			_ => Err(SerdeError::custom("Invalid operation type.")),
 		};
 		result.map_err(|e| D::Error::custom(e.to_string()))
 	}
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::ethabi::ParamType;
+
+	fn function(json: &str) -> Function {
+		match serde_json::from_str(json).unwrap() {
+			Operation::Function(function) => function,
+			operation => panic!("expected a function, got {:?}", operation),
+		}
+	}
+
+	#[test]
+	fn test_legacy_mutability() {
+		let legacy = |flags: &str| function(&format!(r#"{{"type": "function", "name": "f", "inputs": [], "outputs": [], {}}}"#, flags));
+		assert_eq!(legacy(r#""constant": true"#).state_mutability, StateMutability::View);
+		assert_eq!(legacy(r#""constant": true, "payable": true"#).state_mutability, StateMutability::View);
+		assert_eq!(legacy(r#""constant": false, "payable": true"#).state_mutability, StateMutability::Payable);
+		assert_eq!(legacy(r#""constant": false, "payable": false"#).state_mutability, StateMutability::NonPayable);
+		assert_eq!(legacy(r#""payable": false"#).state_mutability, StateMutability::NonPayable);
+	}
+
+	#[test]
+	fn test_modern_mutability() {
+		let modern = |mutability: &str| {
+			function(&format!(
+				r#"{{"type": "function", "name": "f", "inputs": [], "outputs": [], "stateMutability": "{}"}}"#,
+				mutability
+			))
+		};
+		for (name, mutability, constant) in &[
+			("pure", StateMutability::Pure, true),
+			("view", StateMutability::View, true),
+			("nonpayable", StateMutability::NonPayable, false),
+			("payable", StateMutability::Payable, false),
+		] {
+			let function = modern(name);
+			assert_eq!((function.state_mutability, function.constant), (*mutability, *constant));
+		}
+		// stateMutability wins over the legacy flags solc 0.5 still writes
+		let function = function(
+			r#"{"type": "function", "name": "f", "inputs": [], "outputs": [], "constant": false, "payable": false, "stateMutability": "view"}"#,
+		);
+		assert_eq!((function.state_mutability, function.constant), (StateMutability::View, true));
+		assert!(serde_json::from_str::<Operation>(r#"{"type": "function", "name": "f", "inputs": [], "outputs": [], "stateMutability": "constant"}"#).is_err());
+	}
+
+	#[test]
+	fn test_operations() {
+		let error = r#"{"type": "error", "name": "InsufficientBalance", "inputs": [
+			{"name": "available", "type": "uint256", "internalType": "uint256"},
+			{"name": "required", "type": "uint256", "internalType": "uint256"}]}"#;
+		match serde_json::from_str(error).unwrap() {
+			Operation::Error(error) => {
+				assert_eq!(error.signature(), "InsufficientBalance(uint256,uint256)");
+				assert_eq!(error.inputs[1].kind, ParamType::Uint(256));
+			}
+			operation => panic!("expected an error, got {:?}", operation),
+		}
+		assert_eq!(serde_json::from_str::<Operation>(r#"{"type": "fallback", "stateMutability": "payable"}"#).unwrap(), Operation::Fallback);
+		assert_eq!(serde_json::from_str::<Operation>(r#"{"type": "receive", "stateMutability": "payable"}"#).unwrap(), Operation::Receive);
+		assert_eq!(function(r#""function f(uint256)""#).inputs[0].kind, ParamType::Uint(256));
+		assert!(serde_json::from_str::<Operation>(r#"{"type": "modifier", "name": "onlyOwner"}"#).is_err());
+		assert!(serde_json::from_str::<Operation>(r#"{"name": "f"}"#).is_err());
+	}
+}
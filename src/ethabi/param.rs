@@ -12,6 +12,7 @@ use serde::de::{Error, MapAccess, Visitor};
 use serde::{Deserialize, Deserializer};
 use std::fmt;
 
+use crate::ethabi::tuple_param::with_components;
 use crate::ethabi::{ParamType, TupleParam};
 
 /// Function param.
@@ -21,6 +22,10 @@ pub struct Param {
 	pub name: String,
 	/// Param type.
 	pub kind: ParamType,
+	/// Components of a tuple type, or of the tuples of an array type.
+	pub components: Vec<TupleParam>,
+	/// Source type, e.g. `struct Pool.Order[]`.
+	pub internal_type: Option<String>,
 }
 
 impl<'a> Deserialize<'a> for Param {
@@ -48,6 +53,7 @@ impl<'a> Visitor<'a> for ParamVisitor {
 		let mut name = None;
 		let mut kind = None;
 		let mut components = None;
+		let mut internal_type = None;
 
 		while let Some(ref key) = map.next_key::<String>()? {
 			match key.as_ref() {
@@ -70,40 +76,20 @@ impl<'a> Visitor<'a> for ParamVisitor {
 					let component: Vec<TupleParam> = map.next_value()?;
 					components = Some(component)
 				}
+				"internalType" => {
+					if internal_type.is_some() {
+						return Err(Error::duplicate_field("internalType"));
+					}
+					internal_type = Some(map.next_value()?);
+				}
 				_ => {}
 			}
 		}
 		let name = name.ok_or_else(|| Error::missing_field("name"))?;
-		let kind =
-			kind.ok_or_else(|| Error::missing_field("kind")).and_then(|param_type: ParamType| match param_type {
-				ParamType::Tuple(_) => {
-					let tuple_params = components.ok_or_else(|| Error::missing_field("components"))?;
-					Ok(ParamType::Tuple(tuple_params.into_iter().map(|param| param.kind).map(Box::new).collect()))
-				}
-				ParamType::Array(inner_param_type) => match *inner_param_type {
-					ParamType::Tuple(_) => {
-						let tuple_params = components.ok_or_else(|| Error::missing_field("components"))?;
-						Ok(ParamType::Array(Box::new(ParamType::Tuple(
-							tuple_params.into_iter().map(|param| param.kind).map(Box::new).collect(),
-						))))
-					}
-					_ => Ok(ParamType::Array(inner_param_type)),
-				},
-				ParamType::FixedArray(inner_param_type, size) => match *inner_param_type {
-					ParamType::Tuple(_) => {
-						let tuple_params = components.ok_or_else(|| Error::missing_field("components"))?;
-						Ok(ParamType::FixedArray(
-							Box::new(ParamType::Tuple(
-								tuple_params.into_iter().map(|param| param.kind).map(Box::new).collect(),
-							)),
-							size,
-						))
-					}
-					_ => Ok(ParamType::FixedArray(inner_param_type, size)),
-				},
-				_ => Ok(param_type),
-			})?;
-		Ok(Param { name, kind })
+		let kind = kind.ok_or_else(|| Error::missing_field("kind"))?;
+		let kind = with_components(kind, &components).map_err(Error::missing_field)?;
+		let components = components.unwrap_or_default();
+		Ok(Param { name, kind, components, internal_type })
 	}
 }
 
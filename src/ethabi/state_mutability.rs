@@ -0,0 +1,73 @@
+// Copyright 2015-2020 Parity Technologies
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Function state mutability.
+
+use std::fmt;
+
+use serde::Deserialize;
+
+/// Whether a function reads or modifies state and accepts value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StateMutability {
+	/// Does not read state.
+	Pure,
+	/// Reads but does not modify state.
+	View,
+	/// Modifies state, rejects value.
+	NonPayable,
+	/// Modifies state and accepts value.
+	Payable,
+}
+
+impl Default for StateMutability {
+	fn default() -> Self {
+		StateMutability::NonPayable
+	}
+}
+
+impl StateMutability {
+	/// Returns true if the function does not modify state.
+	pub fn is_read_only(&self) -> bool {
+		match self {
+			StateMutability::Pure | StateMutability::View => true,
+			StateMutability::NonPayable | StateMutability::Payable => false,
+		}
+	}
+}
+
+impl fmt::Display for StateMutability {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let name = match self {
+			StateMutability::Pure => "pure",
+			StateMutability::View => "view",
+			StateMutability::NonPayable => "nonpayable",
+			StateMutability::Payable => "payable",
+		};
+		f.write_str(name)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_state_mutability() {
+		for name in &["pure", "view", "nonpayable", "payable"] {
+			let mutability: StateMutability = serde_json::from_str(&format!("\"{}\"", name)).unwrap();
+			assert_eq!(mutability.to_string(), *name);
+		}
+		assert!(serde_json::from_str::<StateMutability>("\"constant\"").is_err());
+		assert!(serde_json::from_str::<StateMutability>("\"View\"").is_err());
+		assert!(StateMutability::Pure.is_read_only() && StateMutability::View.is_read_only());
+		assert!(!StateMutability::NonPayable.is_read_only() && !StateMutability::Payable.is_read_only());
+		assert_eq!(StateMutability::default(), StateMutability::NonPayable);
+	}
+}
@@ -0,0 +1,298 @@
+// Copyright 2015-2020 Parity Technologies
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! JSON representation of tokens.
+//!
+//! Integers are written as decimal strings, signed integers as `int256`.
+//! Addresses and bytes are `0x` prefixed hex strings, arrays and tuples JSON
+//! arrays. Tuples whose components are all named are objects keyed by
+//! component name when written along with their params, and are read in
+//! either form. When parsed against their type, integers may also be JSON
+//! numbers or `0x` prefixed hex strings.
+
+use std::str::FromStr;
+
+use hex::{FromHex, ToHex};
+use serde::de::{DeserializeSeed, Deserializer};
+use serde::ser::{Serialize, SerializeSeq, Serializer};
+use serde_json::{Map, Value};
+
+use crate::ethabi::{Error, Param, ParamType, Result, Token, TupleParam, Uint};
+
+impl Serialize for Token {
+	fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+	where
+		S: Serializer,
+	{
+		match self {
+			Token::Address(address) => serializer.serialize_str(&format!("0x{}", address.as_bytes().to_hex::<String>())),
+			Token::Bytes(bytes) | Token::FixedBytes(bytes) => {
+				serializer.serialize_str(&format!("0x{}", bytes.to_hex::<String>()))
+			}
+			Token::Int(int) if int.bit(255) => {
+				serializer.serialize_str(&format!("-{}", (!*int).overflowing_add(Uint::one()).0))
+			}
+			Token::Int(int) | Token::Uint(int) => serializer.serialize_str(&int.to_string()),
+			Token::Bool(b) => serializer.serialize_bool(*b),
+			Token::String(s) => serializer.serialize_str(s),
+			Token::Array(tokens) | Token::FixedArray(tokens) | Token::Tuple(tokens) => {
+				let mut seq = serializer.serialize_seq(Some(tokens.len()))?;
+				for token in tokens {
+					seq.serialize_element(token)?;
+				}
+				seq.end()
+			}
+		}
+	}
+}
+
+/// Deserializes a token of the given type.
+pub struct TokenSeed<'a>(pub &'a ParamType);
+
+impl<'a, 'de> DeserializeSeed<'de> for TokenSeed<'a> {
+	type Value = Token;
+
+	fn deserialize<D>(self, deserializer: D) -> std::result::Result<Token, D::Error>
+	where
+		D: Deserializer<'de>,
+	{
+		use serde::de::Error as SerdeError;
+		use serde::Deserialize;
+
+		let value = Value::deserialize(deserializer)?;
+		Token::from_json(self.0, &value).map_err(|e| D::Error::custom(e.to_string()))
+	}
+}
+
+/// Parses an unsigned integer of `bits` bits.
+fn parse_uint(value: &Value, bits: usize) -> Result<Uint> {
+	let uint = match value {
+		Value::Number(n) => n.as_u64().map(Uint::from).ok_or(Error::InvalidData)?,
+		Value::String(s) if s.starts_with("0x") => Uint::from_str(&s[2..]).map_err(|_| Error::InvalidData)?,
+		Value::String(s) => Uint::from_dec_str(s)?,
+		_ => return Err(Error::InvalidData),
+	};
+	match bits < 256 && uint >> bits != Uint::zero() {
+		true => Err(Error::InvalidData),
+		false => Ok(uint),
+	}
+}
+
+/// Parses a signed integer of `bits` bits into its two's complement.
+fn parse_int(value: &Value, bits: usize) -> Result<Uint> {
+	let negative = match value {
+		Value::Number(n) => n.is_i64() && n.as_i64() < Some(0),
+		Value::String(s) => s.starts_with('-'),
+		_ => false,
+	};
+	if !negative {
+		return parse_uint(value, bits - 1);
+	}
+	let abs = match value {
+		Value::Number(n) => n.as_i64().map(|n| Uint::from(n.wrapping_neg() as u64)).ok_or(Error::InvalidData)?,
+		Value::String(s) => parse_uint(&Value::String(s[1..].to_owned()), 256)?,
+		_ => return Err(Error::InvalidData),
+	};
+	if abs > Uint::one() << (bits - 1) {
+		return Err(Error::InvalidData);
+	}
+	Ok((!abs).overflowing_add(Uint::one()).0)
+}
+
+fn parse_hex(value: &Value) -> Result<Vec<u8>> {
+	match value.as_str() {
+		Some(s) if s.starts_with("0x") => s[2..].from_hex().map_err(From::from),
+		_ => Err(Error::InvalidData),
+	}
+}
+
+fn parse_array(kind: &ParamType, components: &[TupleParam], value: &Value) -> Result<Vec<Token>> {
+	value
+		.as_array()
+		.ok_or(Error::InvalidData)?
+		.iter()
+		.map(|value| Token::from_json_with_components(kind, components, value))
+		.collect()
+}
+
+/// Whether tuples of `components` can be keyed by component name.
+fn is_named(components: &[TupleParam]) -> bool {
+	!components.is_empty() && components.iter().all(|param| param.name.as_deref().map_or(false, |name| !name.is_empty()))
+}
+
+/// Components of the `i`th member of a tuple of `components`.
+fn member_components(components: &[TupleParam], i: usize) -> &[TupleParam] {
+	components.get(i).map_or(&[], |param| &param.components[..])
+}
+
+impl Token {
+	/// Parses a JSON value as a token of the given type, tuples given as arrays.
+	pub fn from_json(kind: &ParamType, value: &Value) -> Result<Token> {
+		Token::from_json_with_components(kind, &[], value)
+	}
+
+	/// Parses a JSON value as a token of the given type. Tuples, also those
+	/// nested in arrays, may be objects keyed by the names of `components`.
+	pub fn from_json_with_components(kind: &ParamType, components: &[TupleParam], value: &Value) -> Result<Token> {
+		let token = match kind {
+			ParamType::Address => {
+				let bytes = parse_hex(value)?;
+				if bytes.len() != 20 {
+					return Err(Error::InvalidData);
+				}
+				Token::Address(crate::ethabi::Address::from_slice(&bytes))
+			}
+			ParamType::Bytes => Token::Bytes(parse_hex(value)?),
+			ParamType::FixedBytes(len) => {
+				let bytes = parse_hex(value)?;
+				if bytes.len() != *len {
+					return Err(Error::InvalidData);
+				}
+				Token::FixedBytes(bytes)
+			}
+			ParamType::Uint(bits) => Token::Uint(parse_uint(value, *bits)?),
+			ParamType::Int(bits) => Token::Int(parse_int(value, *bits)?),
+			ParamType::Bool => Token::Bool(value.as_bool().ok_or(Error::InvalidData)?),
+			ParamType::String => Token::String(value.as_str().ok_or(Error::InvalidData)?.to_owned()),
+			ParamType::Array(kind) => Token::Array(parse_array(kind, components, value)?),
+			ParamType::FixedArray(kind, len) => {
+				let tokens = parse_array(kind, components, value)?;
+				if tokens.len() != *len {
+					return Err(Error::InvalidData);
+				}
+				Token::FixedArray(tokens)
+			}
+			ParamType::Tuple(kinds) => {
+				let values = match value {
+					Value::Array(values) => values.iter().collect::<Vec<_>>(),
+					Value::Object(values) if is_named(components) && components.len() == kinds.len() => {
+						if values.len() != components.len() {
+							return Err(Error::InvalidData);
+						}
+						components
+							.iter()
+							.map(|param| {
+								let name = param.name.as_deref().unwrap_or_default();
+								values.get(name).ok_or_else(|| Error::Other(format!("missing `{}`", name)))
+							})
+							.collect::<Result<Vec<_>>>()?
+					}
+					_ => return Err(Error::InvalidData),
+				};
+				if values.len() != kinds.len() {
+					return Err(Error::InvalidData);
+				}
+				let tokens = kinds
+					.iter()
+					.zip(values)
+					.enumerate()
+					.map(|(i, (kind, value))| Token::from_json_with_components(kind, member_components(components, i), value))
+					.collect::<Result<_>>()?;
+				Token::Tuple(tokens)
+			}
+		};
+		Ok(token)
+	}
+
+	/// Parses values of `params`, given either as a JSON array or as an object by param name.
+	pub fn from_json_params(params: &[Param], value: &Value) -> Result<Vec<Token>> {
+		match value {
+			Value::Array(values) if values.len() == params.len() => {
+				params
+					.iter()
+					.zip(values)
+					.map(|(param, value)| Token::from_json_with_components(&param.kind, &param.components, value))
+					.collect()
+			}
+			Value::Object(values) if values.len() == params.len() => params
+				.iter()
+				.map(|param| {
+					let value = values.get(&param.name).ok_or_else(|| Error::Other(format!("missing `{}`", param.name)))?;
+					Token::from_json_with_components(&param.kind, &param.components, value)
+				})
+				.collect(),
+			_ => Err(Error::InvalidData),
+		}
+	}
+
+	/// Writes the token as JSON, tuples of named `components` as objects keyed by component name.
+	pub fn to_json(&self, components: &[TupleParam]) -> Value {
+		match self {
+			Token::Tuple(tokens) if is_named(components) && components.len() == tokens.len() => {
+				let mut values = Map::new();
+				for (param, token) in components.iter().zip(tokens) {
+					values.insert(param.name.clone().unwrap_or_default(), token.to_json(&param.components));
+				}
+				Value::Object(values)
+			}
+			Token::Tuple(tokens) => {
+				Value::Array(tokens.iter().enumerate().map(|(i, token)| token.to_json(member_components(components, i))).collect())
+			}
+			Token::Array(tokens) | Token::FixedArray(tokens) => {
+				Value::Array(tokens.iter().map(|token| token.to_json(components)).collect())
+			}
+			token => serde_json::to_value(token).unwrap_or(Value::Null),
+		}
+	}
+
+	/// Writes values of `params` as a JSON object by param name, or as an array if some param is unnamed.
+	pub fn to_json_params(params: &[Param], tokens: &[Token]) -> Value {
+		if params.iter().any(|param| param.name.is_empty()) {
+			return Value::Array(params.iter().zip(tokens).map(|(param, token)| token.to_json(&param.components)).collect());
+		}
+		let mut values = Map::new();
+		for (param, token) in params.iter().zip(tokens) {
+			values.insert(param.name.clone(), token.to_json(&param.components));
+		}
+		Value::Object(values)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::ethabi::{Address, Function};
+	use serde_json::json;
+
+	#[test]
+	fn test_tuple() {
+		let function: Function =
+			"function place((uint256 price, (address owner, bool active) maker) order, (uint8, string)[] notes)".parse().unwrap();
+		let owner = Address::from_low_u64_be(0xaa);
+		let tokens = vec![
+			Token::Tuple(vec![Token::Uint(Uint::from(5)), Token::Tuple(vec![Token::Address(owner), Token::Bool(true)])]),
+			Token::Array(vec![Token::Tuple(vec![Token::Uint(Uint::from(1)), Token::String("a".to_owned())])]),
+		];
+		let named = json!({
+			"order": {"price": "5", "maker": {"owner": "0x00000000000000000000000000000000000000aa", "active": true}},
+			"notes": [["1", "a"]],
+		});
+		assert_eq!(Token::to_json_params(&function.inputs, &tokens), named);
+		assert_eq!(Token::from_json_params(&function.inputs, &named).unwrap(), tokens);
+
+		// named tuples are also read as arrays, nested or not
+		let positional = json!([[5, ["0x00000000000000000000000000000000000000aa", true]], [[1, "a"]]]);
+		assert_eq!(Token::from_json_params(&function.inputs, &positional).unwrap(), tokens);
+		let mixed = json!({"order": [5, {"owner": "0x00000000000000000000000000000000000000aa", "active": true}], "notes": [[1, "a"]]});
+		assert_eq!(Token::from_json_params(&function.inputs, &mixed).unwrap(), tokens);
+		// without components, only arrays
+		assert_eq!(serde_json::to_value(&tokens[0]).unwrap(), json!(["5", ["0x00000000000000000000000000000000000000aa", true]]));
+		assert!(Token::from_json(&function.inputs[0].kind, &named["order"]).is_err());
+
+		// unnamed components are only read as arrays
+		assert!(Token::from_json_params(&function.inputs, &json!({"order": named["order"], "notes": [{"0": 1, "1": "a"}]})).is_err());
+		// missing, extra and mistyped members
+		let order = |order: Value| Token::from_json_params(&function.inputs, &json!({"order": order, "notes": []}));
+		assert!(order(json!({"price": "5"})).is_err());
+		assert!(order(json!({"price": "5", "taker": named["order"]["maker"]})).is_err());
+		assert!(order(json!({"price": "5", "maker": named["order"]["maker"], "size": "1"})).is_err());
+		assert!(order(json!({"price": true, "maker": named["order"]["maker"]})).is_err());
+		assert!(order(json!([5])).is_err());
+		assert!(order(json!("5")).is_err());
+	}
+}
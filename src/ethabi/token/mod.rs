@@ -8,10 +8,12 @@
 
 //! ABI param and parsing for it.
 
+mod json;
 mod lenient;
 mod strict;
 mod token;
 
+pub use self::json::TokenSeed;
 pub use self::lenient::LenientTokenizer;
 pub use self::strict::StrictTokenizer;
 pub use self::token::Token;
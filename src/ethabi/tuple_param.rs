@@ -21,6 +21,25 @@ pub struct TupleParam {
 
 	/// Param type.
 	pub kind: ParamType,
+
+	/// Components of a tuple type, or of the tuples of an array type.
+	pub components: Vec<TupleParam>,
+
+	/// Source type, e.g. `struct Pool.Order[]`.
+	pub internal_type: Option<String>,
+}
+
+/// Fills the tuple of `kind`, possibly nested in arrays, with the types of `components`.
+pub(crate) fn with_components(kind: ParamType, components: &Option<Vec<TupleParam>>) -> Result<ParamType, &'static str> {
+	match kind {
+		ParamType::Tuple(_) => {
+			let components = components.as_ref().ok_or("components")?;
+			Ok(ParamType::Tuple(components.iter().map(|param| Box::new(param.kind.clone())).collect()))
+		}
+		ParamType::Array(kind) => Ok(ParamType::Array(Box::new(with_components(*kind, components)?))),
+		ParamType::FixedArray(kind, size) => Ok(ParamType::FixedArray(Box::new(with_components(*kind, components)?), size)),
+		kind => Ok(kind),
+	}
 }
 
 impl<'a> Deserialize<'a> for TupleParam {
@@ -48,6 +67,7 @@ impl<'a> Visitor<'a> for TupleParamVisitor {
 		let mut name = None;
 		let mut kind = None;
 		let mut components = None;
+		let mut internal_type = None;
 
 		while let Some(ref key) = map.next_key::<String>()? {
 			match key.as_ref() {
@@ -70,19 +90,20 @@ impl<'a> Visitor<'a> for TupleParamVisitor {
 					let component: Vec<TupleParam> = map.next_value()?;
 					components = Some(component)
 				}
+				"internalType" => {
+					if internal_type.is_some() {
+						return Err(Error::duplicate_field("internalType"));
+					}
+					internal_type = Some(map.next_value()?);
+				}
 				_ => {}
 			}
 		}
 
-		let kind = kind.ok_or_else(|| Error::missing_field("kind")).and_then(|param_type| {
-			if let ParamType::Tuple(_) = param_type {
-				let tuple_params = components.ok_or_else(|| Error::missing_field("components"))?;
-				Ok(ParamType::Tuple(tuple_params.into_iter().map(|param| param.kind).map(Box::new).collect()))
-			} else {
-				Ok(param_type)
-			}
-		})?;
+		let kind = kind.ok_or_else(|| Error::missing_field("kind"))?;
+		let kind = with_components(kind, &components).map_err(Error::missing_field)?;
+		let components = components.unwrap_or_default();
 
-		Ok(TupleParam { name, kind })
+		Ok(TupleParam { name, kind, components, internal_type })
 	}
 }
@@ -0,0 +1,192 @@
+//! Textual EVM assembly.
+//!
+//! One instruction per line, in the mnemonics of `Instruction`'s `Display`.
+//! `name:` defines a label at the offset of the next instruction, usually a
+//! `JUMPDEST`, and `PUSH @name` pushes that offset. Comments start with `;`
+//! or `//`.
+//!
+//! ```text
+//!     PUSH @end       ; skip the revert
+//!     JUMP
+//!     INVALID
+//! end:
+//!     JUMPDEST
+//! ```
+
+use super::{AsmError, Disassembly, Instruction};
+use std::collections::{BTreeSet, HashMap};
+
+enum Item {
+    Instr(Instruction),
+    /// `PUSH @label`, with the push size if given
+    Label(String, Option<usize>, usize),
+}
+
+fn is_label(s: &str) -> bool {
+    let mut chars = s.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_' || c == '.')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.' || c == '$')
+}
+
+fn strip_comment(line: &str) -> &str {
+    let end = [line.find(';'), line.find("//")].iter().flatten().min().cloned();
+    &line[..end.unwrap_or(line.len())]
+}
+
+/// `PUSH @label` or `PUSHn @label`, as the label and push size
+fn parse_label_push(line: &str, number: usize) -> Result<Option<(String, Option<usize>)>, AsmError> {
+    let mut words = line.split_whitespace();
+    let (mnemonic, operand) = match (words.next(), words.next(), words.next()) {
+        (Some(mnemonic), Some(operand), None) if operand.starts_with('@') => (mnemonic.to_uppercase(), &operand[1..]),
+        _ => return Ok(None),
+    };
+    let size = match mnemonic.strip_prefix("PUSH") {
+        Some("") => None,
+        Some(n) => match n.parse::<usize>() {
+            Ok(n) if (1..=32).contains(&n) => Some(n),
+            _ => return Err(AsmError::InvalidInstruction(number, format!("unknown instruction {}", line))),
+        },
+        None => return Ok(None),
+    };
+    if !is_label(operand) {
+        return Err(AsmError::InvalidLabel(number, operand.to_owned()));
+    }
+    Ok(Some((operand.to_owned(), size)))
+}
+
+/// Bytes needed to push `value`, at least one
+fn push_size(value: usize) -> usize {
+    std::cmp::max(1, (0usize.leading_zeros() - value.leading_zeros() + 7) as usize / 8)
+}
+
+/// Instructions of assembly `text`, with `PUSH @label` resolved to the label offset
+///
+/// Label pushes without a size take the fewest bytes that hold the offset.
+pub fn parse(text: &str) -> Result<Vec<Instruction>, AsmError> {
+    let mut items = Vec::new();
+    // label name to index of the item it precedes
+    let mut labels = HashMap::new();
+    for (i, line) in text.lines().enumerate() {
+        let number = i + 1;
+        let mut line = strip_comment(line).trim();
+        while let Some(colon) = line.find(':') {
+            let label = line[..colon].trim();
+            if !is_label(label) {
+                return Err(AsmError::InvalidLabel(number, label.to_owned()));
+            }
+            if labels.insert(label.to_owned(), items.len()).is_some() {
+                return Err(AsmError::DuplicateLabel(number, label.to_owned()));
+            }
+            line = line[colon + 1..].trim();
+        }
+        if line.is_empty() {
+            continue;
+        }
+        match parse_label_push(line, number)? {
+            Some((label, size)) => items.push(Item::Label(label, size, number)),
+            None => items.push(Item::Instr(line.parse().map_err(|e| AsmError::InvalidInstruction(number, e))?)),
+        }
+    }
+
+    let mut targets = Vec::new();
+    for item in &items {
+        if let Item::Label(label, _, number) = item {
+            targets.push(*labels.get(label).ok_or_else(|| AsmError::UnknownLabel(*number, label.clone()))?);
+        }
+    }
+    // Grow label pushes until every offset fits, offsets only move forward
+    let mut sizes = items.iter()
+        .filter_map(|item| match item {
+            Item::Label(_, size, _) => Some(size.unwrap_or(1)),
+            Item::Instr(_) => None,
+        })
+        .collect::<Vec<_>>();
+    let offsets = loop {
+        let mut offsets = Vec::with_capacity(items.len() + 1);
+        let mut offset = 0;
+        let mut push = 0;
+        for item in &items {
+            offsets.push(offset);
+            offset += match item {
+                Item::Instr(instr) => instr.size(),
+                Item::Label(..) => {
+                    push += 1;
+                    1 + sizes[push - 1]
+                }
+            };
+        }
+        offsets.push(offset);
+
+        let mut grown = false;
+        let pushes = items.iter().filter_map(|item| match item {
+            Item::Label(label, size, number) => Some((label, size, number)),
+            Item::Instr(_) => None,
+        });
+        for ((j, (label, size, number)), target) in pushes.enumerate().zip(&targets) {
+            let needed = push_size(offsets[*target]);
+            if needed > sizes[j] {
+                if size.is_some() {
+                    return Err(AsmError::LabelOutOfRange(*number, label.clone()));
+                }
+                sizes[j] = needed;
+                grown = true;
+            }
+        }
+        if !grown {
+            break offsets;
+        }
+    };
+
+    let mut pushes = targets.iter().zip(&sizes);
+    Ok(items.into_iter()
+        .map(|item| match item {
+            Item::Instr(instr) => instr,
+            Item::Label(..) => {
+                let (target, size) = pushes.next().unwrap();
+                let offset = offsets[*target].to_be_bytes();
+                let mut value = vec![0; size.saturating_sub(offset.len())];
+                value.extend(&offset[offset.len().saturating_sub(*size)..]);
+                Instruction::Push(value)
+            }
+        })
+        .collect())
+}
+
+fn label(offset: usize) -> String {
+    format!("tag_{:04x}", offset)
+}
+
+/// `disassembly` as assembly that `parse` reads back into the same bytecode
+///
+/// Every `JUMPDEST` gets a label, and pushes of a `JUMPDEST` offset right
+/// before a `JUMP` or `JUMPI` refer to it by label.
+pub fn print(disassembly: &Disassembly) -> String {
+    let jumpdests = disassembly.instructions.iter()
+        .filter(|(_, instr)| *instr == Instruction::JumpDest)
+        .map(|(offset, _)| *offset)
+        .collect::<BTreeSet<_>>();
+    let mut contents = String::new();
+    let mut instrs = disassembly.instructions.iter().peekable();
+    while let Some((offset, instr)) = instrs.next() {
+        if jumpdests.contains(offset) {
+            contents += &format!("{}:\n", label(*offset));
+        }
+        let jumps = matches!(instrs.peek(), Some((_, Instruction::Jump)) | Some((_, Instruction::JumpIf)));
+        match instr {
+            Instruction::Push(value) if jumps => {
+                let zeros = value.iter().take_while(|b| **b == 0).count();
+                let target = match value.len() - zeros {
+                    0..=8 => value[zeros..].iter().fold(0usize, |acc, b| acc << 8 | *b as usize),
+                    _ => usize::MAX,
+                };
+                if jumpdests.contains(&target) {
+                    contents += &format!("    PUSH{} @{}\n", value.len(), label(target));
+                } else {
+                    contents += &format!("    {}\n", instr);
+                }
+            }
+            _ => contents += &format!("    {}\n", instr),
+        }
+    }
+    contents
+}
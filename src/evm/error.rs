@@ -58,3 +58,28 @@ impl std::fmt::Display for DisassemblyError {
 }
 
 impl std::error::Error for DisassemblyError {}
+
+/// Error in textual assembly, with its line number
+#[derive(Debug, PartialEq)]
+pub enum AsmError {
+    InvalidInstruction(usize, String),
+    InvalidLabel(usize, String),
+    DuplicateLabel(usize, String),
+    UnknownLabel(usize, String),
+    /// Label offset does not fit in the size of its `PUSH`
+    LabelOutOfRange(usize, String),
+}
+
+impl std::fmt::Display for AsmError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidInstruction(line, err) => write!(f, "line {}: {}", line, err),
+            Self::InvalidLabel(line, label) => write!(f, "line {}: invalid label {}", line, label),
+            Self::DuplicateLabel(line, label) => write!(f, "line {}: label {} is already defined", line, label),
+            Self::UnknownLabel(line, label) => write!(f, "line {}: unknown label {}", line, label),
+            Self::LabelOutOfRange(line, label) => write!(f, "line {}: offset of {} does not fit in the push", line, label),
+        }
+    }
+}
+
+impl std::error::Error for AsmError {}
@@ -14,6 +14,7 @@
 
 use crate::evm::error::DisassemblyError;
 use std::io::{Cursor, Read};
+use uint::rustc_hex::FromHex;
 
 pub fn assemble(disassembly: &[Instruction]) -> Vec<u8> {
     let mut result = Vec::new();
@@ -500,3 +501,233 @@ impl Instruction {
         }
     }
 }
+
+impl std::fmt::Display for Instruction {
+    /// Mnemonic, followed by the pushed value in hex for `PUSH`
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mnemonic = match self {
+            Self::Push(v) => {
+                write!(f, "PUSH{} 0x", v.len())?;
+                return v.iter().try_for_each(|b| write!(f, "{:02x}", b));
+            }
+            Self::Dup(u) => return write!(f, "DUP{}", u + 1),
+            Self::Swap(u) => return write!(f, "SWAP{}", u),
+            Self::Log(u) => return write!(f, "LOG{}", u),
+            Self::Stop => "STOP",
+            Self::Add => "ADD",
+            Self::Mul => "MUL",
+            Self::Sub => "SUB",
+            Self::Div => "DIV",
+            Self::SDiv => "SDIV",
+            Self::Mod => "MOD",
+            Self::SMod => "SMOD",
+            Self::AddMod => "ADDMOD",
+            Self::MulMod => "MULMOD",
+            Self::Exp => "EXP",
+            Self::SignExtend => "SIGNEXTEND",
+            Self::Lt => "LT",
+            Self::Gt => "GT",
+            Self::SLt => "SLT",
+            Self::SGt => "SGT",
+            Self::EQ => "EQ",
+            Self::IsZero => "ISZERO",
+            Self::And => "AND",
+            Self::Or => "OR",
+            Self::Xor => "XOR",
+            Self::Not => "NOT",
+            Self::Byte => "BYTE",
+            Self::Shl => "SHL",
+            Self::Shr => "SHR",
+            Self::Sar => "SAR",
+            Self::Sha3 => "SHA3",
+            Self::Addr => "ADDRESS",
+            Self::Balance => "BALANCE",
+            Self::Origin => "ORIGIN",
+            Self::Caller => "CALLER",
+            Self::CallValue => "CALLVALUE",
+            Self::CallDataLoad => "CALLDATALOAD",
+            Self::CallDataSize => "CALLDATASIZE",
+            Self::CallDataCopy => "CALLDATACOPY",
+            Self::CodeSize => "CODESIZE",
+            Self::CodeCopy => "CODECOPY",
+            Self::GasPrice => "GASPRICE",
+            Self::ExtCodeSize => "EXTCODESIZE",
+            Self::ExtCodeCopy => "EXTCODECOPY",
+            Self::ReturnDataSize => "RETURNDATASIZE",
+            Self::ReturnDataCopy => "RETURNDATACOPY",
+            Self::ExtCodeHash => "EXTCODEHASH",
+            Self::Blockhash => "BLOCKHASH",
+            Self::Coinbase => "COINBASE",
+            Self::Timestamp => "TIMESTAMP",
+            Self::Number => "NUMBER",
+            Self::Difficulty => "DIFFICULTY",
+            Self::GasLimit => "GASLIMIT",
+            Self::ChainId => "CHAINID",
+            Self::Pop => "POP",
+            Self::MLoad => "MLOAD",
+            Self::MStore => "MSTORE",
+            Self::MStore8 => "MSTORE8",
+            Self::SLoad => "SLOAD",
+            Self::SStore => "SSTORE",
+            Self::Jump => "JUMP",
+            Self::JumpIf => "JUMPI",
+            Self::PC => "PC",
+            Self::MSize => "MSIZE",
+            Self::Gas => "GAS",
+            Self::JumpDest => "JUMPDEST",
+            Self::Create => "CREATE",
+            Self::Call => "CALL",
+            Self::CallCode => "CALLCODE",
+            Self::Return => "RETURN",
+            Self::DelegateCall => "DELEGATECALL",
+            Self::Create2 => "CREATE2",
+            Self::Revert => "REVERT",
+            Self::StaticCall => "STATICCALL",
+            Self::Invalid => "INVALID",
+            Self::SelfDestruct => "SELFDESTRUCT",
+        };
+        write!(f, "{}", mnemonic)
+    }
+}
+
+impl std::str::FromStr for Instruction {
+    type Err = String;
+
+    /// Parse `ADD`, `DUP1` or `PUSH2 0x0100`. `PUSH` without a size takes the
+    /// smallest that fits its value, given in hex or decimal.
+    fn from_str(s: &str) -> Result<Self, String> {
+        let mut words = s.split_whitespace();
+        let mnemonic = words.next().ok_or_else(|| "empty instruction".to_owned())?.to_uppercase();
+        let operand = words.next();
+        if words.next().is_some() {
+            return Err(format!("too many operands in {}", s));
+        }
+        let numbered = |prefix: &str, range: std::ops::RangeInclusive<usize>| {
+            mnemonic.strip_prefix(prefix)
+                .and_then(|n| n.parse::<usize>().ok())
+                .filter(|n| range.contains(n))
+        };
+
+        if let Some(size) = mnemonic.strip_prefix("PUSH") {
+            let operand = operand.ok_or_else(|| format!("missing value in {}", s))?;
+            let mut value = parse_value(operand).ok_or_else(|| format!("invalid value in {}", s))?;
+            let size = match size {
+                "" => value.len(),
+                _ => numbered("PUSH", 1..=32).ok_or_else(|| format!("unknown instruction {}", s))?,
+            };
+            if value.len() > size {
+                return Err(format!("value does not fit in {}", s));
+            }
+            value.splice(0..0, std::iter::repeat(0).take(size - value.len()));
+            return Ok(Self::Push(value));
+        }
+        if let Some(operand) = operand {
+            return Err(format!("unexpected operand {} in {}", operand, s));
+        }
+        if let Some(n) = numbered("DUP", 1..=16) {
+            return Ok(Self::Dup(n - 1));
+        }
+        if let Some(n) = numbered("SWAP", 1..=16) {
+            return Ok(Self::Swap(n));
+        }
+        if let Some(n) = numbered("LOG", 0..=4) {
+            return Ok(Self::Log(n));
+        }
+        Ok(match mnemonic.as_str() {
+            "STOP" => Self::Stop,
+            "ADD" => Self::Add,
+            "MUL" => Self::Mul,
+            "SUB" => Self::Sub,
+            "DIV" => Self::Div,
+            "SDIV" => Self::SDiv,
+            "MOD" => Self::Mod,
+            "SMOD" => Self::SMod,
+            "ADDMOD" => Self::AddMod,
+            "MULMOD" => Self::MulMod,
+            "EXP" => Self::Exp,
+            "SIGNEXTEND" => Self::SignExtend,
+            "LT" => Self::Lt,
+            "GT" => Self::Gt,
+            "SLT" => Self::SLt,
+            "SGT" => Self::SGt,
+            "EQ" => Self::EQ,
+            "ISZERO" => Self::IsZero,
+            "AND" => Self::And,
+            "OR" => Self::Or,
+            "XOR" => Self::Xor,
+            "NOT" => Self::Not,
+            "BYTE" => Self::Byte,
+            "SHL" => Self::Shl,
+            "SHR" => Self::Shr,
+            "SAR" => Self::Sar,
+            "SHA3" => Self::Sha3,
+            "ADDRESS" => Self::Addr,
+            "BALANCE" => Self::Balance,
+            "ORIGIN" => Self::Origin,
+            "CALLER" => Self::Caller,
+            "CALLVALUE" => Self::CallValue,
+            "CALLDATALOAD" => Self::CallDataLoad,
+            "CALLDATASIZE" => Self::CallDataSize,
+            "CALLDATACOPY" => Self::CallDataCopy,
+            "CODESIZE" => Self::CodeSize,
+            "CODECOPY" => Self::CodeCopy,
+            "GASPRICE" => Self::GasPrice,
+            "EXTCODESIZE" => Self::ExtCodeSize,
+            "EXTCODECOPY" => Self::ExtCodeCopy,
+            "RETURNDATASIZE" => Self::ReturnDataSize,
+            "RETURNDATACOPY" => Self::ReturnDataCopy,
+            "EXTCODEHASH" => Self::ExtCodeHash,
+            "BLOCKHASH" => Self::Blockhash,
+            "COINBASE" => Self::Coinbase,
+            "TIMESTAMP" => Self::Timestamp,
+            "NUMBER" => Self::Number,
+            "DIFFICULTY" => Self::Difficulty,
+            "GASLIMIT" => Self::GasLimit,
+            "CHAINID" => Self::ChainId,
+            "POP" => Self::Pop,
+            "MLOAD" => Self::MLoad,
+            "MSTORE" => Self::MStore,
+            "MSTORE8" => Self::MStore8,
+            "SLOAD" => Self::SLoad,
+            "SSTORE" => Self::SStore,
+            "JUMP" => Self::Jump,
+            "JUMPI" => Self::JumpIf,
+            "PC" => Self::PC,
+            "MSIZE" => Self::MSize,
+            "GAS" => Self::Gas,
+            "JUMPDEST" => Self::JumpDest,
+            "CREATE" => Self::Create,
+            "CALL" => Self::Call,
+            "CALLCODE" => Self::CallCode,
+            "RETURN" => Self::Return,
+            "DELEGATECALL" => Self::DelegateCall,
+            "CREATE2" => Self::Create2,
+            "REVERT" => Self::Revert,
+            "STATICCALL" => Self::StaticCall,
+            "INVALID" => Self::Invalid,
+            "SELFDESTRUCT" => Self::SelfDestruct,
+            "KECCAK256" => Self::Sha3,
+            "PREVRANDAO" => Self::Difficulty,
+            _ => return Err(format!("unknown instruction {}", s)),
+        })
+    }
+}
+
+/// Big endian bytes of `0x..` hex or a decimal number below 2^256, at least one
+fn parse_value(s: &str) -> Option<Vec<u8>> {
+    let mut bytes = if let Some(hex) = s.strip_prefix("0x") {
+        let hex = if hex.len() % 2 == 1 { format!("0{}", hex) } else { hex.to_owned() };
+        hex.from_hex::<Vec<u8>>().ok()?
+    } else {
+        let value = ethereum_types::U256::from_dec_str(s).ok()?;
+        let mut word = [0u8; 32];
+        value.to_big_endian(&mut word);
+        word.to_vec()
+    };
+    let zeros = bytes.iter().take_while(|b| **b == 0).count();
+    bytes.drain(..std::cmp::min(zeros, bytes.len().saturating_sub(1)));
+    if bytes.is_empty() || bytes.len() > 32 {
+        return None;
+    }
+    Some(bytes)
+}
@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub mod asm;
 pub mod error;
 pub mod instructions;
 
@@ -20,7 +21,7 @@ use std::io::Cursor;
 
 use instructions::{assemble_instruction, disassemble_next_byte};
 
-pub use error::DisassemblyError;
+pub use error::{AsmError, DisassemblyError};
 pub use instructions::Instruction;
 
 type InstrTy = (usize, Instruction);
@@ -97,4 +98,72 @@ mod tests {
             println!("{:?}", opcode);
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_mnemonics() {
+        let bytes: Vec<u8> = "6080604052348015600f57600080fd5b5060d48061006c6000396000f3fe7f0100000000000000000000000000000000000000000000000000000000000000ffa3"
+            .from_hex().unwrap();
+        for (_, instr) in Disassembly::from_bytes(&bytes).unwrap().instructions {
+            assert_eq!(instr.to_string().parse::<Instruction>(), Ok(instr));
+        }
+        assert_eq!(Instruction::Push(vec![0x60, 0x80]).to_string(), "PUSH2 0x6080");
+        assert_eq!(Instruction::Dup(0).to_string(), "DUP1");
+        assert_eq!("push 256".parse(), Ok(Instruction::Push(vec![1, 0])));
+        assert_eq!("PUSH4 0x80".parse(), Ok(Instruction::Push(vec![0, 0, 0, 0x80])));
+        assert_eq!("keccak256".parse(), Ok(Instruction::Sha3));
+        assert!("PUSH1 0x0100".parse::<Instruction>().is_err());
+        assert!("DUP17".parse::<Instruction>().is_err());
+        assert!("ADD 1".parse::<Instruction>().is_err());
+    }
+
+    #[test]
+    fn test_asm() {
+        let text = "
+            // count down from 3
+                PUSH1 3
+            loop: JUMPDEST      ; counter on the stack
+                PUSH 1
+                SWAP1
+                SUB
+                DUP1
+                PUSH @loop
+                JUMPI
+                PUSH2 @end
+                JUMP
+            end:
+                JUMPDEST
+        ";
+        let instrs = asm::parse(text).unwrap();
+        assert_eq!(instrs[6], Instruction::Push(vec![2]));
+        assert_eq!(instrs[8], Instruction::Push(vec![0, 15]));
+        let bytes = assemble_instructions(&instrs);
+        let expected: Vec<u8> = "60035b600190038060025761000f565b".from_hex().unwrap();
+        assert_eq!(bytes, expected);
+
+        let printed = asm::print(&Disassembly::from_bytes(&bytes).unwrap());
+        assert!(printed.contains("tag_0002:\n    JUMPDEST\n"));
+        assert!(printed.contains("    PUSH1 @tag_0002\n    JUMPI\n"));
+        assert_eq!(assemble_instructions(&asm::parse(&printed).unwrap()), bytes);
+
+        // offsets past 255 need a wider push, moving the label itself
+        let text = format!("PUSH @far\nJUMP\n{}far: JUMPDEST\n", "STOP\n".repeat(253));
+        let instrs = asm::parse(&text).unwrap();
+        assert_eq!(instrs[0], Instruction::Push(vec![1, 1]));
+
+        // wider pushes than an offset are padded with zeros
+        let instrs = asm::parse("PUSH32 @end\nJUMP\nend: JUMPDEST\n").unwrap();
+        let mut word = vec![0; 32];
+        word[31] = 34;
+        assert_eq!(instrs[0], Instruction::Push(word));
+        let bytes = assemble_instructions(&instrs);
+        let printed = asm::print(&Disassembly::from_bytes(&bytes).unwrap());
+        assert!(printed.contains("    PUSH32 @tag_0022\n"));
+        assert_eq!(assemble_instructions(&asm::parse(&printed).unwrap()), bytes);
+        assert_eq!(asm::parse("PUSH9 @a\na: STOP").unwrap()[0], Instruction::Push(vec![0, 0, 0, 0, 0, 0, 0, 0, 10]));
+
+        assert_eq!(asm::parse("PUSH @nowhere"), Err(AsmError::UnknownLabel(1, "nowhere".to_owned())));
+        assert_eq!(asm::parse("a:\na: STOP"), Err(AsmError::DuplicateLabel(2, "a".to_owned())));
+        assert_eq!(asm::parse(&text.replace("PUSH @far", "PUSH1 @far")), Err(AsmError::LabelOutOfRange(1, "far".to_owned())));
+        assert!(matches!(asm::parse("STOP\nFOO"), Err(AsmError::InvalidInstruction(2, _))));
+    }
+}
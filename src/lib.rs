@@ -2,4 +2,13 @@ pub mod evm;
 pub mod compiler;
 pub mod ethabi;
 pub mod cffi;
-pub mod solc;
\ No newline at end of file
+pub mod cstruct;
+pub mod rustgen;
+pub mod tsgen;
+pub mod symbols;
+pub mod solc;
+pub mod artifact;
+pub mod vyper;
+pub mod runner;
+pub mod address;
+pub mod config;
\ No newline at end of file
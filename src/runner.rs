@@ -0,0 +1,265 @@
+//! Run a compiled contract on the host.
+//!
+//! A `run.c` harness is written next to the generated sources. It deploys the
+//! contract, makes a single call and prints the logs and return data. It is
+//! linked against the host build of the runtime copied into the output
+//! directory, then executed.
+
+use inkwell::module::Module;
+use inkwell::targets::{CodeModel, FileType, InitializationConfig, RelocMode, Target, TargetMachine};
+use inkwell::OptimizationLevel;
+use crate::compiler::Compiler;
+use crate::config::BuildConfig;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use uint::rustc_hex::FromHex;
+
+/// Transaction run against a freshly deployed contract
+#[derive(Debug, Default, Clone)]
+pub struct Call {
+    /// ABI encoded constructor arguments
+    pub ctor_args: Vec<u8>,
+    pub calldata: Vec<u8>,
+    pub caller: [u8; 20],
+    /// Address of the contract, its ADDRESS
+    pub address: [u8; 20],
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Log {
+    pub topics: Vec<Vec<u8>>,
+    pub data: Vec<u8>,
+}
+
+/// What the call returned or reverted with, and the logs it emitted
+#[derive(Debug, Clone, PartialEq)]
+pub struct Outcome {
+    pub reverted: bool,
+    pub data: Vec<u8>,
+    pub logs: Vec<Log>,
+}
+
+#[derive(Debug)]
+pub enum RunError {
+    IOError(std::io::Error),
+    /// LLVM could not emit an object file for the host
+    Object(String),
+    /// A build step or the harness failed, with its stderr
+    Command(String, String),
+    InvalidOutput(String),
+    ConstructorReverted(Vec<u8>),
+}
+
+impl std::convert::From<std::io::Error> for RunError {
+    fn from(err: std::io::Error) -> Self {
+        RunError::IOError(err)
+    }
+}
+
+impl std::fmt::Display for RunError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::IOError(err) => write!(f, "{}", err),
+            Self::Object(err) => write!(f, "Unable to emit object file: {}", err),
+            Self::Command(command, stderr) => write!(f, "{} failed: {}", command, stderr.trim_end()),
+            Self::InvalidOutput(output) => write!(f, "Invalid harness output: {}", output),
+            Self::ConstructorReverted(data) => write!(f, "Constructor reverted with 0x{}", hex(data)),
+        }
+    }
+}
+
+impl std::error::Error for RunError {}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// C array initializer, with a trailing zero so that it is never empty
+fn c_array(bytes: &[u8]) -> String {
+    let mut items = bytes.iter().map(|b| format!("0x{:02x}", b)).collect::<Vec<_>>();
+    items.push("0".to_owned());
+    items.join(", ")
+}
+
+/// `run.c`, deploying `contract`, whose init code is `code_size` bytes, and making `call`
+pub fn format_harness(contract: &str, code_size: usize, call: &Call) -> String {
+    let ctor = Compiler::format_fn_name(contract, false);
+    let runtime = Compiler::format_fn_name(contract, true);
+    let mut contents = String::new();
+    contents += &format!("/* automatically generated by solenoid {} */\n", env!("CARGO_PKG_VERSION"));
+    contents += "\n";
+    contents += "#include \"rt.h\"\n";
+    contents += "#include \"contracts.h\"\n";
+    contents += "\n";
+    contents += &format!("extern i8 {}_code[];\n", contract);
+    contents += &format!("static i8 ctor_tx[{}];\n", code_size + call.ctor_args.len() + 1);
+    contents += &format!("static i8 ctor_args[] = {{{}}};\n", c_array(&call.ctor_args));
+    contents += &format!("static i8 calldata[] = {{{}}};\n", c_array(&call.calldata));
+    contents += &format!("static i8 caller[] = {{{}}};\n", c_array(&call.caller));
+    contents += &format!("static i8 address[] = {{{}}};\n", c_array(&call.address));
+    contents += "\n";
+    contents += "static void print_hex(const i8* p, uint64_t len) {\n";
+    contents += "    for (uint64_t i = 0; i < len; i++) {\n";
+    contents += "        printf(\"%02x\", p[i]);\n";
+    contents += "    }\n";
+    contents += "}\n";
+    contents += "\n";
+    contents += "/* memory words are little endian */\n";
+    contents += "static void print_ret(const char* label, i8* ret, long len) {\n";
+    contents += "    swap_words(ret, len);\n";
+    contents += "    printf(\"%s \", label);\n";
+    contents += "    print_hex(ret, len);\n";
+    contents += "    printf(\"\\n\");\n";
+    contents += "}\n";
+    contents += "\n";
+    contents += "int main() {\n";
+    contents += "    long offset = 0, length = 0;\n";
+    contents += "    reverted = 0;\n";
+    contents += &format!("    memcpy({}_address, address, 20);\n", contract);
+    contents += "    /* init code followed by the arguments, in memory word order */\n";
+    contents += &format!("    memcpy(ctor_tx, {}_code, {});\n", contract, code_size);
+    contents += &format!("    memcpy(ctor_tx + {}, ctor_args, {});\n", code_size, call.ctor_args.len());
+    contents += &format!("    swap_words(ctor_tx + {}, {});\n", code_size, call.ctor_args.len());
+    contents += &format!("    {}(ctor_tx, {}, &offset, &length, storage, caller);\n", ctor, code_size + call.ctor_args.len());
+    contents += "    if (reverted) {\n";
+    contents += &format!("        print_ret(\"constructor-revert\", {}_mem + offset, length);\n", contract);
+    contents += "        return 0;\n";
+    contents += "    }\n";
+    contents += "    log_clear();\n";
+    contents += "\n";
+    contents += "    offset = length = 0;\n";
+    contents += &format!("    {}(calldata, {}, &offset, &length, storage, caller);\n", runtime, call.calldata.len());
+    contents += "    for (uint64_t i = 0; i < log_count(); i++) {\n";
+    contents += "        const i8 *topics, *data;\n";
+    contents += "        uint64_t n, len;\n";
+    contents += "        log_get(i, &topics, &n, &data, &len);\n";
+    contents += "        printf(\"log \");\n";
+    contents += "        for (uint64_t t = 0; t < n; t++) {\n";
+    contents += "            if (t) {\n";
+    contents += "                printf(\",\");\n";
+    contents += "            }\n";
+    contents += "            print_hex(topics + 32 * t, 32);\n";
+    contents += "        }\n";
+    contents += "        printf(\" \");\n";
+    contents += "        print_hex(data, len);\n";
+    contents += "        printf(\"\\n\");\n";
+    contents += "    }\n";
+    contents += &format!("    print_ret(reverted ? \"revert\" : \"return\", {}_mem + offset, length);\n", contract);
+    contents += "    return 0;\n";
+    contents += "}\n";
+    contents
+}
+
+/// Outcome printed by the harness
+pub fn parse_outcome(output: &str) -> Result<Outcome, RunError> {
+    let invalid = || RunError::InvalidOutput(output.to_owned());
+    let bytes = |s: Option<&str>| s.unwrap_or("").from_hex::<Vec<u8>>().map_err(|_| invalid());
+    let mut logs = Vec::new();
+    for line in output.lines() {
+        let mut words = line.split(' ');
+        match words.next() {
+            Some("log") => {
+                let topics = words.next().unwrap_or("").split(',')
+                    .filter(|topic| !topic.is_empty())
+                    .map(|topic| bytes(Some(topic)))
+                    .collect::<Result<_, _>>()?;
+                logs.push(Log { topics, data: bytes(words.next())? });
+            }
+            Some("return") => return Ok(Outcome { reverted: false, data: bytes(words.next())?, logs }),
+            Some("revert") => return Ok(Outcome { reverted: true, data: bytes(words.next())?, logs }),
+            Some("constructor-revert") => return Err(RunError::ConstructorReverted(bytes(words.next())?)),
+            _ => return Err(invalid()),
+        }
+    }
+    Err(invalid())
+}
+
+/// Emit `module` as a host object file, optimized at `opt_level` 0 to 3
+pub fn write_object(module: &Module, path: &Path, opt_level: u8) -> Result<(), RunError> {
+    Target::initialize_native(&InitializationConfig::default()).map_err(RunError::Object)?;
+    let triple = TargetMachine::get_default_triple();
+    let target = Target::from_triple(&triple).map_err(|e| RunError::Object(e.to_string()))?;
+    let level = match opt_level {
+        0 => OptimizationLevel::None,
+        1 => OptimizationLevel::Less,
+        2 => OptimizationLevel::Default,
+        _ => OptimizationLevel::Aggressive,
+    };
+    let machine = target
+        .create_target_machine(&triple, "generic", "", level, RelocMode::PIC, CodeModel::Default)
+        .ok_or_else(|| RunError::Object(format!("no target machine for {}", triple)))?;
+    machine.write_to_file(module, FileType::Object, path).map_err(|e| RunError::Object(e.to_string()))
+}
+
+/// stdout of `command`, failing if it does not exit successfully
+fn command(command: &mut Command) -> Result<String, RunError> {
+    let output = command.output()?;
+    if !output.status.success() {
+        return Err(RunError::Command(format!("{:?}", command), String::from_utf8_lossy(&output.stderr).to_string()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Build the harness for `contract` in `outdir`, the output directory `module` was generated in, and run `call`
+///
+/// `code_size` is the length of the contract's init code. The harness always
+/// runs on the host, whatever the configured target.
+pub fn run(module: &Module, outdir: &Path, contract: &str, code_size: usize, call: &Call, config: &BuildConfig) -> Result<Outcome, RunError> {
+    let build = outdir.join("build");
+    fs::create_dir_all(&build)?;
+    let object = build.join("contracts.o");
+    write_object(module, &object, config.opt_level)?;
+    let harness = outdir.join("src").join("run.c");
+    fs::write(&harness, format_harness(contract, code_size, call))?;
+
+    let runtime = outdir.join("solenoid-rt");
+    command(Command::new("cargo")
+        .arg("build")
+        .arg("--release")
+        .arg("--manifest-path")
+        .arg(runtime.join("Cargo.toml"))
+        .arg("--features")
+        .arg(config.features.join(" ")))?;
+    let exe = build.join("run");
+    let cc = std::env::var("CC").unwrap_or_else(|_| "cc".to_owned());
+    command(Command::new(cc)
+        .arg(&harness)
+        .arg(&object)
+        .arg(runtime.join("target").join("release").join("libsolenoid_rt.a"))
+        .arg("-Wl,--gc-sections")
+        .arg("-o")
+        .arg(&exe))?;
+    parse_outcome(&command(&mut Command::new(&exe))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_harness() {
+        let call = Call { ctor_args: vec![], calldata: vec![0x18, 0x16, 0x0d, 0xdd], caller: [0xaa; 20], address: [0xbb; 20] };
+        let harness = format_harness("ERC20Basic", 100, &call);
+        assert!(harness.contains("static i8 ctor_args[] = {0};\n"));
+        assert!(harness.contains("static i8 calldata[] = {0x18, 0x16, 0x0d, 0xdd, 0};\n"));
+        assert!(harness.contains("    memcpy(ERC20Basic_address, address, 20);\n"));
+        assert!(harness.contains("    memcpy(ctor_tx, ERC20Basic_code, 100);\n"));
+        assert!(harness.contains("    ERC20Basic_constructor(ctor_tx, 100, &offset, &length, storage, caller);\n"));
+        assert!(harness.contains("    ERC20Basic_runtime(calldata, 4, &offset, &length, storage, caller);\n"));
+    }
+
+    #[test]
+    fn test_parse_outcome() {
+        let output = "log aa,bb 01\nlog  \nreturn 0001\n";
+        let outcome = parse_outcome(output).unwrap();
+        assert_eq!(outcome, Outcome {
+            reverted: false,
+            data: vec![0, 1],
+            logs: vec![Log { topics: vec![vec![0xaa], vec![0xbb]], data: vec![1] }, Log { topics: vec![], data: vec![] }],
+        });
+        assert!(parse_outcome("revert \n").unwrap().reverted);
+        assert!(matches!(parse_outcome("constructor-revert 08\n"), Err(RunError::ConstructorReverted(data)) if data == [8]));
+        assert!(parse_outcome("").is_err());
+    }
+}
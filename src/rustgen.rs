@@ -0,0 +1,801 @@
+//! Typed Rust bindings over the raw bindgen output.
+//!
+//! Every contract gets a struct of the same name in `contracts.rs` that owns
+//! its storage, is created with `deploy` and has one method per ABI function.
+//! Methods encode their arguments with the `abi_{C}_{fn}` helpers, run the
+//! contract and decode the return data with `abi_{C}_{fn}_decode`, so they
+//! take and return `U256`, `Address`, `bool`, `Vec<u8>` and `String` instead of
+//! raw pointers. A reverted call rolls storage back and returns the revert
+//! data as `Err(CallError::Revert)`, return data that does not decode as the
+//! function outputs is `Err(CallError::MalformedReturn)`. Logs emitted by a
+//! call are decoded into the contract's event enum.
+//!
+//! Methods and `deploy` return `CallError` rather than a bare `Revert`
+//! because a call can fail before or after the contract runs: a `U256`
+//! argument to a `uint8` to `uint64` or `int8` to `int64` parameter that does
+//! not fit is `Err(CallError::ArgumentOutOfRange)` instead of being truncated.
+//! `fallback` and `receive` take no typed arguments and return no typed
+//! values, so they still return `Revert`.
+//!
+//! The generated module sits next to `bindings.rs` and depends on the
+//! `ethereum-types` crate.
+
+use crate::compiler::Compiler;
+use crate::symbols::Symbols;
+use crate::ethabi::{Contract, Event, Function, Param, ParamType, StateMutability, param_type::ParamType::*};
+use std::path::PathBuf;
+use std::fs;
+use std::string::String;
+use log::warn;
+
+/// Generate `contracts.rs` wrapping the compiled contracts in safe structs
+pub struct RustGenerator {
+    contracts: Vec<String>,
+}
+
+const PRELUDE: &str = r#"
+#![allow(dead_code, unused_mut, unused_unsafe, non_camel_case_types, clippy::all)]
+
+use crate::bindings;
+use ethereum_types::{Address, U256};
+use std::sync::Mutex;
+
+/// Compiled contracts share their stack and memory, so calls are serialized
+static LOCK: Mutex<()> = Mutex::new(());
+
+/// Size of the storage table owned by every contract
+pub const STORAGE_SIZE: usize = 1024 * 64;
+
+type Entry = unsafe extern "C" fn(*mut u8, i64, *mut i64, *mut i64, *mut u8, *mut u8);
+
+/// Data passed to REVERT by a failed call, its memory words in ABI byte order
+///
+/// solc writes `Error(string)` and custom errors at unaligned offsets, which
+/// little endian memory words do not preserve, so they are not decoded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Revert {
+    pub data: Vec<u8>,
+}
+
+impl std::fmt::Display for Revert {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "reverted")
+    }
+}
+
+impl std::error::Error for Revert {}
+
+/// Why a call to an ABI function failed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CallError {
+    /// The contract reverted
+    Revert(Revert),
+    /// The return data does not decode as the outputs of the function
+    MalformedReturn(Vec<u8>),
+    /// The argument of this name does not fit its `uintN` or `intN` type
+    ArgumentOutOfRange(&'static str),
+}
+
+impl From<Revert> for CallError {
+    fn from(revert: Revert) -> Self {
+        Self::Revert(revert)
+    }
+}
+
+impl std::fmt::Display for CallError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Revert(revert) => write!(f, "{}", revert),
+            Self::MalformedReturn(data) => write!(f, "malformed return data of {} bytes", data.len()),
+            Self::ArgumentOutOfRange(name) => write!(f, "argument {} is out of range", name),
+        }
+    }
+}
+
+impl std::error::Error for CallError {}
+
+/// Log emitted by a call, topics and data in ABI byte order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Log {
+    pub topics: Vec<[u8; 32]>,
+    pub data: Vec<u8>,
+}
+
+/// Types that take a single ABI word
+trait Word: Sized {
+    fn to_word(&self) -> [u8; 32];
+    fn from_word(word: &[u8]) -> Self;
+}
+
+impl Word for U256 {
+    fn to_word(&self) -> [u8; 32] {
+        let mut word = [0u8; 32];
+        self.to_big_endian(&mut word);
+        word
+    }
+
+    fn from_word(word: &[u8]) -> Self {
+        U256::from_big_endian(word)
+    }
+}
+
+impl Word for bool {
+    fn to_word(&self) -> [u8; 32] {
+        U256::from(*self as u8).to_word()
+    }
+
+    fn from_word(word: &[u8]) -> Self {
+        word.iter().any(|&b| b != 0)
+    }
+}
+
+impl Word for Address {
+    fn to_word(&self) -> [u8; 32] {
+        let mut word = [0u8; 32];
+        word[12..].copy_from_slice(self.as_bytes());
+        word
+    }
+
+    fn from_word(word: &[u8]) -> Self {
+        Address::from_slice(&word[12..])
+    }
+}
+
+/// Sign extend `x` to 256 bits
+fn int256(x: i64) -> U256 {
+    if x < 0 {
+        !U256::from(!x as u64)
+    } else {
+        U256::from(x as u64)
+    }
+}
+
+/// Whether `x`, two's complement if signed, fits `bits` wide integers
+fn fits(x: U256, bits: usize, signed: bool) -> bool {
+    if !signed {
+        return x.bits() <= bits;
+    }
+    let half = U256::one() << (bits - 1);
+    x < half || x >= !(half - 1)
+}
+
+/// Left aligned, zero padded `bytesN` word
+fn fixed_bytes(x: &[u8]) -> [u8; 32] {
+    let mut word = [0u8; 32];
+    let n = x.len().min(32);
+    word[..n].copy_from_slice(&x[..n]);
+    word
+}
+
+/// Word `i` of ABI encoded `data`
+fn read_word(data: &[u8], i: usize) -> Option<&[u8]> {
+    data.get(i * 32..i * 32 + 32)
+}
+
+/// Offset and length of the dynamic value whose offset is word `i` of `data`
+fn read_tail(data: &[u8], i: usize) -> Option<(usize, usize)> {
+    let bound = U256::from(data.len());
+    let offset = U256::from_big_endian(read_word(data, i)?);
+    if offset >= bound {
+        return None;
+    }
+    let offset = offset.as_usize();
+    let len = U256::from_big_endian(data.get(offset..offset + 32)?);
+    if len > bound {
+        return None;
+    }
+    Some((offset + 32, len.as_usize()))
+}
+
+/// Contents of the `bytes` or `string` whose offset is word `i` of `data`
+fn read_bytes(data: &[u8], i: usize) -> Option<&[u8]> {
+    let (start, len) = read_tail(data, i)?;
+    data.get(start..start + len)
+}
+
+/// Elements of the array whose offset is word `i` of `data`
+fn read_array<T: Word>(data: &[u8], i: usize) -> Option<Vec<T>> {
+    let (start, len) = read_tail(data, i)?;
+    Some(data.get(start..start + 32 * len)?.chunks(32).map(T::from_word).collect())
+}
+
+/// Elements of an array handed back by a decoder
+unsafe fn array<T: Word>(ptr: *mut u8, len: i64) -> Vec<T> {
+    std::slice::from_raw_parts(ptr, 32 * len as usize).chunks(32).map(T::from_word).collect()
+}
+
+/// Drain the logs buffered by the runtime
+unsafe fn take_logs() -> Vec<Log> {
+    let mut logs = Vec::new();
+    for i in 0..bindings::log_count() {
+        let (mut topics, mut n, mut data, mut len): (*const u8, u64, *const u8, u64) = (std::ptr::null(), 0, std::ptr::null(), 0);
+        if bindings::log_get(i, &mut topics, &mut n, &mut data, &mut len) == 0 {
+            break;
+        }
+        let topics = std::slice::from_raw_parts(topics, 32 * n as usize)
+            .chunks(32)
+            .map(|t| {
+                let mut topic = [0u8; 32];
+                topic.copy_from_slice(t);
+                topic
+            })
+            .collect();
+        let data = std::slice::from_raw_parts(data, len as usize).to_vec();
+        logs.push(Log { topics, data });
+    }
+    bindings::log_clear();
+    logs
+}
+
+/// Run `entry` over `tx` against `storage` and copy the return data out of
+/// `mem`, with `address` copied to `this`, the contract's ADDRESS. Storage is
+/// rolled back and the logs dropped if the call reverts.
+fn call(entry: Entry, mem: *const u8, this: *mut u8, tx: &mut [u8], storage: &mut [u8], caller: &Address, address: &Address) -> (Result<Vec<u8>, Revert>, Vec<Log>) {
+    let _lock = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let snapshot = storage.to_vec();
+    let mut caller = caller.to_fixed_bytes();
+    let (mut offset, mut len) = (0i64, 0i64);
+    unsafe {
+        std::ptr::copy_nonoverlapping(address.as_ptr(), this, 20);
+        bindings::reverted = 0;
+        bindings::log_clear();
+        entry(tx.as_mut_ptr(), tx.len() as i64, &mut offset, &mut len, storage.as_mut_ptr(), caller.as_mut_ptr());
+        let mut data = std::slice::from_raw_parts(mem.add(offset as usize), len as usize).to_vec();
+        let logs = take_logs();
+        if bindings::reverted != 0 {
+            storage.copy_from_slice(&snapshot);
+            // memory words are little endian
+            data.chunks_exact_mut(32).for_each(|word| word.reverse());
+            return (Err(Revert { data }), Vec::new());
+        }
+        (Ok(data), logs)
+    }
+}
+"#;
+
+/// Methods every contract struct has, which ABI functions must not shadow
+const RESERVED: &[&str] = &["deploy", "storage", "take_events", "call", "view", "fallback", "receive"];
+
+const KEYWORDS: &[&str] = &[
+    "as", "break", "const", "continue", "crate", "else", "enum", "extern", "false", "fn", "for",
+    "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return",
+    "self", "static", "struct", "super", "trait", "true", "type", "unsafe", "use", "where",
+    "while", "async", "await", "dyn", "abstract", "become", "box", "do", "final", "macro",
+    "override", "priv", "typeof", "unsized", "virtual", "yield", "try",
+];
+
+impl RustGenerator {
+    pub fn new() -> Self {
+        Self {
+            contracts: Vec::new(),
+        }
+    }
+
+    /// `balanceOf` -> `balance_of`, escaping keywords
+    fn snake_case(name: &str) -> String {
+        let mut out = String::new();
+        let mut prev_lower = false;
+        for c in name.chars() {
+            if c.is_uppercase() && prev_lower {
+                out.push('_');
+            }
+            prev_lower = c.is_lowercase() || c.is_numeric();
+            out.extend(c.to_lowercase());
+        }
+        if KEYWORDS.contains(&out.as_str()) {
+            out = format!("r#{}", out);
+        }
+        out
+    }
+
+    fn param_name(param: &Param, i: usize) -> String {
+        if param.name.is_empty() {
+            format!("arg{}", i)
+        } else {
+            Self::snake_case(param.name.trim_start_matches('_'))
+        }
+    }
+
+    /// Element types arrays can hold
+    fn word_type(kind: &ParamType) -> Option<&'static str> {
+        match kind {
+            Uint(_) | Int(_) => Some("U256"),
+            Bool => Some("bool"),
+            Address => Some("Address"),
+            _ => None,
+        }
+    }
+
+    /// Rust type of an argument
+    fn arg_type(kind: &ParamType) -> Option<String> {
+        match kind {
+            Bytes | FixedBytes(_) => Some("&[u8]".to_owned()),
+            String => Some("&str".to_owned()),
+            Array(elem) => Self::word_type(elem).map(|ty| format!("&[{}]", ty)),
+            _ => Self::word_type(kind).map(str::to_owned),
+        }
+    }
+
+    /// Rust type of a return value or event field
+    fn ret_type(kind: &ParamType) -> Option<String> {
+        match kind {
+            Bytes | FixedBytes(_) => Some("Vec<u8>".to_owned()),
+            String => Some("String".to_owned()),
+            Array(elem) => Self::word_type(elem).map(|ty| format!("Vec<{}>", ty)),
+            _ => Self::word_type(kind).map(str::to_owned),
+        }
+    }
+
+    /// Rust type bindgen gives a small int in the C helpers
+    fn small_int(bits: usize) -> Option<&'static str> {
+        match bits {
+            8 => Some("u8"),
+            16 => Some("i16"),
+            32 => Some("i32"),
+            64 => Some("i64"),
+            _ => None,
+        }
+    }
+
+    /// Typed signature of `fun`, or `None` if one of its types is not supported
+    fn signature(fun: &Function) -> Option<(Vec<String>, Vec<String>)> {
+        let args = fun.inputs.iter().enumerate()
+            .map(|(i, param)| Self::arg_type(&param.kind).map(|ty| format!("{}: {}", Self::param_name(param, i), ty)))
+            .collect::<Option<Vec<_>>>()?;
+        let rets = fun.outputs.iter()
+            .map(|param| Self::ret_type(&param.kind))
+            .collect::<Option<Vec<_>>>()?;
+        Some((args, rets))
+    }
+
+    /// Statements building the transaction for `fun` into `tx`/`tx_size`
+    fn format_encode(fun: &Function, symbol: &str, base: usize) -> String {
+        let mut contents = String::new();
+        let mut cap = format!("{} + {}", base, 32 * fun.inputs.len());
+        let mut args = vec!["tx.as_mut_ptr()".to_owned(), "&mut tx_size".to_owned()];
+        let mut temps = String::new();
+        for (i, param) in fun.inputs.iter().enumerate() {
+            let name = Self::param_name(param, i);
+            match &param.kind {
+                Uint(bits) | Int(bits) if Self::small_int(*bits).is_some() => {
+                    let signed = matches!(param.kind, Int(_));
+                    contents += &format!("        if !fits({}, {}, {}) {{\n", name, bits, signed);
+                    contents += &format!("            return Err(CallError::ArgumentOutOfRange(\"{}\"));\n", name);
+                    contents += "        }\n";
+                    args.push(format!("{}.low_u64() as _", name));
+                }
+                Bool => args.push(format!("{} as _", name)),
+                Uint(_) | Int(_) => {
+                    temps += &format!("        let mut a{}_word = {}.to_word();\n", i, name);
+                    args.push(format!("a{}_word.as_mut_ptr()", i));
+                }
+                Address => {
+                    temps += &format!("        let mut a{}_word = {}.to_fixed_bytes();\n", i, name);
+                    args.push(format!("a{}_word.as_mut_ptr()", i));
+                }
+                FixedBytes(_) => {
+                    temps += &format!("        let mut a{}_word = fixed_bytes({});\n", i, name);
+                    args.push(format!("a{}_word.as_mut_ptr()", i));
+                }
+                Array(_) => {
+                    cap += &format!(" + 32 + 32 * {}.len()", name);
+                    temps += &format!("        let mut a{}_words: Vec<u8> = {}.iter().flat_map(Word::to_word).collect();\n", i, name);
+                    args.push(format!("a{}_words.as_mut_ptr()", i));
+                    args.push(format!("{}.len() as _", name));
+                }
+                _ => {
+                    // bytes and string
+                    cap += &format!(" + 64 + {}.len()", name);
+                    args.push(format!("{}.as_ptr() as *mut u8", name));
+                    args.push(format!("{}.len() as _", name));
+                }
+            }
+        }
+        contents += &format!("        let mut tx = vec![0u8; {}];\n", cap);
+        contents += "        let mut tx_size = 0;\n";
+        contents += &temps;
+        contents += &format!("        unsafe {{ bindings::{}({}) }};\n", symbol, args.join(", "));
+        contents
+    }
+
+    /// Statements decoding `ret` into the value of the method body
+    fn format_decode(fun: &Function, symbol: &str) -> String {
+        let mut contents = String::new();
+        let mut args = vec!["ret.as_mut_ptr()".to_owned(), "ret.len() as _".to_owned()];
+        let mut values = Vec::new();
+        for (i, param) in fun.outputs.iter().enumerate() {
+            let out = format!("out{}", i);
+            match &param.kind {
+                Uint(bits) | Int(bits) if Self::small_int(*bits).is_some() => {
+                    contents += &format!("        let mut {}: {} = 0;\n", out, Self::small_int(*bits).unwrap());
+                    args.push(format!("&mut {}", out));
+                    values.push(match param.kind {
+                        Int(_) => format!("int256({} as i{} as i64)", out, bits),
+                        _ => format!("U256::from({} as u{})", out, bits),
+                    });
+                }
+                Bool => {
+                    contents += &format!("        let mut {}: i32 = 0;\n", out);
+                    args.push(format!("&mut {}", out));
+                    values.push(format!("{} != 0", out));
+                }
+                Uint(_) | Int(_) => {
+                    contents += &format!("        let mut {} = [0u8; 32];\n", out);
+                    args.push(format!("{}.as_mut_ptr()", out));
+                    values.push(format!("U256::from_big_endian(&{})", out));
+                }
+                Address => {
+                    contents += &format!("        let mut {} = [0u8; 20];\n", out);
+                    args.push(format!("{}.as_mut_ptr()", out));
+                    values.push(format!("Address::from({})", out));
+                }
+                FixedBytes(n) => {
+                    contents += &format!("        let mut {} = [0u8; {}];\n", out, n);
+                    args.push(format!("{}.as_mut_ptr()", out));
+                    values.push(format!("{}.to_vec()", out));
+                }
+                kind => {
+                    // dynamic outputs point into ret
+                    contents += &format!("        let (mut {}, mut {}_len): (*mut u8, i64) = (std::ptr::null_mut(), 0);\n", out, out);
+                    args.push(format!("&mut {}", out));
+                    args.push(format!("&mut {}_len", out));
+                    let slice = format!("std::slice::from_raw_parts({}, {}_len as usize)", out, out);
+                    values.push(match kind {
+                        String => format!("unsafe {{ String::from_utf8_lossy({}).into_owned() }}", slice),
+                        Array(_) => format!("unsafe {{ array({}, {}_len) }}", out, out),
+                        _ => format!("unsafe {{ {}.to_vec() }}", slice),
+                    });
+                }
+            }
+        }
+        let decoder = format!("{}_decode", symbol);
+        contents += &format!("        let ok = unsafe {{ bindings::{}({}) }};\n", decoder, args.join(", "));
+        contents += "        if ok == 0 {\n";
+        contents += "            return Err(CallError::MalformedReturn(ret));\n";
+        contents += "        }\n";
+        contents += &match values.len() {
+            1 => format!("        Ok({})\n", values[0]),
+            _ => format!("        Ok(({}))\n", values.join(", ")),
+        };
+        contents
+    }
+
+    /// Method calling `fun`, named after its ABI helper `symbol` so overloads
+    /// get the same suffix as in `contracts.h`
+    fn format_method(contract_name: &str, fun: &Function, symbol: &str) -> String {
+        let (args, rets) = match Self::signature(fun) {
+            Some(sig) => sig,
+            None => {
+                warn!("{}.{} has types the typed bindings do not support", contract_name, fun.signature());
+                return format!("    // {} is not supported by the typed bindings\n", fun.signature());
+            }
+        };
+        let prefix = format!("abi_{}_", contract_name);
+        let mut name = Self::snake_case(&symbol.strip_prefix(&prefix).unwrap_or(symbol).replace("__", "_"));
+        if RESERVED.contains(&name.as_str()) {
+            name += "_";
+        }
+        let ret = match rets.len() {
+            1 => rets[0].clone(),
+            _ => format!("({})", rets.join(", ")),
+        };
+
+        // view and pure functions run against a scratch copy of storage
+        let (receiver, call) = if fun.state_mutability.is_read_only() {
+            ("&self", "view")
+        } else {
+            ("&mut self", "call")
+        };
+
+        let mut contents = String::new();
+        contents += &format!("    /// Call `{}`\n", fun.signature());
+        contents += &format!("    pub fn {}({}{}) -> Result<{}, CallError> {{\n", name, receiver,
+            args.iter().map(|arg| format!(", {}", arg)).collect::<String>(), ret);
+        contents += &Self::format_encode(fun, symbol, 4);
+        let runtime = Compiler::format_fn_name(contract_name, true);
+        if fun.outputs.is_empty() {
+            contents += &format!("        self.{}(bindings::{}, &mut tx[..tx_size as usize])?;\n", call, runtime);
+            contents += "        Ok(())\n";
+        } else {
+            contents += &format!("        let mut ret = self.{}(bindings::{}, &mut tx[..tx_size as usize])?;\n", call, runtime);
+            contents += &Self::format_decode(fun, symbol);
+        }
+        contents += "    }\n";
+        contents
+    }
+
+    fn format_deploy(contract_name: &str, contract: &Contract, code_size: usize, symbols: &Symbols) -> String {
+        let inputs = contract.constructor.as_ref().map(|i| i.inputs.clone()).unwrap_or(vec![]);
+        let ctor = Function {
+            name: "constructor".to_owned(),
+            inputs,
+            outputs: vec![],
+            constant: false,
+            state_mutability: StateMutability::NonPayable,
+        };
+        let (args, _) = match Self::signature(&ctor) {
+            Some(sig) => sig,
+            None => {
+                warn!("{} constructor has types the typed bindings do not support", contract_name);
+                return "    // the constructor is not supported by the typed bindings\n".to_owned();
+            }
+        };
+
+        let mut contents = String::new();
+        contents += "    /// Run the constructor against fresh storage\n";
+        contents += &format!("    pub fn deploy(caller: Address{}) -> Result<Self, CallError> {{\n",
+            args.iter().map(|arg| format!(", {}", arg)).collect::<String>());
+        contents += "        let mut contract = Self {\n";
+        contents += "            storage: vec![0; STORAGE_SIZE],\n";
+        contents += "            caller,\n";
+        contents += "            address: Address::zero(),\n";
+        contents += "            events: Vec::new(),\n";
+        contents += "        };\n";
+        contents += &Self::format_encode(&ctor, &symbols.get(contract_name, &ctor), code_size);
+        contents += &format!("        contract.call(bindings::{}, &mut tx[..tx_size as usize])?;\n",
+            Compiler::format_fn_name(contract_name, false));
+        contents += "        Ok(contract)\n";
+        contents += "    }\n";
+        contents
+    }
+
+    /// Expression reading event field `param`, from the topics of
+    /// `log` if indexed and from the ABI encoded `data` otherwise. `None` if
+    /// the type is not supported.
+    fn format_field(param: &ParamType, indexed: bool, pos: usize, data: &str) -> Option<(String, String)> {
+        if indexed {
+            // dynamic indexed values only leave their hash
+            return Some(match Self::word_type(param) {
+                Some(ty) => (ty.to_owned(), format!("Word::from_word(log.topics.get({})?)", pos)),
+                None => ("[u8; 32]".to_owned(), format!("*log.topics.get({})?", pos)),
+            });
+        }
+        let ty = Self::ret_type(param)?;
+        let value = match param {
+            FixedBytes(n) => format!("read_word({}, {})?[..{}].to_vec()", data, pos, n),
+            Bytes => format!("read_bytes({}, {})?.to_vec()", data, pos),
+            String => format!("String::from_utf8_lossy(read_bytes({}, {})?).into_owned()", data, pos),
+            Array(_) => format!("read_array({}, {})?", data, pos),
+            _ => format!("Word::from_word(read_word({}, {})?)", data, pos),
+        };
+        Some((ty, value))
+    }
+
+    /// Variant declaration and decoder of `event`
+    fn format_event(variant: &str, event: &Event) -> Option<(String, String)> {
+        let mut fields = Vec::new();
+        let mut values = Vec::new();
+        let (mut topic, mut word) = (1, 0);
+        for (i, param) in event.inputs.iter().enumerate() {
+            let name = if param.name.is_empty() {
+                format!("arg{}", i)
+            } else {
+                Self::snake_case(param.name.trim_start_matches('_'))
+            };
+            let pos = if param.indexed { topic } else { word };
+            let (ty, value) = Self::format_field(&param.kind, param.indexed, pos, "&log.data")?;
+            if param.indexed {
+                topic += 1;
+            } else {
+                word += 1;
+            }
+            fields.push(format!("{}: {}", name, ty));
+            values.push(format!("{}: {}", name, value));
+        }
+
+        let decl = if fields.is_empty() {
+            format!("    {},\n", variant)
+        } else {
+            format!("    {} {{ {} }},\n", variant, fields.join(", "))
+        };
+        let decoder = if values.is_empty() {
+            format!("Some(Self::{})", variant)
+        } else {
+            format!("(|| Some(Self::{} {{ {} }}))()", variant, values.join(", "))
+        };
+        Some((decl, decoder))
+    }
+
+    fn format_events(contract_name: &str, contract: &Contract) -> String {
+        let enum_name = format!("{}Event", contract_name);
+        let mut variants = String::new();
+        let mut arms = String::new();
+        for (name, events) in &contract.events {
+            for (idx, event) in events.iter().enumerate() {
+                let variant = if idx == 0 { name.clone() } else { format!("{}{}", name, idx) };
+                if event.anonymous {
+                    warn!("{}.{} is anonymous and cannot be decoded", contract_name, name);
+                    continue;
+                }
+                let (decl, decoder) = match Self::format_event(&variant, event) {
+                    Some(event) => event,
+                    None => {
+                        warn!("{}.{} has types the typed bindings do not support", contract_name, name);
+                        continue;
+                    }
+                };
+                let topic = event.signature().as_bytes().iter()
+                    .map(|b| format!("0x{:02x}", b))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                variants += &decl;
+                arms += &format!("            Some(topic) if *topic == [{}] => {},\n", topic, decoder);
+            }
+        }
+
+        let mut contents = String::new();
+        contents += &format!("/// Events emitted by `{}`\n", contract_name);
+        contents += "#[derive(Debug, Clone, PartialEq, Eq)]\n";
+        contents += &format!("pub enum {} {{\n", enum_name);
+        contents += &variants;
+        contents += "    /// Log that matches none of the events above\n";
+        contents += "    Unknown(Log),\n";
+        contents += "}\n";
+        contents += "\n";
+        contents += &format!("impl From<Log> for {} {{\n", enum_name);
+        contents += "    fn from(log: Log) -> Self {\n";
+        contents += "        let event = match log.topics.first() {\n";
+        contents += &arms;
+        contents += "            _ => None,\n";
+        contents += "        };\n";
+        contents += "        event.unwrap_or(Self::Unknown(log))\n";
+        contents += "    }\n";
+        contents += "}\n";
+        contents
+    }
+
+    /// Wrap `contract_name`. `code_size` bounds the bytecode the constructor
+    /// helper copies in front of the arguments.
+    pub fn add_contract(&mut self, contract_name: &str, contract: &Contract, code_size: usize, symbols: &Symbols) {
+        let mut contents = String::new();
+        contents += &Self::format_events(contract_name, contract);
+        contents += "\n";
+        contents += &format!("/// `{}` deployed with its own storage\n", contract_name);
+        contents += &format!("pub struct {} {{\n", contract_name);
+        contents += "    storage: Vec<u8>,\n";
+        contents += "    /// Address calls are made from\n";
+        contents += "    pub caller: Address,\n";
+        contents += "    /// Address the contract runs at, its ADDRESS, zero while deploying\n";
+        contents += "    pub address: Address,\n";
+        contents += &format!("    events: Vec<{}Event>,\n", contract_name);
+        contents += "}\n";
+        contents += "\n";
+        contents += &format!("impl {} {{\n", contract_name);
+        contents += &Self::format_deploy(contract_name, contract, code_size, symbols);
+        contents += "\n";
+        contents += "    /// Storage table, laid out as in Solana account data\n";
+        contents += "    pub fn storage(&self) -> &[u8] {\n";
+        contents += "        &self.storage\n";
+        contents += "    }\n";
+        contents += "\n";
+        contents += "    /// Events emitted since the last call to `take_events`\n";
+        contents += &format!("    pub fn take_events(&mut self) -> Vec<{}Event> {{\n", contract_name);
+        contents += "        std::mem::take(&mut self.events)\n";
+        contents += "    }\n";
+        contents += "\n";
+        contents += "    fn call(&mut self, entry: Entry, tx: &mut [u8]) -> Result<Vec<u8>, Revert> {\n";
+        contents += &format!("        let mem = unsafe {{ std::ptr::addr_of!(bindings::{}_mem) as *const u8 }};\n", contract_name);
+        contents += &format!("        let this = unsafe {{ std::ptr::addr_of_mut!(bindings::{}_address) as *mut u8 }};\n", contract_name);
+        contents += "        let (ret, logs) = call(entry, mem, this, tx, &mut self.storage, &self.caller, &self.address);\n";
+        contents += "        self.events.extend(logs.into_iter().map(From::from));\n";
+        contents += "        ret\n";
+        contents += "    }\n";
+        contents += "\n";
+        contents += "    fn view(&self, entry: Entry, tx: &mut [u8]) -> Result<Vec<u8>, Revert> {\n";
+        contents += &format!("        let mem = unsafe {{ std::ptr::addr_of!(bindings::{}_mem) as *const u8 }};\n", contract_name);
+        contents += &format!("        let this = unsafe {{ std::ptr::addr_of_mut!(bindings::{}_address) as *mut u8 }};\n", contract_name);
+        contents += "        let mut storage = self.storage.clone();\n";
+        contents += "        call(entry, mem, this, tx, &mut storage, &self.caller, &self.address).0\n";
+        contents += "    }\n";
+        let runtime = Compiler::format_fn_name(contract_name, true);
+        if contract.fallback {
+            contents += "\n";
+            contents += "    /// Call the fallback function with raw `calldata`, returning the raw return data\n";
+            contents += "    pub fn fallback(&mut self, calldata: &[u8]) -> Result<Vec<u8>, Revert> {\n";
+            contents += &format!("        let mut ret = self.call(bindings::{}, &mut calldata.to_vec())?;\n", runtime);
+            contents += "        // memory words are little endian\n";
+            contents += "        ret.chunks_exact_mut(32).for_each(|word| word.reverse());\n";
+            contents += "        Ok(ret)\n";
+            contents += "    }\n";
+        }
+        if contract.receive {
+            contents += "\n";
+            contents += "    /// Call the receive function, with empty calldata\n";
+            contents += "    pub fn receive(&mut self) -> Result<(), Revert> {\n";
+            contents += &format!("        self.call(bindings::{}, &mut [])?;\n", runtime);
+            contents += "        Ok(())\n";
+            contents += "    }\n";
+        }
+        for (_name, funs) in &contract.functions {
+            for fun in funs {
+                contents += "\n";
+                contents += &Self::format_method(contract_name, fun, &symbols.get(contract_name, fun));
+            }
+        }
+        contents += "}\n";
+        self.contracts.push(contents);
+    }
+
+    pub fn generate(&self, outdir: &PathBuf) -> std::io::Result<()> {
+        fs::create_dir_all(outdir.join("src"))?;
+
+        let mut contents = String::new();
+        contents += &format!("/* automatically generated by solenoid {} */\n", env!("CARGO_PKG_VERSION"));
+        contents += PRELUDE;
+        for contract in &self.contracts {
+            contents += "\n";
+            contents += contract;
+        }
+        fs::write(outdir.join("src/contracts.rs"), contents)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snake_case() {
+        assert_eq!(RustGenerator::snake_case("balanceOf"), "balance_of");
+        assert_eq!(RustGenerator::snake_case("getURI"), "get_uri");
+        assert_eq!(RustGenerator::snake_case("transfer2"), "transfer2");
+        assert_eq!(RustGenerator::snake_case("type"), "r#type");
+    }
+
+    #[test]
+    fn test_format_encode() {
+        let fun: Function = "function set(uint8 small, int16 delta, uint256 big)".parse().unwrap();
+        let encode = RustGenerator::format_encode(&fun, "abi_C_set", 4);
+        assert!(encode.starts_with(concat!(
+            "        if !fits(small, 8, false) {\n",
+            "            return Err(CallError::ArgumentOutOfRange(\"small\"));\n",
+            "        }\n",
+            "        if !fits(delta, 16, true) {\n",
+        )));
+        assert!(!encode.contains("fits(big"));
+        assert!(encode.contains("bindings::abi_C_set(tx.as_mut_ptr(), &mut tx_size, small.low_u64() as _, delta.low_u64() as _, a2_word.as_mut_ptr())"));
+    }
+
+    #[test]
+    fn test_format_decode() {
+        let fun: Function = "function balanceOf(address owner) view returns (uint256)".parse().unwrap();
+        let method = RustGenerator::format_method("Token", &fun, "abi_Token_balanceOf");
+        assert!(method.contains("    pub fn balance_of(&self, owner: Address) -> Result<U256, CallError> {\n"));
+        assert!(method.contains("        let mut ret = self.view(bindings::Token_runtime, &mut tx[..tx_size as usize])?;\n"));
+        assert!(method.contains("        let ok = unsafe { bindings::abi_Token_balanceOf_decode(ret.as_mut_ptr(), ret.len() as _, out0.as_mut_ptr()) };\n"));
+        assert!(method.ends_with(concat!(
+            "        if ok == 0 {\n",
+            "            return Err(CallError::MalformedReturn(ret));\n",
+            "        }\n",
+            "        Ok(U256::from_big_endian(&out0))\n",
+            "    }\n",
+        )));
+        assert!(!method.contains("assert!"));
+
+        let fun: Function = "function info() returns (uint8, bool, address, string, uint256[])".parse().unwrap();
+        let decode = RustGenerator::format_decode(&fun, "abi_Token_info");
+        assert!(decode.starts_with(concat!(
+            "        let mut out0: u8 = 0;\n",
+            "        let mut out1: i32 = 0;\n",
+            "        let mut out2 = [0u8; 20];\n",
+            "        let (mut out3, mut out3_len): (*mut u8, i64) = (std::ptr::null_mut(), 0);\n",
+            "        let (mut out4, mut out4_len): (*mut u8, i64) = (std::ptr::null_mut(), 0);\n",
+            "        let ok = unsafe { bindings::abi_Token_info_decode(ret.as_mut_ptr(), ret.len() as _, ",
+            "&mut out0, &mut out1, out2.as_mut_ptr(), &mut out3, &mut out3_len, &mut out4, &mut out4_len) };\n",
+            "        if ok == 0 {\n",
+            "            return Err(CallError::MalformedReturn(ret));\n",
+            "        }\n",
+        )));
+        assert!(decode.ends_with(concat!(
+            "        Ok((U256::from(out0 as u8), out1 != 0, Address::from(out2), ",
+            "unsafe { String::from_utf8_lossy(std::slice::from_raw_parts(out3, out3_len as usize)).into_owned() }, ",
+            "unsafe { array(out4, out4_len) }))\n",
+        )));
+
+        // functions without outputs have nothing to decode
+        let fun: Function = "function approve(address spender, uint256 value)".parse().unwrap();
+        let method = RustGenerator::format_method("Token", &fun, "abi_Token_approve");
+        assert!(method.contains("-> Result<(), CallError> {\n"));
+        assert!(!method.contains("_decode"));
+    }
+}
@@ -1,47 +1,681 @@
-use serde_json;
+//! Compile Solidity sources with `solc --standard-json`.
+//!
+//! The compiler is picked from a directory of installed versions,
+//! `~/.solenoid/solc/<version>/solc`: the highest one satisfying the
+//! `pragma solidity` constraints of every source. `solc` on `PATH` is used
+//! when none is installed that does. The binary's `--version` is checked
+//! before compiling.
+
+mod version;
+
+pub use self::version::{Version, VersionReq};
+
+use serde_json::{self, json, Value};
 use serde::{Deserialize, Serialize};
-use crate::evm::{Disassembly, Instruction};
-use std::collections::BTreeMap;
-use std::process::Command;
-use std::path::PathBuf;
-use uint::rustc_hex::FromHex;
+use crate::evm::{Disassembly, DisassemblyError, Instruction};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::path::{Path, PathBuf};
+use uint::rustc_hex::{FromHex, ToHex};
+use ethereum_types::Address;
+use log::info;
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(default)]
 pub struct Contract {
     pub abi: String,
     pub bin: String,
     #[serde(rename="bin-runtime")]
     pub bin_runtime: String,
+    /// Constructor source map
+    pub srcmap: String,
+    #[serde(rename="srcmap-runtime")]
+    pub srcmap_runtime: String,
+    /// Selectors, by canonical function signature
+    pub method_identifiers: BTreeMap<String, String>,
+    pub storage_layout: Option<Value>,
+    /// Library placeholders in `bin`
+    pub link_references: Vec<LinkReference>,
+    pub link_references_runtime: Vec<LinkReference>,
+}
+
+/// Placeholder left for a library address, `__$<hash>$__` or `__<name>___`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LinkReference {
+    /// `<source>:<library>`, or what the placeholder spells when unknown
+    pub library: String,
+    /// Byte offset in the bytecode
+    pub start: usize,
+    pub length: usize,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy)]
+pub(crate) struct Offset {
+    start: usize,
+    length: usize,
+}
+
+/// `linkReferences` of solc and compiler artifacts, offsets by library by source
+pub(crate) type LinkReferences = BTreeMap<String, BTreeMap<String, Vec<Offset>>>;
+
+pub(crate) fn link_references(refs: LinkReferences) -> Vec<LinkReference> {
+    let mut refs = refs.into_iter()
+        .flat_map(|(source, libraries)| libraries.into_iter().flat_map(move |(library, offsets)| {
+            let library = format!("{}:{}", source, library);
+            offsets.into_iter().map(move |offset| LinkReference { library: library.clone(), start: offset.start, length: offset.length })
+        }))
+        .collect::<Vec<_>>();
+    refs.sort_by_key(|r| r.start);
+    refs
+}
+
+/// Placeholders found by scanning hex bytecode, for artifacts without link references
+pub fn placeholders(hex: &str) -> Vec<LinkReference> {
+    let mut refs = Vec::new();
+    let mut i = 0;
+    while i + 40 <= hex.len() {
+        if hex[i..].starts_with("__") && hex[i + 38..].starts_with("__") {
+            let library = hex[i + 2..i + 38].trim_matches(|c| c == '_' || c == '$').to_owned();
+            refs.push(LinkReference { library, start: i / 2, length: 20 });
+            i += 40;
+        } else {
+            i += 2;
+        }
+    }
+    refs
+}
+
+/// Library address given as `[<source>:]<library>=<address>`
+pub fn parse_library(s: &str) -> Result<(String, Address), String> {
+    let mut parts = s.splitn(2, '=');
+    let (library, address) = match (parts.next(), parts.next()) {
+        (Some(library), Some(address)) => (library, address),
+        _ => return Err(format!("expected <library>=<address>, got {}", s)),
+    };
+    let bytes: Vec<u8> = address.trim_start_matches("0x").from_hex().map_err(|_| format!("invalid address {}", address))?;
+    if bytes.len() != 20 {
+        return Err(format!("invalid address {}", address));
+    }
+    Ok((library.to_owned(), Address::from_slice(&bytes)))
 }
 
 impl Contract {
-    pub fn parse(&self) -> (Vec<u8>, Vec<u8>, Vec<(usize, Instruction)>, Vec<(usize, Instruction)>) {
-        let ctor_bytes: Vec<u8> = (self.bin).from_hex().expect("Invalid Hex String");
-        let ctor_opcodes =  Disassembly::from_bytes(&ctor_bytes).unwrap().instructions;
+    /// Contract of already deployed bytecode, given as hex
+    ///
+    /// Without init code, the constructor only copies the runtime code out and
+    /// returns it, which takes runtime code of at most 0xffff bytes.
+    pub fn from_bytecode(abi: String, bin_runtime: String, bin: Option<String>) -> Result<Self, String> {
+        let bin = match bin {
+            Some(bin) => bin,
+            None => {
+                let length = bin_runtime.len() / 2;
+                if length > 0xffff {
+                    return Err(format!("runtime code of {} bytes needs init code, the generated one copies at most 65535", length));
+                }
+                // PUSH2 length DUP1 PUSH1 12 PUSH1 0 CODECOPY PUSH1 0 RETURN
+                format!("61{:04x}80600c6000396000f3{}", length, bin_runtime)
+            }
+        };
+        Ok(Contract { abi, bin, bin_runtime, ..Default::default() })
+    }
+
+    /// Substitute the placeholders of `libraries`, keyed `<source>:<library>` or by library name alone
+    pub fn link(&mut self, libraries: &BTreeMap<String, Address>) {
+        let link = |hex: &mut String, refs: &[LinkReference]| {
+            for r in refs {
+                let name = r.library.rsplit(':').next().unwrap_or(&r.library);
+                let address = libraries.get(&r.library).or_else(|| libraries.get(name));
+                let range = r.start * 2..(r.start + r.length) * 2;
+                if let (Some(address), Some(_)) = (address, hex.get(range.clone())) {
+                    hex.replace_range(range, &address.as_bytes().to_hex::<String>());
+                }
+            }
+        };
+        link(&mut self.bin, &self.link_references);
+        link(&mut self.bin_runtime, &self.link_references_runtime);
+    }
+
+    /// Libraries whose placeholders are still in the bytecode
+    pub fn unlinked_libraries(&self) -> BTreeSet<String> {
+        let unlinked = |hex: &str, refs: &[LinkReference]| {
+            refs.iter()
+                .filter(|r| hex.get(r.start * 2..r.start * 2 + 2) == Some("__"))
+                .map(|r| r.library.clone())
+                .collect::<Vec<_>>()
+        };
+        unlinked(&self.bin, &self.link_references).into_iter()
+            .chain(unlinked(&self.bin_runtime, &self.link_references_runtime))
+            .collect()
+    }
+
+    pub fn parse(&self) -> Result<(Vec<u8>, Vec<u8>, Vec<(usize, Instruction)>, Vec<(usize, Instruction)>), DisassemblyError> {
+        let ctor_bytes: Vec<u8> = (self.bin).from_hex().map_err(|_| DisassemblyError::InvalidHexCharacter)?;
+        let ctor_opcodes =  Disassembly::from_bytes(&ctor_bytes)?.instructions;
+
+        let rt_bytes: Vec<u8> = (self.bin_runtime).from_hex().map_err(|_| DisassemblyError::InvalidHexCharacter)?;
+        let rt_opcodes =  Disassembly::from_bytes(&rt_bytes)?.instructions;
+        Ok((ctor_bytes, rt_bytes, ctor_opcodes, rt_opcodes))
+    }
+}
+
+/// Contracts in `names`, by name or `<source>:<name>`, and the contracts they depend on
+///
+/// A contract depends on the contracts whose creation code it embeds, which it
+/// deploys with CREATE or CREATE2, and on the libraries it links. Contracts it
+/// only calls through an address cannot be found this way and must be named.
+pub fn select(contracts: BTreeMap<String, Contract>, names: &[String]) -> Result<BTreeMap<String, Contract>, String> {
+    let matches = |wanted: &str, name: &str| wanted == name || wanted == name.rsplit(':').next().unwrap_or(name);
+    let mut pending = Vec::new();
+    for wanted in names {
+        let found = contracts.keys().filter(|name| matches(wanted, name)).collect::<Vec<_>>();
+        if found.is_empty() {
+            return Err(format!("{} was not compiled", wanted));
+        }
+        pending.extend(found);
+    }
 
-        let rt_bytes: Vec<u8> = (self.bin_runtime).from_hex().expect("Invalid Hex String");
-        let rt_opcodes =  Disassembly::from_bytes(&rt_bytes).unwrap().instructions;
-        (ctor_bytes, rt_bytes, ctor_opcodes, rt_opcodes)
+    let mut selected = BTreeSet::new();
+    while let Some(name) = pending.pop() {
+        if !selected.insert(name.clone()) {
+            continue;
+        }
+        let contract = &contracts[name];
+        let refs = contract.link_references.iter().chain(&contract.link_references_runtime).collect::<Vec<_>>();
+        for (other, dependency) in &contracts {
+            let created = other != name
+                && !dependency.bin.is_empty()
+                && (contract.bin.contains(&dependency.bin) || contract.bin_runtime.contains(&dependency.bin));
+            if created || refs.iter().any(|r| matches(&r.library, other)) {
+                pending.push(other);
+            }
+        }
     }
+    Ok(contracts.into_iter().filter(|(name, _)| selected.contains(name)).collect())
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct Contracts {
-    contracts: BTreeMap<String, Contract>,
+/// Compiler settings passed in the standard JSON input
+#[derive(Debug, Default, Clone)]
+pub struct SolcOptions {
+    /// Import remappings, `prefix=path`
+    pub remappings: Vec<String>,
+    /// Enable the optimizer, tuned for this many runs
+    pub optimizer_runs: Option<u32>,
+    pub evm_version: Option<String>,
+    /// Compile through the Yul IR pipeline
+    pub via_ir: bool,
+    /// solc binary to use instead, a path or an installed version
+    pub solc: Option<String>,
+    /// Directory of installed compilers, `~/.solenoid/solc` by default
+    pub solc_dir: Option<PathBuf>,
 }
 
-pub fn solc_compile(path: &PathBuf) -> BTreeMap<String, Contract> {
-    let cmd = Command::new("solc")
-            .arg(path)
-            .arg("--combined-json")
-            .arg("bin,bin-runtime,abi")
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+    Info,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct SourceLocation {
+    pub file: String,
+    pub start: i64,
+    pub end: i64,
+}
+
+/// Error, warning or info reported by solc
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Diagnostic {
+    pub severity: Severity,
+    /// `TypeError`, `ParserError`, `Warning`..
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub component: String,
+    pub message: String,
+    pub formatted_message: Option<String>,
+    pub error_code: Option<String>,
+    pub source_location: Option<SourceLocation>,
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.formatted_message {
+            Some(message) => write!(f, "{}", message.trim_end()),
+            None => write!(f, "{}: {}", self.kind, self.message),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum SolcError {
+    IOError(std::io::Error),
+    InvalidOutput(serde_json::Error),
+    /// Compilation failed, with all the diagnostics of the run
+    Compilation(Vec<Diagnostic>),
+    InvalidPragma(PathBuf, String),
+    /// No installed compiler satisfies the constraints
+    NoMatchingVersion(Vec<VersionReq>, PathBuf, Vec<Version>),
+    /// The binary reports another version than expected, or none
+    WrongVersion(PathBuf, Option<Version>, String),
+    /// solc exited unsuccessfully, with its stderr
+    Failed(PathBuf, std::process::ExitStatus, String),
+}
+
+impl std::convert::From<std::io::Error> for SolcError {
+    fn from(err: std::io::Error) -> Self {
+        SolcError::IOError(err)
+    }
+}
+
+impl std::convert::From<serde_json::Error> for SolcError {
+    fn from(err: serde_json::Error) -> Self {
+        SolcError::InvalidOutput(err)
+    }
+}
+
+impl std::fmt::Display for SolcError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::IOError(err) => write!(f, "Unable to run solc: {}", err),
+            Self::InvalidOutput(err) => write!(f, "Invalid solc output: {}", err),
+            Self::InvalidPragma(path, err) => write!(f, "Invalid pragma in {}: {}", path.display(), err),
+            Self::NoMatchingVersion(reqs, dir, installed) => {
+                write!(f, "No solc satisfies {}, ", join(reqs, " and "))?;
+                match installed.len() {
+                    0 => write!(f, "none is installed in {}", dir.display()),
+                    _ => write!(f, "{} has {}", dir.display(), join(installed, ", ")),
+                }
+            }
+            Self::WrongVersion(solc, Some(version), expected) => {
+                write!(f, "{} is version {}, expected {}", solc.display(), version, expected)
+            }
+            Self::WrongVersion(solc, None, _) => write!(f, "{} does not report its version", solc.display()),
+            Self::Failed(solc, status, stderr) => write!(f, "{} failed with {}: {}", solc.display(), status, stderr.trim_end()),
+            Self::Compilation(diagnostics) => {
+                let errors = diagnostics.iter().filter(|d| d.severity == Severity::Error);
+                for diagnostic in errors {
+                    writeln!(f, "{}", diagnostic)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for SolcError {}
+
+fn join<T: std::fmt::Display>(items: &[T], sep: &str) -> String {
+    items.iter().map(|item| item.to_string()).collect::<Vec<_>>().join(sep)
+}
+
+fn default_solc_dir() -> PathBuf {
+    let home = std::env::var_os("HOME").unwrap_or_default();
+    PathBuf::from(home).join(".solenoid").join("solc")
+}
+
+/// Versions installed in `dir` as `<version>/solc`, highest first
+pub fn installed_versions(dir: &Path) -> Vec<Version> {
+    let mut versions = fs::read_dir(dir).into_iter().flatten()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().join("solc").is_file())
+        .filter_map(|entry| entry.file_name().to_str()?.parse().ok())
+        .collect::<Vec<Version>>();
+    versions.sort_unstable_by(|a, b| b.cmp(a));
+    versions
+}
+
+/// Version `solc --version` reports
+pub fn solc_version(solc: &Path) -> Result<Version, SolcError> {
+    let output = Command::new(solc).arg("--version").output()?;
+    String::from_utf8_lossy(&output.stdout).lines()
+        .find_map(|line| line.strip_prefix("Version:"))
+        .and_then(|version| version.trim().parse().ok())
+        .ok_or_else(|| SolcError::WrongVersion(solc.to_owned(), None, String::new()))
+}
+
+/// Select the compiler for `sources` and check its version
+pub fn find_solc(sources: &[PathBuf], options: &SolcOptions) -> Result<(PathBuf, Version), SolcError> {
+    let mut reqs = Vec::new();
+    for source in sources {
+        let text = fs::read_to_string(source)?;
+        reqs.extend(version::pragmas(&text).map_err(|e| SolcError::InvalidPragma(source.clone(), e))?);
+    }
+    let satisfies = |version: &Version| reqs.iter().all(|req| req.matches(version));
+
+    let dir = options.solc_dir.clone().unwrap_or_else(default_solc_dir);
+    let (solc, expected) = match &options.solc {
+        Some(solc) => match solc.parse::<Version>() {
+            Ok(version) => (dir.join(version.to_string()).join("solc"), Some(version)),
+            Err(_) => (PathBuf::from(solc), None),
+        },
+        None => {
+            let installed = installed_versions(&dir);
+            match installed.iter().find(|version| satisfies(version)) {
+                Some(version) => (dir.join(version.to_string()).join("solc"), Some(*version)),
+                None => {
+                    let solc = PathBuf::from("solc");
+                    match solc_version(&solc) {
+                        Ok(version) if satisfies(&version) => return Ok((solc, version)),
+                        _ => return Err(SolcError::NoMatchingVersion(reqs, dir, installed)),
+                    }
+                }
+            }
+        }
+    };
+
+    let version = solc_version(&solc)?;
+    if let Some(expected) = expected.filter(|expected| *expected != version) {
+        return Err(SolcError::WrongVersion(solc, Some(version), expected.to_string()));
+    }
+    if !satisfies(&version) {
+        return Err(SolcError::WrongVersion(solc, Some(version), join(&reqs, " and ")));
+    }
+    Ok((solc, version))
+}
+
+/// Contracts by `<source>:<name>`, and the warnings of a successful compilation
+#[derive(Debug, Default)]
+pub struct Output {
+    pub contracts: BTreeMap<String, Contract>,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+#[derive(Deserialize)]
+struct StandardOutput {
+    #[serde(default)]
+    errors: Vec<Diagnostic>,
+    #[serde(default)]
+    contracts: BTreeMap<String, BTreeMap<String, StandardContract>>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StandardContract {
+    #[serde(default)]
+    abi: Value,
+    #[serde(default)]
+    evm: Evm,
+    storage_layout: Option<Value>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct Evm {
+    #[serde(default)]
+    bytecode: Bytecode,
+    #[serde(default)]
+    deployed_bytecode: Bytecode,
+    #[serde(default)]
+    method_identifiers: BTreeMap<String, String>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct Bytecode {
+    #[serde(default)]
+    object: String,
+    #[serde(default)]
+    source_map: String,
+    #[serde(default)]
+    link_references: LinkReferences,
+}
+
+/// Standard JSON input compiling `sources` with `options`
+pub fn standard_input(sources: &[PathBuf], options: &SolcOptions) -> Value {
+    let sources = sources.iter()
+        .map(|path| {
+            let path = path.to_string_lossy().to_string();
+            (path.clone(), json!({ "urls": [path] }))
+        })
+        .collect::<serde_json::Map<_, _>>();
+    let outputs = [
+        "abi",
+        "evm.bytecode.object",
+        "evm.bytecode.sourceMap",
+        "evm.bytecode.linkReferences",
+        "evm.deployedBytecode.object",
+        "evm.deployedBytecode.sourceMap",
+        "evm.deployedBytecode.linkReferences",
+        "evm.methodIdentifiers",
+        "storageLayout",
+    ];
+    let mut settings = json!({
+        "remappings": options.remappings,
+        "optimizer": {
+            "enabled": options.optimizer_runs.is_some(),
+            "runs": options.optimizer_runs.unwrap_or(200),
+        },
+        "outputSelection": { "*": { "*": outputs } },
+    });
+    if let Some(evm_version) = &options.evm_version {
+        settings["evmVersion"] = json!(evm_version);
+    }
+    if options.via_ir {
+        settings["viaIR"] = json!(true);
+    }
+    json!({
+        "language": "Solidity",
+        "sources": sources,
+        "settings": settings,
+    })
+}
+
+/// Parse the standard JSON output, failing if any diagnostic is an error
+pub fn parse_output(json: &str) -> Result<Output, SolcError> {
+    let output: StandardOutput = serde_json::from_str(json)?;
+    if output.errors.iter().any(|d| d.severity == Severity::Error) {
+        return Err(SolcError::Compilation(output.errors));
+    }
+    let mut contracts = BTreeMap::new();
+    for (source, source_contracts) in output.contracts {
+        for (name, contract) in source_contracts {
+            // interfaces and abstract contracts have no bytecode
+            if contract.evm.bytecode.object.is_empty() {
+                continue;
+            }
+            contracts.insert(format!("{}:{}", source, name), Contract {
+                abi: contract.abi.to_string(),
+                bin: contract.evm.bytecode.object,
+                bin_runtime: contract.evm.deployed_bytecode.object,
+                srcmap: contract.evm.bytecode.source_map,
+                srcmap_runtime: contract.evm.deployed_bytecode.source_map,
+                method_identifiers: contract.evm.method_identifiers,
+                storage_layout: contract.storage_layout,
+                link_references: link_references(contract.evm.bytecode.link_references),
+                link_references_runtime: link_references(contract.evm.deployed_bytecode.link_references),
+            });
+        }
+    }
+    Ok(Output { contracts, diagnostics: output.errors })
+}
+
+/// Compile `sources` together, imports being resolved anywhere on the filesystem
+pub fn compile(sources: &[PathBuf], options: &SolcOptions) -> Result<Output, SolcError> {
+    let (solc, version) = find_solc(sources, options)?;
+    info!("Compiling with solc {} at {}", version, solc.display());
+    let mut child = Command::new(&solc)
+            .arg("--standard-json")
             .arg("--allow-paths=/")
-            .output()
-            .expect("solc command failed to start");
-    let json = String::from_utf8_lossy(&cmd.stdout);
-
-    let contracts = serde_json::from_str::<Contracts>(&json)
-        .unwrap_or_else(|_| panic!("{}", &String::from_utf8_lossy(&cmd.stderr)))
-        .contracts;
-    contracts
-}
\ No newline at end of file
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+    let input = standard_input(sources, options).to_string();
+    // a solc exiting early breaks the pipe, its status and stderr tell why
+    let written = child.stdin.take().unwrap().write_all(input.as_bytes());
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(SolcError::Failed(solc, output.status, String::from_utf8_lossy(&output.stderr).into_owned()));
+    }
+    written?;
+    parse_output(&String::from_utf8_lossy(&output.stdout))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_standard_input() {
+        let options = SolcOptions {
+            remappings: vec!["@oz/=lib/openzeppelin/".to_owned()],
+            optimizer_runs: Some(1000),
+            evm_version: Some("istanbul".to_owned()),
+            via_ir: false,
+            ..Default::default()
+        };
+        let input = standard_input(&[PathBuf::from("a.sol"), PathBuf::from("lib/b.sol")], &options);
+        assert_eq!(input["sources"]["lib/b.sol"]["urls"][0], "lib/b.sol");
+        assert_eq!(input["settings"]["remappings"][0], "@oz/=lib/openzeppelin/");
+        assert_eq!(input["settings"]["optimizer"], json!({"enabled": true, "runs": 1000}));
+        assert_eq!(input["settings"]["evmVersion"], "istanbul");
+        assert!(input["settings"].get("viaIR").is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_find_solc() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("solenoid-solc-{}", std::process::id()));
+        for (installed, reported) in &[("0.5.16", "0.5.16"), ("0.8.17", "0.8.17"), ("0.8.3", "0.8.3"), ("0.6.12", "0.6.11")] {
+            let solc = dir.join(installed).join("solc");
+            fs::create_dir_all(solc.parent().unwrap()).unwrap();
+            fs::write(&solc, format!("#!/bin/sh\necho 'solc, the solidity compiler'\necho 'Version: {}+commit.0'\n", reported)).unwrap();
+            fs::set_permissions(&solc, fs::Permissions::from_mode(0o755)).unwrap();
+        }
+        let source = |pragma: &str| {
+            let path = dir.join("c.sol");
+            fs::write(&path, format!("pragma solidity {};\ncontract C {{}}\n", pragma)).unwrap();
+            vec![path]
+        };
+        let options = SolcOptions { solc_dir: Some(dir.clone()), ..Default::default() };
+
+        assert_eq!(find_solc(&source("^0.8.0"), &options).unwrap(), (dir.join("0.8.17/solc"), Version::new(0, 8, 17)));
+        assert_eq!(find_solc(&source(">=0.5.0 <0.8.4"), &options).unwrap().1, Version::new(0, 8, 3));
+        assert_eq!(find_solc(&source("^0.5.0"), &options).unwrap().1, Version::new(0, 5, 16));
+        match find_solc(&source("^0.6.0"), &options) {
+            Err(SolcError::WrongVersion(_, Some(version), _)) => assert_eq!(version, Version::new(0, 6, 11)),
+            other => panic!("unexpected {:?}", other),
+        }
+        match find_solc(&source("=0.1.0"), &options) {
+            Err(SolcError::NoMatchingVersion(_, _, installed)) => assert_eq!(installed.len(), 4),
+            other => panic!("unexpected {:?}", other),
+        }
+        let pinned = SolcOptions { solc: Some("0.5.16".to_owned()), ..options.clone() };
+        assert!(matches!(find_solc(&source("^0.8.0"), &pinned), Err(SolcError::WrongVersion(..))));
+        assert_eq!(find_solc(&source("^0.5.0"), &pinned).unwrap().1, Version::new(0, 5, 16));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_compile_failure() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!("solenoid-solc-failure-{}", std::process::id()));
+        let solc = dir.join("0.8.17").join("solc");
+        fs::create_dir_all(solc.parent().unwrap()).unwrap();
+        fs::write(&solc, "#!/bin/sh\nif [ \"$1\" = --version ]; then echo 'Version: 0.8.17+commit.0'; exit 0; fi\necho 'out of memory' >&2\nexit 3\n").unwrap();
+        fs::set_permissions(&solc, fs::Permissions::from_mode(0o755)).unwrap();
+        let source = dir.join("c.sol");
+        fs::write(&source, "pragma solidity ^0.8.0;\ncontract C {}\n").unwrap();
+
+        let options = SolcOptions { solc_dir: Some(dir.clone()), ..Default::default() };
+        let err = compile(&[source], &options).unwrap_err();
+        assert!(matches!(err, SolcError::Failed(..)));
+        assert!(err.to_string().ends_with(": out of memory"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_parse_output() {
+        let json = r#"{
+            "errors": [{"severity": "warning", "type": "Warning", "component": "general", "errorCode": "2072",
+                "message": "Unused local variable.", "formattedMessage": "Warning: Unused local variable.\n",
+                "sourceLocation": {"file": "a.sol", "start": 10, "end": 20}}],
+            "contracts": {"a.sol": {
+                "A": {"abi": [], "evm": {
+                    "bytecode": {"object": "6080", "sourceMap": "0:1:0:-:0"},
+                    "deployedBytecode": {"object": "6001", "sourceMap": "1:1:0:-:0"},
+                    "methodIdentifiers": {"flip()": "cde4efa9"}},
+                    "storageLayout": {"storage": [], "types": null}},
+                "I": {"abi": [], "evm": {"bytecode": {"object": ""}, "deployedBytecode": {"object": ""}}}
+            }}
+        }"#;
+        let output = parse_output(json).unwrap();
+        assert_eq!(output.contracts.keys().collect::<Vec<_>>(), ["a.sol:A"]);
+        let contract = &output.contracts["a.sol:A"];
+        assert_eq!((contract.abi.as_str(), contract.bin.as_str(), contract.bin_runtime.as_str()), ("[]", "6080", "6001"));
+        assert_eq!(contract.method_identifiers["flip()"], "cde4efa9");
+        assert_eq!(output.diagnostics[0].source_location.as_ref().unwrap().start, 10);
+        assert_eq!(output.diagnostics[0].to_string(), "Warning: Unused local variable.");
+
+        let json = r#"{"errors": [{"severity": "error", "type": "ParserError", "component": "general",
+            "message": "Expected ';' but got '}'"}]}"#;
+        match parse_output(json) {
+            Err(SolcError::Compilation(diagnostics)) => assert_eq!(diagnostics[0].kind, "ParserError"),
+            _ => panic!("expected a compilation error"),
+        }
+    }
+
+    #[test]
+    fn test_select() {
+        let child = Contract { bin: "60016002".to_owned(), ..Default::default() };
+        let factory = Contract { bin: "6080".to_owned(), bin_runtime: "6000600160026003f0".to_owned(), ..Default::default() };
+        let user = Contract {
+            bin: "73__$0123456789abcdef0123456789abcdef01$__00".to_owned(),
+            link_references: vec![LinkReference { library: "l.sol:L".to_owned(), start: 1, length: 20 }],
+            ..Default::default()
+        };
+        let contracts = vec![("c.sol:Child", child), ("f.sol:Factory", factory), ("u.sol:User", user), ("l.sol:L", Contract::default())]
+            .into_iter()
+            .map(|(name, contract)| (name.to_owned(), contract))
+            .collect::<BTreeMap<_, _>>();
+        let names = |selected: BTreeMap<String, Contract>| selected.keys().cloned().collect::<Vec<_>>();
+        assert_eq!(names(select(contracts.clone(), &["Factory".to_owned()]).unwrap()), ["c.sol:Child", "f.sol:Factory"]);
+        assert_eq!(names(select(contracts.clone(), &["u.sol:User".to_owned()]).unwrap()), ["l.sol:L", "u.sol:User"]);
+        assert_eq!(select(contracts, &["Token".to_owned()]).unwrap_err(), "Token was not compiled");
+    }
+
+    #[test]
+    fn test_from_bytecode() {
+        let deployed = Contract::from_bytecode("[]".to_owned(), "6001".to_owned(), None).unwrap();
+        let (ctor_bytes, rt_bytes, ctor_opcodes, _) = deployed.parse().unwrap();
+        assert_eq!((ctor_bytes.len(), &ctor_bytes[12..], rt_bytes), (14, &[0x60, 0x01][..], vec![0x60, 0x01]));
+        assert_eq!(ctor_opcodes[6], (11, Instruction::Return));
+
+        let deployed = Contract::from_bytecode("[]".to_owned(), "6001".to_owned(), Some("6080".to_owned())).unwrap();
+        assert_eq!(deployed.bin, "6080");
+        let largest = Contract::from_bytecode("[]".to_owned(), "00".repeat(0xffff), None).unwrap();
+        assert!(largest.bin.starts_with("61ffff"));
+        assert!(Contract::from_bytecode("[]".to_owned(), "00".repeat(0x10000), None).is_err());
+        assert!(Contract::from_bytecode("[]".to_owned(), "00".repeat(0x10000), Some("6080".to_owned())).is_ok());
+    }
+
+    #[test]
+    fn test_link() {
+        let json = r#"{"contracts": {"a.sol": {
+            "B": {"abi": [], "evm": {
+                "bytecode": {"object": "73__$d4ef4b96a2a48d3d8bb4b1cec8ae7d8bbc$__00",
+                    "linkReferences": {"lib/L.sol": {"L": [{"start": 1, "length": 20}]}}},
+                "deployedBytecode": {"object": "6001"}}}
+        }}}"#;
+        let output = parse_output(json).unwrap();
+        let unlinked = &output.contracts["a.sol:B"];
+        let mut linked = unlinked.clone();
+        assert_eq!(linked.unlinked_libraries().into_iter().collect::<Vec<_>>(), ["lib/L.sol:L"]);
+        linked.link(&BTreeMap::new());
+        assert_eq!(linked.bin, unlinked.bin);
+        let (library, address) = parse_library("L=0x00000000000000000000000000000000000000aa").unwrap();
+        linked.link(&vec![(library, address)].into_iter().collect());
+        assert_eq!(linked.bin, "7300000000000000000000000000000000000000aa00");
+        assert!(linked.unlinked_libraries().is_empty());
+        assert!(linked.parse().unwrap().1 == vec![0x60, 0x01]);
+        assert!(parse_library("lib/L.sol:L=0xaa").is_err());
+    }
+}
@@ -0,0 +1,275 @@
+//! Compiler versions and the `pragma solidity` constraints they must satisfy.
+//!
+//! Constraints follow the npm semver ranges solc accepts: `^0.8.0`, `~0.6.2`,
+//! `>=0.5.0 <0.7.0`, `0.5.0 - 0.6.0`, partial versions such as `0.8` and
+//! alternatives separated by `||`.
+
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Version {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+}
+
+impl Version {
+    pub fn new(major: u64, minor: u64, patch: u64) -> Self {
+        Self { major, minor, patch }
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+impl FromStr for Version {
+    type Err = String;
+
+    /// `0.8.17`, ignoring a `+commit..` or `-nightly..` suffix
+    fn from_str(s: &str) -> Result<Self, String> {
+        match Partial::parse(s.split(|c| c == '+' || c == '-').next().unwrap_or(s))? {
+            Partial { major, minor: Some(minor), patch: Some(patch) } => Ok(Version::new(major, minor, patch)),
+            _ => Err(format!("incomplete version {}", s)),
+        }
+    }
+}
+
+/// Version with trailing components left out or given as `x`/`*`
+#[derive(Debug, Clone, Copy)]
+struct Partial {
+    major: u64,
+    minor: Option<u64>,
+    patch: Option<u64>,
+}
+
+impl Partial {
+    fn parse(s: &str) -> Result<Self, String> {
+        let mut parts = s.trim().trim_start_matches('v').split('.').map(|part| match part {
+            "x" | "X" | "*" => Ok(None),
+            part => part.parse::<u64>().map(Some).map_err(|_| format!("invalid version {}", s)),
+        });
+        let major = parts.next().unwrap_or(Ok(None))?.ok_or_else(|| format!("invalid version {}", s))?;
+        let minor = parts.next().unwrap_or(Ok(None))?;
+        let patch = if minor.is_some() { parts.next().unwrap_or(Ok(None))? } else { None };
+        if parts.next().is_some() {
+            return Err(format!("invalid version {}", s));
+        }
+        Ok(Partial { major, minor, patch })
+    }
+
+    fn lowest(&self) -> Version {
+        Version::new(self.major, self.minor.unwrap_or(0), self.patch.unwrap_or(0))
+    }
+
+    /// Lowest version above every version this one matches
+    fn next(&self) -> Version {
+        match (self.minor, self.patch) {
+            (Some(minor), Some(patch)) => Version::new(self.major, minor, patch + 1),
+            (Some(minor), None) => Version::new(self.major, minor + 1, 0),
+            _ => Version::new(self.major + 1, 0, 0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Eq,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Comparator {
+    op: Op,
+    version: Version,
+}
+
+impl Comparator {
+    fn matches(&self, v: &Version) -> bool {
+        match self.op {
+            Op::Eq => v == &self.version,
+            Op::Gt => v > &self.version,
+            Op::Ge => v >= &self.version,
+            Op::Lt => v < &self.version,
+            Op::Le => v <= &self.version,
+        }
+    }
+}
+
+fn cmp(op: Op, version: Version) -> Comparator {
+    Comparator { op, version }
+}
+
+/// Comparators `op partial` stands for
+fn expand(op: &str, partial: Partial) -> Result<Vec<Comparator>, String> {
+    let exact = partial.patch.is_some();
+    let comparators = match op {
+        "" | "=" if exact => vec![cmp(Op::Eq, partial.lowest())],
+        "" | "=" => vec![cmp(Op::Ge, partial.lowest()), cmp(Op::Lt, partial.next())],
+        ">" if exact => vec![cmp(Op::Gt, partial.lowest())],
+        ">" => vec![cmp(Op::Ge, partial.next())],
+        ">=" => vec![cmp(Op::Ge, partial.lowest())],
+        "<" => vec![cmp(Op::Lt, partial.lowest())],
+        "<=" if exact => vec![cmp(Op::Le, partial.lowest())],
+        "<=" => vec![cmp(Op::Lt, partial.next())],
+        "~" => {
+            let upper = match partial.minor {
+                Some(minor) => Version::new(partial.major, minor + 1, 0),
+                None => Version::new(partial.major + 1, 0, 0),
+            };
+            vec![cmp(Op::Ge, partial.lowest()), cmp(Op::Lt, upper)]
+        }
+        "^" => {
+            // the first non zero component is fixed
+            let upper = match (partial.major, partial.minor, partial.patch) {
+                (0, Some(0), Some(patch)) => Version::new(0, 0, patch + 1),
+                (0, Some(minor), _) => Version::new(0, minor + 1, 0),
+                (major, _, _) => Version::new(major + 1, 0, 0),
+            };
+            vec![cmp(Op::Ge, partial.lowest()), cmp(Op::Lt, upper)]
+        }
+        op => return Err(format!("invalid operator {}", op)),
+    };
+    Ok(comparators)
+}
+
+/// Version constraint, alternatives of comparators that must all hold
+#[derive(Debug, Clone, PartialEq)]
+pub struct VersionReq {
+    text: String,
+    alternatives: Vec<Vec<Comparator>>,
+}
+
+impl VersionReq {
+    pub fn matches(&self, v: &Version) -> bool {
+        self.alternatives.iter().any(|comparators| comparators.iter().all(|c| c.matches(v)))
+    }
+}
+
+impl fmt::Display for VersionReq {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+impl FromStr for VersionReq {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        let mut alternatives = Vec::new();
+        for range in s.split("||") {
+            let words = range.split_whitespace().collect::<Vec<_>>();
+            let mut comparators = Vec::new();
+            if words.len() == 3 && words[1] == "-" {
+                comparators.extend(expand(">=", Partial::parse(words[0])?)?);
+                comparators.extend(expand("<=", Partial::parse(words[2])?)?);
+            } else {
+                let mut words = words.into_iter();
+                while let Some(word) = words.next() {
+                    let split = word.find(|c: char| c.is_ascii_alphanumeric() || c == '*').unwrap_or_else(|| word.len());
+                    let (op, version) = word.split_at(split);
+                    // the operator may be followed by a space
+                    let version = match version {
+                        "" => words.next().ok_or_else(|| format!("missing version in {}", s))?,
+                        version => version,
+                    };
+                    comparators.extend(expand(op, Partial::parse(version)?)?);
+                }
+            }
+            if comparators.is_empty() {
+                return Err(format!("empty version constraint {}", s));
+            }
+            alternatives.push(comparators);
+        }
+        Ok(VersionReq { text: s.trim().to_owned(), alternatives })
+    }
+}
+
+/// Source without its comments
+fn strip_comments(source: &str) -> String {
+    let mut stripped = String::with_capacity(source.len());
+    let mut rest = source;
+    while let Some(pos) = rest.find('/') {
+        stripped += &rest[..pos];
+        let comment = &rest[pos..];
+        rest = if comment.starts_with("//") {
+            &comment[comment.find('\n').unwrap_or_else(|| comment.len())..]
+        } else if comment.starts_with("/*") {
+            &comment[comment.find("*/").map(|end| end + 2).unwrap_or_else(|| comment.len())..]
+        } else {
+            stripped.push('/');
+            &comment[1..]
+        };
+    }
+    stripped + rest
+}
+
+/// Constraints of the `pragma solidity` directives of a source
+pub fn pragmas(source: &str) -> Result<Vec<VersionReq>, String> {
+    let source = strip_comments(source);
+    let mut reqs = Vec::new();
+    let mut rest = source.as_str();
+    while let Some(pos) = rest.find("pragma") {
+        let directive = rest[pos + "pragma".len()..].trim_start();
+        let end = directive.find(';').unwrap_or_else(|| directive.len());
+        if let Some(constraint) = directive[..end].strip_prefix("solidity") {
+            reqs.push(constraint.parse()?);
+        }
+        rest = &directive[end..];
+    }
+    Ok(reqs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn req(s: &str) -> VersionReq {
+        s.parse().unwrap()
+    }
+
+    fn v(s: &str) -> Version {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_version_req() {
+        assert!(req("^0.8.0").matches(&v("0.8.17")));
+        assert!(!req("^0.8.0").matches(&v("0.9.0")));
+        assert!(!req("^0.0.3").matches(&v("0.0.4")));
+        assert!(req("~0.6.2").matches(&v("0.6.12")));
+        assert!(!req("~0.6.2").matches(&v("0.6.1")));
+        assert!(req(">=0.5.0 <0.7.0").matches(&v("0.6.12")));
+        assert!(req(">= 0.5.0 < 0.7.0").matches(&v("0.5.0")));
+        assert!(!req(">=0.5.0 <0.7.0").matches(&v("0.7.0")));
+        assert!(req("0.8").matches(&v("0.8.3")));
+        assert!(req("=0.5.16").matches(&v("0.5.16")));
+        assert!(!req("0.5.16").matches(&v("0.5.17")));
+        assert!(req(">0.5").matches(&v("0.6.0")) && !req(">0.5").matches(&v("0.5.9")));
+        assert!(req("<=0.6").matches(&v("0.6.12")));
+        assert!(req("0.5.0 - 0.6").matches(&v("0.6.12")));
+        assert!(req("^0.5.0 || ^0.8.0").matches(&v("0.8.1")));
+        assert!(!req("^0.5.0 || ^0.8.0").matches(&v("0.6.1")));
+        assert!("~>0.5".parse::<VersionReq>().is_err());
+        assert_eq!(v("0.8.17+commit.8df45f5f.Linux.g++"), Version::new(0, 8, 17));
+    }
+
+    #[test]
+    fn test_pragmas() {
+        let source = r#"
+            // pragma solidity ^0.4.0;
+            /* pragma solidity ^0.5.0; */
+            pragma solidity >=0.6.0 <0.8.0;
+            pragma experimental ABIEncoderV2;
+            contract C {}
+        "#;
+        assert_eq!(pragmas(source).unwrap(), vec![req(">=0.6.0 <0.8.0")]);
+        assert!(pragmas("contract C {}").unwrap().is_empty());
+    }
+}
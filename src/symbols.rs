@@ -0,0 +1,261 @@
+//! Symbols of the generated ABI helpers.
+//!
+//! Every ABI function gets a C helper encoding its calldata and another one
+//! decoding its return data, `abi_<C>_<fn>` and `abi_<C>_<fn>_decode`, plus
+//! `_struct` and `_decode_struct` variants for tuples. The first overload of a
+//! name, in ABI order, keeps the plain name, so adding an overload does not
+//! rename it. Later overloads have their canonical parameter types appended,
+//! `abi_ERC20_transfer__address_uint256`. Should any of the generated names
+//! still collide, the 4 byte selector is used instead.
+//!
+//! A mapping file, from contract name and canonical signature to symbol, pins
+//! or renames symbols, no two signatures of a contract may share a generated
+//! name. The mapping the IR, `contracts.h` and the bindings were generated
+//! with is written to `symbols.json`.
+
+use crate::ethabi::{AbiError, Contract, Function, Param, ParamType};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::fs;
+
+#[derive(Debug)]
+pub enum SymbolsError {
+    IOError(PathBuf, std::io::Error),
+    InvalidJson(PathBuf, serde_json::Error),
+    /// Contract, generated name and the two signatures whose symbols both generate it
+    DuplicateSymbol(String, String, String, String),
+}
+
+impl std::fmt::Display for SymbolsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::IOError(path, err) => write!(f, "Unable to read {}: {}", path.display(), err),
+            Self::InvalidJson(path, err) => write!(f, "Invalid symbol mapping {}: {}", path.display(), err),
+            Self::DuplicateSymbol(contract, symbol, first, second) => {
+                write!(f, "{}.{} and {}.{} both generate {}", contract, first, contract, second, symbol)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SymbolsError {}
+
+/// ABI helper symbols, by contract name and canonical signature
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(transparent)]
+pub struct Symbols {
+    contracts: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+impl Symbols {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Read a mapping file, whose entries take precedence over the naming scheme
+    pub fn load(path: &PathBuf) -> Result<Self, SymbolsError> {
+        let json = fs::read_to_string(path).map_err(|e| SymbolsError::IOError(path.clone(), e))?;
+        serde_json::from_str(&json).map_err(|e| SymbolsError::InvalidJson(path.clone(), e))
+    }
+
+    /// Canonical signature, `transfer(address,uint256)`
+    pub fn signature(fun: &Function) -> String {
+        let inputs = fun.inputs.iter().map(|p| p.kind.to_string()).collect::<Vec<_>>();
+        format!("{}({})", fun.name, inputs.join(","))
+    }
+
+    /// Identifier suffix telling overloads of `fun` apart, `address_uint256`
+    pub fn type_suffix(fun: &Function) -> String {
+        Self::params_suffix(&fun.inputs)
+    }
+
+    fn params_suffix(inputs: &[Param]) -> String {
+        if inputs.is_empty() {
+            return "void".to_owned();
+        }
+        inputs.iter().map(|p| Self::type_name(&p.kind)).collect::<Vec<_>>().join("_")
+    }
+
+    /// Name of custom error `error` in `contracts.h`. As for the ABI helpers,
+    /// overloads after the first get their parameter types appended,
+    /// `InsufficientBalance__uint256`.
+    pub fn error_name(error: &AbiError, suffixed: bool) -> String {
+        if suffixed {
+            format!("{}__{}", error.name, Self::params_suffix(&error.inputs))
+        } else {
+            error.name.clone()
+        }
+    }
+
+    fn type_name(kind: &ParamType) -> String {
+        match kind {
+            ParamType::Array(elem) => format!("{}array", Self::type_name(elem)),
+            ParamType::FixedArray(elem, n) => format!("{}array{}", Self::type_name(elem), n),
+            ParamType::Tuple(elems) => format!("tuple_{}_end",
+                elems.iter().map(|elem| Self::type_name(elem)).collect::<Vec<_>>().join("_")),
+            kind => kind.to_string(),
+        }
+    }
+
+    /// Name `fun` the ABI helper of `contract_name` is given by default
+    fn format_name(contract_name: &str, fun: &Function, suffixed: bool) -> String {
+        if suffixed {
+            format!("abi_{}_{}__{}", contract_name, fun.name, Self::type_suffix(fun))
+        } else {
+            format!("abi_{}_{}", contract_name, fun.name)
+        }
+    }
+
+    /// Every name generated from ABI helper `symbol`
+    fn generated_names(symbol: &str) -> [String; 4] {
+        [
+            symbol.to_owned(),
+            format!("{}_decode", symbol),
+            format!("{}_struct", symbol),
+            format!("{}_decode_struct", symbol),
+        ]
+    }
+
+    /// Reserve the names generated from `symbol` for `signature`, or return a
+    /// name already reserved and the signature holding it
+    fn claim(used: &mut BTreeMap<String, String>, symbol: &str, signature: &str) -> Result<(), (String, String)> {
+        let names = Self::generated_names(symbol);
+        if let Some(name) = names.iter().find(|name| used.contains_key(*name)) {
+            return Err((name.clone(), used[name].clone()));
+        }
+        for name in names.iter() {
+            used.insert(name.clone(), signature.to_owned());
+        }
+        Ok(())
+    }
+
+    /// Assign a symbol to every function of `contract_name` the mapping does not name yet
+    pub fn add_contract(&mut self, contract_name: &str, contract: &Contract) -> Result<(), SymbolsError> {
+        let symbols = self.contracts.entry(contract_name.to_owned()).or_default();
+        let duplicate = |name: String, first: String, second: &str| {
+            SymbolsError::DuplicateSymbol(contract_name.to_owned(), name, first, second.to_owned())
+        };
+        let mut used = BTreeMap::new();
+        for (signature, symbol) in symbols.iter() {
+            Self::claim(&mut used, symbol, signature).map_err(|(name, first)| duplicate(name, first, signature))?;
+        }
+        for (_name, funs) in &contract.functions {
+            for (idx, fun) in funs.iter().enumerate() {
+                let signature = Self::signature(fun);
+                if symbols.contains_key(&signature) {
+                    continue;
+                }
+                let mut symbol = Self::format_name(contract_name, fun, idx > 0);
+                if Self::claim(&mut used, &symbol, &signature).is_err() {
+                    let selector = fun.short_signature().iter().map(|b| format!("{:02x}", b)).collect::<String>();
+                    symbol = format!("abi_{}_{}_{}", contract_name, fun.name, selector);
+                    Self::claim(&mut used, &symbol, &signature).map_err(|(name, first)| duplicate(name, first, &signature))?;
+                }
+                symbols.insert(signature, symbol);
+            }
+        }
+        Ok(())
+    }
+
+    /// Symbol of the ABI helper of `fun`, the constructor included
+    pub fn get(&self, contract_name: &str, fun: &Function) -> String {
+        self.contracts.get(contract_name)
+            .and_then(|symbols| symbols.get(&Self::signature(fun)))
+            .cloned()
+            .unwrap_or_else(|| Self::format_name(contract_name, fun, false))
+    }
+
+    pub fn generate(&self, outdir: &PathBuf) -> std::io::Result<()> {
+        fs::create_dir_all(outdir.join("src"))?;
+        let contents = serde_json::to_string_pretty(self)?;
+        fs::write(outdir.join("src/symbols.json"), contents)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn test_overloads() {
+        let abi = r#"[
+            {"type":"function","name":"transfer","inputs":[{"name":"to","type":"address"},{"name":"value","type":"uint256"}],"outputs":[],"constant":false},
+            {"type":"function","name":"transfer","inputs":[{"name":"to","type":"address[]"}],"outputs":[],"constant":false},
+            {"type":"function","name":"balanceOf","inputs":[{"name":"owner","type":"address"}],"outputs":[],"constant":true}
+        ]"#;
+        let contract = Contract::load(abi.as_bytes()).unwrap();
+        let funs = &contract.functions["transfer"];
+        let single = funs.iter().find(|f| f.inputs.len() == 2).unwrap();
+        let batch = funs.iter().find(|f| f.inputs.len() == 1).unwrap();
+
+        // the first overload keeps the name it had before the second was added
+        let mut symbols = Symbols::new();
+        symbols.add_contract("Token", &contract).unwrap();
+        assert_eq!(symbols.get("Token", single), "abi_Token_transfer");
+        assert_eq!(symbols.get("Token", batch), "abi_Token_transfer__addressarray");
+        assert_eq!(symbols.get("Token", &contract.functions["balanceOf"][0]), "abi_Token_balanceOf");
+
+        let mut symbols = Symbols::new();
+        symbols.contracts.entry("Token".to_owned()).or_default()
+            .insert("transfer(address[])".to_owned(), "abi_Token_batch".to_owned());
+        symbols.add_contract("Token", &contract).unwrap();
+        assert_eq!(symbols.get("Token", single), "abi_Token_transfer");
+        assert_eq!(symbols.get("Token", batch), "abi_Token_batch");
+
+        symbols.contracts.get_mut("Token").unwrap()
+            .insert("approve(address,uint256)".to_owned(), "abi_Token_batch".to_owned());
+        let err = symbols.add_contract("Token", &contract).unwrap_err();
+        assert_eq!(err.to_string(), "Token.approve(address,uint256) and Token.transfer(address[]) both generate abi_Token_batch");
+
+        symbols.contracts.get_mut("Token").unwrap()
+            .insert("approve(address,uint256)".to_owned(), "abi_Token_batch_decode".to_owned());
+        let err = symbols.add_contract("Token", &contract).unwrap_err();
+        assert_eq!(err.to_string(), "Token.approve(address,uint256) and Token.transfer(address[]) both generate abi_Token_batch_decode");
+    }
+
+    #[test]
+    fn test_generated_names() {
+        let abi = r#"[
+            {"type":"function","name":"foo","inputs":[],"outputs":[],"constant":false},
+            {"type":"function","name":"foo_decode","inputs":[],"outputs":[],"constant":false}
+        ]"#;
+        let contract = Contract::load(abi.as_bytes()).unwrap();
+        let mut symbols = Symbols::new();
+        symbols.add_contract("C", &contract).unwrap();
+        let foo_decode = &contract.functions["foo_decode"][0];
+        let selector = foo_decode.short_signature().iter().map(|b| format!("{:02x}", b)).collect::<String>();
+        assert_eq!(symbols.get("C", &contract.functions["foo"][0]), "abi_C_foo");
+        assert_eq!(symbols.get("C", foo_decode), format!("abi_C_foo_decode_{}", selector));
+    }
+
+    #[test]
+    fn test_error_name() {
+        let abi = r#"[
+            {"type":"error","name":"Unauthorized","inputs":[]},
+            {"type":"error","name":"InsufficientBalance","inputs":[{"name":"required","type":"uint256"}]},
+            {"type":"error","name":"InsufficientBalance","inputs":[{"name":"token","type":"address"},{"name":"required","type":"uint256[]"}]}
+        ]"#;
+        let contract = Contract::load(abi.as_bytes()).unwrap();
+        let names = contract.errors.values()
+            .flat_map(|errors| errors.iter().enumerate().map(|(idx, error)| Symbols::error_name(error, idx > 0)))
+            .collect::<BTreeSet<_>>();
+        assert_eq!(names.into_iter().collect::<Vec<_>>(),
+            ["InsufficientBalance", "InsufficientBalance__address_uint256array", "Unauthorized"]);
+    }
+
+    #[test]
+    fn test_load() {
+        let root = std::env::temp_dir().join(format!("solenoid-symbols-{}", std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+        let path = root.join("symbols.json");
+        fs::write(&path, r#"{"Token": {"transfer(address[])": "abi_Token_batch"}}"#).unwrap();
+        assert_eq!(Symbols::load(&path).unwrap().contracts["Token"]["transfer(address[])"], "abi_Token_batch");
+
+        fs::write(&path, r#"{"Token": ["abi_Token_batch"]}"#).unwrap();
+        assert!(matches!(Symbols::load(&path), Err(SymbolsError::InvalidJson(..))));
+        fs::remove_dir_all(&root).unwrap();
+        assert!(matches!(Symbols::load(&path), Err(SymbolsError::IOError(..))));
+    }
+}
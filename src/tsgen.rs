@@ -0,0 +1,577 @@
+//! TypeScript client bindings generated from the ABI.
+//!
+//! `contracts.ts` exports one object per contract with, for every function,
+//! its selector and typed `encode`/`decode`, and for every event its topic and
+//! a typed `decode`. Selectors and topics are computed here, from the same
+//! `ethabi::Contract` the compiled dispatch and the C helpers are generated
+//! from. The module has no dependencies: integers are `bigint`, addresses hex
+//! strings and bytes `Uint8Array`. `instruction` builds the payload of a call
+//! to the program entrypoint.
+
+use crate::symbols::Symbols;
+use crate::ethabi::{Contract, Event, Function, ParamType, param_type::ParamType::*};
+use std::path::PathBuf;
+use std::fs;
+use std::string::String;
+use log::warn;
+
+/// Generate `contracts.ts` with typed encoders and decoders for the compiled contracts
+pub struct TsGenerator {
+    contracts: Vec<String>,
+}
+
+const PRELUDE: &str = r#"
+/** Integer argument, values are decoded as `bigint` */
+export type Int = bigint | number;
+
+/** Log emitted by a contract, topics and data in ABI byte order */
+export interface Log {
+    topics: Uint8Array[];
+    data: Uint8Array;
+}
+
+export interface AccountMeta<K> {
+    pubkey: K;
+    isSigner: boolean;
+    isWritable: boolean;
+}
+
+export interface Instruction<K> {
+    programId: K;
+    keys: AccountMeta<K>[];
+    data: Uint8Array;
+}
+
+/** Accounts read by the program entrypoint, see `src/entrypoint.c` */
+export interface Accounts<K> {
+    storage: K;
+    signer: K;
+    registry?: K;
+    systemProgram?: K;
+    shards?: K[];
+}
+
+/**
+ * Instruction calling the program entrypoint with `calldata`. The storage
+ * account comes first, then the address registry, the system program and the
+ * storage shards if any, and the signer last.
+ */
+export function instruction<K>(programId: K, accounts: Accounts<K>, calldata: Uint8Array): Instruction<K> {
+    const keys: AccountMeta<K>[] = [{ pubkey: accounts.storage, isSigner: false, isWritable: true }];
+    const shards = accounts.shards ?? [];
+    if (accounts.registry !== undefined) {
+        keys.push({ pubkey: accounts.registry, isSigner: false, isWritable: true });
+    }
+    if (shards.length > 0) {
+        if (accounts.registry === undefined || accounts.systemProgram === undefined) {
+            throw new Error("sharded storage needs the registry and system program accounts");
+        }
+        keys.push({ pubkey: accounts.systemProgram, isSigner: false, isWritable: false });
+        shards.forEach((shard) => keys.push({ pubkey: shard, isSigner: false, isWritable: true }));
+    }
+    keys.push({ pubkey: accounts.signer, isSigner: true, isWritable: true });
+    return { programId, keys, data: calldata };
+}
+
+/** Log from the fields passed to `sol_log_data`, topics first and data last */
+export function parseLogData(fields: Uint8Array[]): Log {
+    if (fields.length === 0) {
+        throw new Error("empty log");
+    }
+    return { topics: fields.slice(0, -1), data: fields[fields.length - 1] };
+}
+
+export function hexToBytes(hex: string): Uint8Array {
+    const digits = hex.startsWith("0x") ? hex.slice(2) : hex;
+    if (digits.length % 2 !== 0 || /[^0-9a-fA-F]/.test(digits)) {
+        throw new Error(`invalid hex string ${hex}`);
+    }
+    const out = new Uint8Array(digits.length / 2);
+    for (let i = 0; i < out.length; i++) {
+        out[i] = parseInt(digits.slice(2 * i, 2 * i + 2), 16);
+    }
+    return out;
+}
+
+export function bytesToHex(bytes: Uint8Array): string {
+    return "0x" + Array.from(bytes, (b) => b.toString(16).padStart(2, "0")).join("");
+}
+
+function concat(parts: Uint8Array[]): Uint8Array {
+    const out = new Uint8Array(parts.reduce((n, part) => n + part.length, 0));
+    let at = 0;
+    for (const part of parts) {
+        out.set(part, at);
+        at += part.length;
+    }
+    return out;
+}
+
+/** Components of a tuple type such as `(uint256,string)` */
+function components(tuple: string): string[] {
+    const inner = tuple.slice(1, -1);
+    const out: string[] = [];
+    let depth = 0;
+    let start = 0;
+    for (let i = 0; i < inner.length; i++) {
+        if (inner[i] === "(") {
+            depth++;
+        } else if (inner[i] === ")") {
+            depth--;
+        } else if (inner[i] === "," && depth === 0) {
+            out.push(inner.slice(start, i));
+            start = i + 1;
+        }
+    }
+    if (inner.length > 0) {
+        out.push(inner.slice(start));
+    }
+    return out;
+}
+
+/** Element type and length of an array type, the length is -1 if dynamic */
+function arrayType(ty: string): [string, number] | null {
+    if (!ty.endsWith("]")) {
+        return null;
+    }
+    const open = ty.lastIndexOf("[");
+    const len = ty.slice(open + 1, -1);
+    return [ty.slice(0, open), len === "" ? -1 : parseInt(len, 10)];
+}
+
+function isDynamic(ty: string): boolean {
+    if (ty === "bytes" || ty === "string") {
+        return true;
+    }
+    const array = arrayType(ty);
+    if (array !== null) {
+        return array[1] < 0 || isDynamic(array[0]);
+    }
+    return ty.startsWith("(") && components(ty).some(isDynamic);
+}
+
+/** Size of the head of `ty`: its encoding when static, an offset otherwise */
+function headSize(ty: string): number {
+    if (isDynamic(ty)) {
+        return 32;
+    }
+    const array = arrayType(ty);
+    if (array !== null) {
+        return array[1] * headSize(array[0]);
+    }
+    if (ty.startsWith("(")) {
+        return components(ty).reduce((n, t) => n + headSize(t), 0);
+    }
+    return 32;
+}
+
+function word(x: bigint): Uint8Array {
+    const out = new Uint8Array(32);
+    let v = BigInt.asUintN(256, x);
+    for (let i = 31; i >= 0; i--) {
+        out[i] = Number(v & BigInt(0xff));
+        v >>= BigInt(8);
+    }
+    return out;
+}
+
+function padded(bytes: Uint8Array): Uint8Array {
+    const out = new Uint8Array(Math.ceil(bytes.length / 32) * 32);
+    out.set(bytes);
+    return out;
+}
+
+function encodeValue(ty: string, value: unknown): Uint8Array {
+    const array = arrayType(ty);
+    if (array !== null) {
+        const [elem, len] = array;
+        const values = value as unknown[];
+        if (len < 0) {
+            return concat([word(BigInt(values.length)), encodeSequence(values.map(() => elem), values)]);
+        }
+        if (values.length !== len) {
+            throw new Error(`expected ${len} values for ${ty}, got ${values.length}`);
+        }
+        return encodeSequence(values.map(() => elem), values);
+    }
+    if (ty.startsWith("(")) {
+        return encodeSequence(components(ty), value as unknown[]);
+    }
+    if (ty === "bool") {
+        return word(value ? BigInt(1) : BigInt(0));
+    }
+    if (ty === "address") {
+        const address = hexToBytes(value as string);
+        if (address.length !== 20) {
+            throw new Error(`invalid address ${value}`);
+        }
+        const out = new Uint8Array(32);
+        out.set(address, 12);
+        return out;
+    }
+    if (ty === "string") {
+        const bytes = new TextEncoder().encode(value as string);
+        return concat([word(BigInt(bytes.length)), padded(bytes)]);
+    }
+    if (ty === "bytes") {
+        const bytes = value as Uint8Array;
+        return concat([word(BigInt(bytes.length)), padded(bytes)]);
+    }
+    if (ty.startsWith("bytes")) {
+        const bytes = value as Uint8Array;
+        const out = new Uint8Array(32);
+        out.set(bytes.subarray(0, parseInt(ty.slice(5), 10)));
+        return out;
+    }
+    return word(BigInt(value as Int));
+}
+
+/** Heads first, then the tails of the dynamic values */
+function encodeSequence(types: string[], values: unknown[]): Uint8Array {
+    if (types.length !== values.length) {
+        throw new Error(`expected ${types.length} values, got ${values.length}`);
+    }
+    const heads: Uint8Array[] = [];
+    const tails: Uint8Array[] = [];
+    let offset = types.reduce((n, ty) => n + headSize(ty), 0);
+    types.forEach((ty, i) => {
+        const encoded = encodeValue(ty, values[i]);
+        if (isDynamic(ty)) {
+            heads.push(word(BigInt(offset)));
+            tails.push(encoded);
+            offset += encoded.length;
+        } else {
+            heads.push(encoded);
+        }
+    });
+    return concat(heads.concat(tails));
+}
+
+function readWord(data: Uint8Array, at: number): bigint {
+    if (at + 32 > data.length) {
+        throw new Error("ABI data too short");
+    }
+    return BigInt(bytesToHex(data.subarray(at, at + 32)));
+}
+
+function readLength(data: Uint8Array, at: number): number {
+    const x = readWord(data, at);
+    if (x > BigInt(data.length)) {
+        throw new Error("ABI offset or length out of bounds");
+    }
+    return Number(x);
+}
+
+function decodeValue(ty: string, data: Uint8Array): unknown {
+    const array = arrayType(ty);
+    if (array !== null) {
+        const [elem, len] = array;
+        if (len < 0) {
+            const n = readLength(data, 0);
+            return decodeSequence(new Array(n).fill(elem), data.subarray(32));
+        }
+        return decodeSequence(new Array(len).fill(elem), data);
+    }
+    if (ty.startsWith("(")) {
+        return decodeSequence(components(ty), data);
+    }
+    if (ty === "string" || ty === "bytes") {
+        const len = readLength(data, 0);
+        if (32 + len > data.length) {
+            throw new Error("ABI data too short");
+        }
+        const bytes = data.slice(32, 32 + len);
+        return ty === "string" ? new TextDecoder().decode(bytes) : bytes;
+    }
+    const x = readWord(data, 0);
+    if (ty === "bool") {
+        return x !== BigInt(0);
+    }
+    if (ty === "address") {
+        return bytesToHex(data.subarray(12, 32));
+    }
+    if (ty.startsWith("bytes")) {
+        return data.slice(0, parseInt(ty.slice(5), 10));
+    }
+    if (ty.startsWith("int")) {
+        return BigInt.asIntN(parseInt(ty.slice(3), 10), x);
+    }
+    return x;
+}
+
+function decodeSequence(types: string[], data: Uint8Array): unknown[] {
+    let at = 0;
+    return types.map((ty) => {
+        const value = isDynamic(ty)
+            ? decodeValue(ty, data.subarray(readLength(data, at)))
+            : decodeValue(ty, data.subarray(at));
+        at += headSize(ty);
+        return value;
+    });
+}
+
+export function encodeParams(types: string[], values: unknown[]): Uint8Array {
+    return encodeSequence(types, values);
+}
+
+export function decodeParams(types: string[], data: Uint8Array): unknown[] {
+    return decodeSequence(types, data);
+}
+
+/** Calldata of a call to the function with 4 byte `selector` */
+export function encodeCall(selector: string, types: string[], values: unknown[]): Uint8Array {
+    return concat([hexToBytes(selector), encodeParams(types, values)]);
+}
+
+/**
+ * Decode the return data of a call. The program returns contract memory,
+ * which stores each word least significant byte first.
+ */
+export function decodeReturnData(types: string[], returnData: Uint8Array): unknown[] {
+    const data = returnData.slice();
+    for (let at = 0; at + 32 <= data.length; at += 32) {
+        data.subarray(at, at + 32).reverse();
+    }
+    return decodeParams(types, data);
+}
+
+/** Indexed event field, dynamic values only leave their hash */
+export function decodeTopic(ty: string, topic: Uint8Array | undefined): unknown {
+    if (topic === undefined) {
+        throw new Error("missing topic");
+    }
+    return isDynamic(ty) ? topic : decodeValue(ty, topic);
+}
+"#;
+
+const KEYWORDS: &[&str] = &[
+    "break", "case", "catch", "class", "const", "continue", "debugger", "default", "delete", "do",
+    "else", "enum", "export", "extends", "false", "finally", "for", "function", "if", "import",
+    "in", "instanceof", "new", "null", "return", "super", "switch", "this", "throw", "true", "try",
+    "typeof", "var", "void", "while", "with", "let", "static", "yield", "await", "implements",
+    "interface", "package", "private", "protected", "public", "arguments", "eval",
+];
+
+impl TsGenerator {
+    pub fn new() -> Self {
+        Self {
+            contracts: Vec::new(),
+        }
+    }
+
+    fn ident(name: &str, i: usize) -> String {
+        if name.is_empty() {
+            format!("arg{}", i)
+        } else if KEYWORDS.contains(&name) {
+            format!("{}_", name)
+        } else {
+            name.to_owned()
+        }
+    }
+
+    /// TypeScript type of a value, `input` for arguments
+    fn ts_type(kind: &ParamType, input: bool) -> String {
+        match kind {
+            Uint(_) | Int(_) => if input { "Int".to_owned() } else { "bigint".to_owned() },
+            Bool => "boolean".to_owned(),
+            Address | String => "string".to_owned(),
+            Bytes | FixedBytes(_) => "Uint8Array".to_owned(),
+            Array(elem) | FixedArray(elem, _) => format!("{}[]", Self::ts_type(elem, input)),
+            Tuple(params) => format!("[{}]", params.iter().map(|p| Self::ts_type(p, input)).collect::<Vec<_>>().join(", ")),
+        }
+    }
+
+    fn type_list(kinds: &[ParamType]) -> String {
+        format!("[{}]", kinds.iter().map(|kind| format!("\"{}\"", kind)).collect::<Vec<_>>().join(", "))
+    }
+
+    fn hex(bytes: &[u8]) -> String {
+        format!("0x{}", bytes.iter().map(|b| format!("{:02x}", b)).collect::<String>())
+    }
+
+    fn format_function(fun: &Function, key: &str) -> String {
+        let types = Self::type_list(&fun.input_param_types());
+        let signature = format!("{}({})", fun.name, fun.input_param_types().iter().map(|k| k.to_string()).collect::<Vec<_>>().join(","));
+        let selector = Self::hex(&fun.short_signature());
+        let args = fun.inputs.iter().enumerate()
+            .map(|(i, param)| (Self::ident(&param.name, i), Self::ts_type(&param.kind, true)))
+            .collect::<Vec<_>>();
+
+        let mut contents = String::new();
+        contents += &format!("    /** `{}` */\n", signature);
+        contents += &format!("    {}: {{\n", key);
+        contents += &format!("        signature: \"{}\",\n", signature);
+        contents += &format!("        selector: \"{}\",\n", selector);
+        contents += &format!("        stateMutability: \"{}\",\n", fun.state_mutability);
+        contents += &format!("        encode({}): Uint8Array {{\n",
+            args.iter().map(|(name, ty)| format!("{}: {}", name, ty)).collect::<Vec<_>>().join(", "));
+        contents += &format!("            return encodeCall(\"{}\", {}, [{}]);\n", selector, types,
+            args.iter().map(|(name, _)| name.clone()).collect::<Vec<_>>().join(", "));
+        contents += "        },\n";
+        if !fun.outputs.is_empty() {
+            let outputs = fun.outputs.iter().map(|p| p.kind.clone()).collect::<Vec<_>>();
+            let decoded = format!("decodeReturnData({}, returnData)", Self::type_list(&outputs));
+            let (ty, value) = match outputs.len() {
+                1 => {
+                    let ty = Self::ts_type(&outputs[0], false);
+                    let value = format!("{}[0] as {}", decoded, ty);
+                    (ty, value)
+                }
+                _ => {
+                    let ty = format!("[{}]", outputs.iter().map(|kind| Self::ts_type(kind, false)).collect::<Vec<_>>().join(", "));
+                    let value = format!("{} as {}", decoded, ty);
+                    (ty, value)
+                }
+            };
+            contents += &format!("        decode(returnData: Uint8Array): {} {{\n", ty);
+            contents += &format!("            return {};\n", value);
+            contents += "        },\n";
+        }
+        contents += "    },\n";
+        contents
+    }
+
+    /// Event entry and the type of its decoded fields
+    fn format_event(event: &Event, key: &str) -> (String, String) {
+        let signature = format!("{}({})", event.name, event.inputs.iter().map(|p| p.kind.to_string()).collect::<Vec<_>>().join(","));
+        let data_types = event.inputs.iter().filter(|p| !p.indexed).map(|p| p.kind.clone()).collect::<Vec<_>>();
+
+        let mut fields = Vec::new();
+        let mut values = Vec::new();
+        let (mut topic, mut word) = (1, 0);
+        for (i, param) in event.inputs.iter().enumerate() {
+            let name = Self::ident(&param.name, i);
+            let ty = if param.indexed && param.kind.is_dynamic() {
+                "Uint8Array".to_owned()
+            } else {
+                Self::ts_type(&param.kind, false)
+            };
+            let value = if param.indexed {
+                topic += 1;
+                format!("decodeTopic(\"{}\", log.topics[{}]) as {}", param.kind, topic - 1, ty)
+            } else {
+                word += 1;
+                format!("data[{}] as {}", word - 1, ty)
+            };
+            fields.push(format!("{}: {}", name, ty));
+            values.push(format!("{}: {}", name, value));
+        }
+        let fields = if fields.is_empty() { "{}".to_owned() } else { format!("{{ {} }}", fields.join("; ")) };
+
+        let mut contents = String::new();
+        contents += &format!("        /** `{}` */\n", signature);
+        contents += &format!("        {}: {{\n", key);
+        contents += &format!("            signature: \"{}\",\n", signature);
+        if !event.anonymous {
+            contents += &format!("            topic: \"{}\",\n", Self::hex(event.signature().as_bytes()));
+        }
+        contents += &format!("            decode(log: Log): {} {{\n", fields);
+        if !data_types.is_empty() {
+            contents += &format!("                const data = decodeParams({}, log.data);\n", Self::type_list(&data_types));
+        }
+        if values.is_empty() {
+            contents += "                return {};\n";
+        } else {
+            contents += &format!("                return {{ {} }};\n", values.join(", "));
+        }
+        contents += "            },\n";
+        contents += "        },\n";
+        (contents, fields)
+    }
+
+    pub fn add_contract(&mut self, contract_name: &str, contract: &Contract, symbols: &Symbols) {
+        let event_type = format!("{}Event", contract_name);
+        let prefix = format!("abi_{}_", contract_name);
+        let mut functions = String::new();
+        for (_name, funs) in &contract.functions {
+            for fun in funs {
+                // named after the ABI helper, so overloads are told apart as in contracts.h
+                let symbol = symbols.get(contract_name, fun);
+                let key = symbol.strip_prefix(&prefix).unwrap_or(&symbol).replace("__", "_");
+                if ["events", "decodeEvent"].contains(&key.as_str()) {
+                    warn!("{}.{} clashes with the TypeScript bindings and is skipped", contract_name, key);
+                    continue;
+                }
+                functions += &Self::format_function(fun, &key);
+            }
+        }
+
+        let mut events = String::new();
+        let mut variants = Vec::new();
+        let mut arms = String::new();
+        for (name, overloads) in &contract.events {
+            for (idx, event) in overloads.iter().enumerate() {
+                let key = if idx == 0 { name.clone() } else { format!("{}_{}", name, idx) };
+                let (entry, fields) = Self::format_event(event, &key);
+                events += &entry;
+                if event.anonymous {
+                    continue;
+                }
+                variants.push(format!("{{ name: \"{}\"; args: {} }}", key, fields));
+                arms += &format!("        if (topic === {}.events.{}.topic) {{\n", contract_name, key);
+                arms += &format!("            return {{ name: \"{}\", args: {}.events.{}.decode(log) }};\n", key, contract_name, key);
+                arms += "        }\n";
+            }
+        }
+
+        let mut contents = String::new();
+        contents += &format!("/** Events emitted by `{}` */\n", contract_name);
+        contents += &format!("export type {} = {};\n", event_type,
+            if variants.is_empty() { "never".to_owned() } else { variants.join(" | ") });
+        contents += "\n";
+        contents += &format!("export const {} = {{\n", contract_name);
+        contents += &functions;
+        contents += "    events: {\n";
+        contents += &events;
+        contents += "    },\n";
+        contents += "    /** Decode a log by its first topic, `null` if it is none of the events */\n";
+        contents += &format!("    decodeEvent(log: Log): {} | null {{\n", event_type);
+        if !arms.is_empty() {
+            contents += "        const topic = log.topics.length > 0 ? bytesToHex(log.topics[0]) : \"\";\n";
+        }
+        contents += &arms;
+        contents += "        return null;\n";
+        contents += "    },\n";
+        contents += "};\n";
+        self.contracts.push(contents);
+    }
+
+    pub fn generate(&self, outdir: &PathBuf) -> std::io::Result<()> {
+        fs::create_dir_all(outdir.join("src"))?;
+
+        let mut contents = String::new();
+        contents += &format!("/* automatically generated by solenoid {} */\n", env!("CARGO_PKG_VERSION"));
+        contents += PRELUDE;
+        for contract in &self.contracts {
+            contents += "\n";
+            contents += contract;
+        }
+        fs::write(outdir.join("src/contracts.ts"), contents)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_contract() {
+        let abi = r#"[
+            {"type":"function","name":"transfer","inputs":[{"name":"to","type":"address"},{"name":"value","type":"uint256"}],"outputs":[{"name":"","type":"bool"}],"constant":false},
+            {"type":"function","name":"transfer","inputs":[{"name":"to","type":"address[]"}],"outputs":[],"constant":false},
+            {"type":"event","name":"Transfer","inputs":[{"name":"from","type":"address","indexed":true},{"name":"to","type":"address","indexed":true},{"name":"value","type":"uint256","indexed":false}],"anonymous":false}
+        ]"#;
+        let contract = Contract::load(abi.as_bytes()).unwrap();
+        let mut symbols = Symbols::new();
+        symbols.add_contract("Token", &contract).unwrap();
+        let mut generator = TsGenerator::new();
+        generator.add_contract("Token", &contract, &symbols);
+        let contents = &generator.contracts[0];
+        assert!(contents.contains("    transfer: {\n"));
+        assert!(contents.contains("    transfer_addressarray: {\n"));
+        assert!(contents.contains("selector: \"0xa9059cbb\""));
+        assert!(contents.contains("encode(to: string, value: Int): Uint8Array"));
+        assert!(contents.contains("decode(returnData: Uint8Array): boolean"));
+        assert!(contents.contains("topic: \"0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef\""));
+    }
+}
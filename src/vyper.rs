@@ -0,0 +1,78 @@
+//! Compile Vyper sources with a local `vyper` binary.
+//!
+//! `vyper -f abi,bytecode,bytecode_runtime` prints the ABI and both bytecodes
+//! on a line each. A Vyper module holds a single contract, named after the file.
+
+use crate::solc::Contract;
+use std::path::Path;
+use std::process::Command;
+
+#[derive(Debug)]
+pub enum VyperError {
+    IOError(std::io::Error),
+    /// Compilation failed, with what vyper printed
+    Compilation(String),
+    InvalidOutput(String),
+}
+
+impl std::convert::From<std::io::Error> for VyperError {
+    fn from(err: std::io::Error) -> Self {
+        VyperError::IOError(err)
+    }
+}
+
+impl std::fmt::Display for VyperError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::IOError(err) => write!(f, "Unable to run vyper: {}", err),
+            Self::Compilation(err) => write!(f, "{}", err.trim_end()),
+            Self::InvalidOutput(output) => write!(f, "Invalid vyper output: {}", output),
+        }
+    }
+}
+
+impl std::error::Error for VyperError {}
+
+/// Contract of the source at `path` from vyper's output, named `<path>:<stem>`
+pub fn parse_output(path: &Path, output: &str) -> Result<(String, Contract), VyperError> {
+    let mut lines = output.lines().map(str::trim).filter(|line| !line.is_empty());
+    let (abi, bin, bin_runtime) = match (lines.next(), lines.next(), lines.next()) {
+        (Some(abi), Some(bin), Some(bin_runtime)) if abi.starts_with('[') => (abi, bin, bin_runtime),
+        _ => return Err(VyperError::InvalidOutput(output.to_owned())),
+    };
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    Ok((format!("{}:{}", path.display(), stem), Contract {
+        abi: abi.to_owned(),
+        bin: bin.trim_start_matches("0x").to_owned(),
+        bin_runtime: bin_runtime.trim_start_matches("0x").to_owned(),
+        ..Default::default()
+    }))
+}
+
+/// Compile the Vyper source at `path`
+pub fn compile(path: &Path) -> Result<(String, Contract), VyperError> {
+    let output = Command::new("vyper")
+        .arg("-f")
+        .arg("abi,bytecode,bytecode_runtime")
+        .arg(path)
+        .output()?;
+    if !output.status.success() {
+        return Err(VyperError::Compilation(String::from_utf8_lossy(&output.stderr).to_string()));
+    }
+    parse_output(path, &String::from_utf8_lossy(&output.stdout))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_output() {
+        let output = "[{\"stateMutability\": \"view\", \"type\": \"function\", \"name\": \"totalSupply\", \"inputs\": [], \"outputs\": [{\"name\": \"\", \"type\": \"uint256\"}]}]\n0x6080\n0x6001\n";
+        let (name, contract) = parse_output(Path::new("tests/contracts/vyper/ERC20Basic.vy"), output).unwrap();
+        assert_eq!(name, "tests/contracts/vyper/ERC20Basic.vy:ERC20Basic");
+        assert_eq!((contract.bin.as_str(), contract.bin_runtime.as_str()), ("6080", "6001"));
+        assert!(crate::ethabi::Contract::load(contract.abi.as_bytes()).unwrap().function("totalSupply").is_ok());
+        assert!(parse_output(Path::new("a.vy"), "0x6080\n").is_err());
+    }
+}
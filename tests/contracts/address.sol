@@ -15,4 +15,8 @@ contract SimpleAddress {
     function get() public view returns (address) {
         return storedData;
     }
+
+    function current() public view returns (address) {
+        return address(this);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,16 @@
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity ^0.7.0;
+
+contract Ctor {
+    uint32 private number;
+    string private name;
+
+    constructor(uint32 initnumber, string memory initname) {
+        number = initnumber;
+        name = initname;
+    }
+
+    function info() public view returns (uint32, string memory) {
+        return (number, name);
+    }
+}
@@ -0,0 +1,23 @@
+// SPDX-License-Identifier: GPL-3.0
+pragma solidity >=0.4.16 <0.8.0;
+
+contract Dynamic {
+    mapping (address => uint256) balances;
+    string greeting;
+    uint32 count;
+
+    function transfer(address to, uint256[] memory amounts) public {
+        for (uint i = 0; i < amounts.length; i++) {
+            balances[to] += amounts[i];
+        }
+    }
+
+    function greet(string memory message, uint32 times) public {
+        greeting = message;
+        count = times;
+    }
+
+    function info() public view returns (uint32, string memory) {
+        return (count, greeting);
+    }
+}
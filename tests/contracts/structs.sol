@@ -0,0 +1,25 @@
+// SPDX-License-Identifier: GPL-3.0
+pragma solidity >=0.6.0 <0.8.0;
+pragma experimental ABIEncoderV2;
+
+contract Structs {
+    struct Leg {
+        uint64 amount;
+        string memo;
+    }
+
+    struct Order {
+        address maker;
+        Leg[] legs;
+    }
+
+    function summarize(Order memory order) public pure returns (Leg memory total, address maker) {
+        if (order.legs.length > 0) {
+            total.memo = order.legs[0].memo;
+        }
+        for (uint i = 0; i < order.legs.length; i++) {
+            total.amount += order.legs[i].amount;
+        }
+        maker = order.maker;
+    }
+}
@@ -0,0 +1,70 @@
+# @version ^0.3.7
+
+name: public(constant(String[10])) = "ERC20Basic"
+symbol: public(constant(String[3])) = "BSC"
+decimals: public(constant(uint8)) = 18
+
+event Approval:
+    tokenOwner: indexed(address)
+    spender: indexed(address)
+    tokens: uint256
+
+event Transfer:
+    sender: indexed(address)
+    receiver: indexed(address)
+    tokens: uint256
+
+balances: HashMap[address, uint256]
+allowed: HashMap[address, HashMap[address, uint256]]
+totalSupply_: uint256
+
+
+@external
+def __init__():
+    self.totalSupply_ = 0xAAAA
+    self.balances[msg.sender] = self.totalSupply_
+
+
+@external
+@view
+def totalSupply() -> uint256:
+    return self.totalSupply_
+
+
+@external
+@view
+def balanceOf(tokenOwner: address) -> uint256:
+    return self.balances[tokenOwner]
+
+
+@external
+def transfer(receiver: address, numTokens: uint256) -> bool:
+    assert numTokens <= self.balances[msg.sender]
+    self.balances[msg.sender] -= numTokens
+    self.balances[receiver] += numTokens
+    log Transfer(msg.sender, receiver, numTokens)
+    return True
+
+
+@external
+def approve(delegate: address, numTokens: uint256) -> bool:
+    self.allowed[msg.sender][delegate] = numTokens
+    log Approval(msg.sender, delegate, numTokens)
+    return True
+
+
+@external
+@view
+def allowance(owner: address, delegate: address) -> uint256:
+    return self.allowed[owner][delegate]
+
+
+@external
+def transferFrom(owner: address, buyer: address, numTokens: uint256) -> bool:
+    assert numTokens <= self.balances[owner]
+    assert numTokens <= self.allowed[owner][msg.sender]
+    self.balances[owner] -= numTokens
+    self.allowed[owner][msg.sender] -= numTokens
+    self.balances[buyer] += numTokens
+    log Transfer(owner, buyer, numTokens)
+    return True
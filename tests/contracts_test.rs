@@ -1,9 +1,11 @@
 use std::process::Command;
+use hex::ToHex;
+use libsolenoid::ethabi::{encode, Address, Function, Token};
 
 #[cfg(test)]
 fn test_contract_factory(contract: &str, main_c: &str) -> String {
     Command::new("./target/debug/solenoid")
-        .args(&["--input", contract, "-o", "./test_contract"])
+        .args(&["compile", "--input", contract, "-o", "./test_contract"])
         .spawn().unwrap().wait();
 
     Command::new("cp").args(&[main_c, "./test_contract/src/main.c"]).spawn().unwrap().wait();
@@ -89,4 +91,94 @@ fn test_contract_erc20() {
 "#;
 
     assert_eq!(expected, output);
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_contract_erc20_vyper() {
+    let contract = "./tests/contracts/vyper/ERC20Basic.vy";
+    let main_c = "./tests/main/main_erc20.c";
+
+    let output = test_contract_factory(contract, main_c);
+
+    let expected = r#"000000000000000000000000000000000000000000000000000000000000AAAA
+0000000000000000000000000000000000000000000000000000000000000001
+000000000000000000000000000000000000000000000000000000000000AAA9
+"#;
+
+    assert_eq!(expected, output);
+}
+
+#[test]
+fn test_contract_dynamic() {
+    let contract = "./tests/contracts/dynamic.sol";
+    let main_c = "./tests/main/main_dynamic.c";
+
+    let output = test_contract_factory(contract, main_c);
+
+    let transfer: Function = "function transfer(address to, uint256[] amounts)".parse().unwrap();
+    let greet: Function = "function greet(string message, uint32 times)".parse().unwrap();
+    let transfer: String = transfer.encode_input(&[
+        Token::Address(Address::repeat_byte(0xBB)),
+        Token::Array(vec![Token::Uint(1.into()), Token::Uint(2.into()), Token::Uint(3.into())]),
+    ]).unwrap().to_hex();
+    let greet: String = greet.encode_input(&[
+        Token::String("hello, solana".to_owned()),
+        Token::Uint(7.into()),
+    ]).unwrap().to_hex();
+
+    assert_eq!(format!("{}\n{}\n7 hello, solana\n", transfer, greet), output);
+}
+
+#[test]
+fn test_contract_structs() {
+    let contract = "./tests/contracts/structs.sol";
+    let main_c = "./tests/main/main_structs.c";
+
+    let output = test_contract_factory(contract, main_c);
+
+    let summarize: Function = "function summarize((address maker, (uint64 amount, string memo)[] legs) order)".parse().unwrap();
+    let leg = |amount: u64, memo: &str| Token::Tuple(vec![Token::Uint(amount.into()), Token::String(memo.to_owned())]);
+    let summarize: String = summarize.encode_input(&[
+        Token::Tuple(vec![
+            Token::Address(Address::repeat_byte(0xAA)),
+            Token::Array(vec![leg(3, "first"), leg(4, "second")]),
+        ]),
+    ]).unwrap().to_hex();
+
+    assert_eq!(format!("{}\n7 first {}\n", summarize, "aa".repeat(20)), output);
+}
+
+#[test]
+fn test_contract_ctor() {
+    let contract = "./tests/contracts/ctor.sol";
+    let main_c = "./tests/main/main_ctor.c";
+
+    let output = test_contract_factory(contract, main_c);
+    assert_eq!("5 solenoid\n", output);
+}
+
+#[test]
+fn test_run_ctor() {
+    let ctor_args = encode(&[Token::Uint(5.into()), Token::String("solenoid".to_owned())]).to_hex::<String>();
+    let info: Function = "function info() returns (uint32, string)".parse().unwrap();
+    let calldata = info.encode_input(&[]).unwrap().to_hex::<String>();
+    let output = Command::new("./target/debug/solenoid")
+        .args(&["run", "--input", "./tests/contracts/ctor.sol", "Ctor", "--ctor-args", &ctor_args, "--calldata", &calldata])
+        .output().unwrap();
+
+    let expected = encode(&[Token::Uint(5.into()), Token::String("solenoid".to_owned())]).to_hex::<String>();
+    assert_eq!(format!("0x{}\n", expected), String::from_utf8_lossy(&output.stdout));
+}
+
+#[test]
+fn test_run_address() {
+    let current: Function = "function current() returns (address)".parse().unwrap();
+    let calldata = current.encode_input(&[]).unwrap().to_hex::<String>();
+    let address = "cc".repeat(20);
+    let output = Command::new("./target/debug/solenoid")
+        .args(&["run", "--input", "./tests/contracts/address.sol", "SimpleAddress", "--address", &address, "--calldata", &calldata])
+        .output().unwrap();
+
+    let expected = encode(&[Token::Address(Address::repeat_byte(0xCC))]).to_hex::<String>();
+    assert_eq!(format!("0x{}\n", expected), String::from_utf8_lossy(&output.stdout));
+}
@@ -3,7 +3,7 @@ use primitive_types::U256;
 use uint::rustc_hex::FromHex;
 
 use inkwell::context::Context;
-use libsolenoid::evm::{self, Instruction};
+use libsolenoid::evm::{self, asm, Instruction};
 use libsolenoid::compiler::Compiler;
 
 #[cfg(test)]
@@ -353,4 +353,23 @@ fn test_div() {
         Instruction::Push(vec![0xAA, 0x00]),
         Instruction::Div,
     ]), &[vec![0xAA, 0x00]]);
-}
\ No newline at end of file
+}
+
+/// Programs in `tests/easm`, each with its expected stack as `; expect:` hex words
+#[test]
+fn test_easm() {
+    let mut paths = std::fs::read_dir("./tests/easm").unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect::<Vec<_>>();
+    paths.sort();
+    for path in paths {
+        let text = std::fs::read_to_string(&path).unwrap();
+        let instrs = asm::parse(&text).unwrap_or_else(|e| panic!("{}: {}", path.display(), e));
+        let expected = text.lines()
+            .filter_map(|line| line.trim().strip_prefix("; expect:"))
+            .flat_map(|line| line.split_whitespace())
+            .map(|word| word.from_hex().unwrap())
+            .collect::<Vec<Vec<u8>>>();
+        assert_stack(&compile_and_run(&instrs), &expected);
+    }
+}
@@ -0,0 +1,27 @@
+#include "rt.h"
+#include "contracts.h"
+
+int main() {
+    i8 caller[20] = {0};
+    long offset = 0, length = 0;
+    i8 tx_ctor[4096] = {0};
+    int sz_ctor = 0;
+    char* name = "solenoid";
+    abi_Ctor_constructor(tx_ctor, &sz_ctor, 5, (i8*)name, strlen(name));
+    Ctor_constructor(tx_ctor, sz_ctor, &offset, &length, storage, caller);
+
+    i8 tx_info[1024] = {0};
+    int sz_info = 0;
+    abi_Ctor_info(tx_info, &sz_info);
+    offset = length = 0;
+    Ctor_runtime(tx_info, sz_info, &offset, &length, storage, caller);
+
+    int number = 0;
+    i8* info_name = NULL;
+    long info_name_len = 0;
+    if (abi_Ctor_info_decode(Ctor_mem + offset, length, &number, &info_name, &info_name_len)) {
+        printf("%d %.*s\n", number, (int)info_name_len, info_name);
+    }
+
+    return 0;
+}
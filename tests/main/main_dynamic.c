@@ -0,0 +1,52 @@
+#include "rt.h"
+#include "contracts.h"
+
+void print_hex(i8* buf, int sz) {
+    for (int i = 0; i < sz; i++) {
+        printf("%02x", buf[i]);
+    }
+    printf("\n");
+}
+
+int main() {
+    i8 to[20] = {0};
+    memset(to, 0xBB, 20);
+    i8 amounts[96] = {0};
+    pad_int(amounts, 1);
+    pad_int(amounts + 32, 2);
+    pad_int(amounts + 64, 3);
+
+    i8 tx_transfer[1024] = {0};
+    int sz_transfer = 0;
+    abi_Dynamic_transfer(tx_transfer, &sz_transfer, to, amounts, 3);
+    print_hex(tx_transfer, sz_transfer);
+
+    i8 tx_greet[1024] = {0};
+    int sz_greet = 0;
+    char* message = "hello, solana";
+    abi_Dynamic_greet(tx_greet, &sz_greet, (i8*)message, strlen(message), 7);
+    print_hex(tx_greet, sz_greet);
+
+    i8 caller[20] = {0};
+    long offset = 0, length = 0;
+    i8 tx_ctor[4096] = {0};
+    int sz_ctor = 0;
+    abi_Dynamic_constructor(tx_ctor, &sz_ctor);
+    Dynamic_constructor(tx_ctor, sz_ctor, &offset, &length, storage, caller);
+    Dynamic_runtime(tx_greet, sz_greet, &offset, &length, storage, caller);
+
+    i8 tx_info[1024] = {0};
+    int sz_info = 0;
+    abi_Dynamic_info(tx_info, &sz_info);
+    offset = length = 0;
+    Dynamic_runtime(tx_info, sz_info, &offset, &length, storage, caller);
+
+    int times = 0;
+    i8* greeting = NULL;
+    long greeting_len = 0;
+    if (abi_Dynamic_info_decode(Dynamic_mem + offset, length, &times, &greeting, &greeting_len)) {
+        printf("%d %.*s\n", times, (int)greeting_len, greeting);
+    }
+
+    return 0;
+}
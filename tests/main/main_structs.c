@@ -0,0 +1,47 @@
+#include "rt.h"
+#include "contracts.h"
+
+void print_hex(const i8* buf, int sz) {
+    for (int i = 0; i < sz; i++) {
+        printf("%02x", buf[i]);
+    }
+    printf("\n");
+}
+
+int main() {
+    char* first = "first";
+    char* second = "second";
+    struct Structs_Leg legs[2] = {
+        { 3, { (const i8*)first, strlen(first) } },
+        { 4, { (const i8*)second, strlen(second) } },
+    };
+    struct Structs_Order order = { {0}, { (const i8*)legs, 2 } };
+    memset(order.maker, 0xAA, 20);
+
+    int sz_summarize = 0;
+    abi_Structs_summarize_struct(NULL, &sz_summarize, &order);
+    i8* tx_summarize = calloc(sz_summarize, 1);
+    abi_Structs_summarize_struct(tx_summarize, &sz_summarize, &order);
+    print_hex(tx_summarize, sz_summarize);
+
+    i8 caller[20] = {0};
+    long offset = 0, length = 0;
+    i8 tx_ctor[4096] = {0};
+    int sz_ctor = 0;
+    abi_Structs_constructor(tx_ctor, &sz_ctor);
+    Structs_constructor(tx_ctor, sz_ctor, &offset, &length, storage, caller);
+    offset = length = 0;
+    Structs_runtime(tx_summarize, sz_summarize, &offset, &length, storage, caller);
+
+    i8 scratch[1024];
+    abi_arena arena = { scratch, sizeof(scratch) };
+    struct Structs_Leg total;
+    i8 maker[20] = {0};
+    if (abi_Structs_summarize_decode_struct(Structs_mem + offset, length, &total, maker, &arena)) {
+        printf("%ld %.*s ", total.amount, (int)total.memo.len, total.memo.ptr);
+        print_hex(maker, 20);
+    }
+
+    free(tx_summarize);
+    return 0;
+}